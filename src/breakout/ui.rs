@@ -1,27 +1,45 @@
-use super::{Game, HorizontalMovement, BOTTOM_WALL, LEFT_WALL, RIGHT_WALL, TOP_WALL};
+use super::{
+    Game, HorizontalMovement, BOTTOM_WALL, HEIGHT, LEFT_WALL, RIGHT_WALL, TOP_WALL, WDITH,
+};
 use crate::components::application::MiniApp;
 use crate::components::{
-    button::{Pos, UIButton},
-    color::Color,
+    button::{ButtonStyle, UIButton},
+    input::Action,
+    layout::{self, Anchor, GameLayout},
+    pause::{PauseEvent, PauseMenu},
+    render::Renderer,
+    widgets::{Focus, Response},
 };
-use crate::menu::{config::Config, highscores::HighScores, ui::TOP_PAD};
-use crate::{rgb, Event};
-use piston_window::*;
+use crate::menu::{config::Config, highscores::HighScores};
+use crate::Event;
 
 pub struct BreakoutApp {
     state: Game,
-    hover_pos: [f64; 2],
+    focus: Focus,
+    reset: UIButton,
+    // time not yet simulated, in seconds
+    lag: f64,
+    pause: Option<PauseMenu>,
+    // Quit was picked in the pause menu
+    quit: bool,
 }
 
 impl BreakoutApp {
     pub fn new() -> Self {
         BreakoutApp {
             state: Game::new(),
-            hover_pos: [0.0, 0.0],
+            focus: Focus::new(),
+            reset: UIButton::new("     Reset", ButtonStyle::Danger, 24),
+            lag: 0.0,
+            pause: None,
+            quit: false,
         }
     }
 }
 
+// the game is simulated in fixed steps
+const TICK: f64 = 1.0 / 60.0;
+
 // const BACKGROUND_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
 // const PADDLE_COLOR: Color = Color::rgb(0.3, 0.3, 0.7);
 // const BALL_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
@@ -29,118 +47,132 @@ impl BreakoutApp {
 // const WALL_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
 
 impl MiniApp for BreakoutApp {
-    fn render(
+    fn handle_event(
         &mut self,
-        window: &mut PistonWindow,
         event: &Event,
-        glyphs: &mut Glyphs,
+        size: [f64; 2],
         config: &mut Config,
         _highscores: &mut HighScores,
     ) {
-        if let Some([cx, cy]) = event.mouse_cursor_args() {
-            self.hover_pos = [cx, cy];
+        // the pause menu takes every event while it is open
+        if let Some(pause) = self.pause.as_mut() {
+            match pause.handle_event(event, size, &config.options.bindings) {
+                PauseEvent::Resume => self.pause = None,
+                PauseEvent::Restart => {
+                    self.state.reset();
+                    self.pause = None;
+                }
+                PauseEvent::Quit => self.quit = true,
+                PauseEvent::Consumed => (),
+            }
+            return;
         }
 
-        // init buttons
-        let mut reset_button = UIButton::new(
-            "     Reset",
-            Color::RESET,
-            Color::WHITE,
-            24,
-            Pos { x: 791.2, y: 135.2 },
-            160.0,
-            48.0,
-        );
-
-        let left_click = event.press_args() == Some(Button::Mouse(MouseButton::Left));
-
         // handle button events
-        if reset_button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-            if left_click {
-                self.state.reset();
-            } else {
-                reset_button.width += 6.0;
-                reset_button.pos.x -= 3.0;
-                reset_button.height += 6.0;
-                reset_button.pos.y -= 3.0;
-                reset_button.size += 1;
-            }
+        let reset = GameLayout::new(size).buttons(1)[0];
+        let action = config.options.bindings.action(event);
+        let response = self.focus.dispatch(event, &mut [(&mut self.reset, reset)]);
+        if response == Some((0, Response::Clicked)) || action == Some(Action::Reset) {
+            self.state.reset();
         }
 
-        if let Some(Button::Keyboard(press)) = event.press_args() {
-            match press {
-                Key::Left => {
-                    self.state.update(Some(HorizontalMovement::Left));
-                }
-                Key::Right => {
-                    self.state.update(Some(HorizontalMovement::Right));
-                }
-                _ => (),
+        match action {
+            Some(Action::Left) => {
+                self.state.update(Some(HorizontalMovement::Left));
             }
+            Some(Action::Right) => {
+                self.state.update(Some(HorizontalMovement::Right));
+            }
+            Some(Action::Pause | Action::Back) => self.pause(),
+            _ => (),
         }
+    }
 
-        window.draw_2d(event, |c, g, device| {
-            clear(
-                if config.options.white_theme {
-                    Color::WHITE
-                } else {
-                    Color::DARK_THEME_BG
-                },
-                g,
-            );
-
-            // draw buttons
-            reset_button.draw(&c, g, glyphs);
-
-            let ctx = c.trans(0.0, TOP_PAD);
-
-            // Draw Walls
-            Rectangle::new_border(Color::BLACK, 1.0).draw(
-                [
-                    LEFT_WALL as f64,
-                    TOP_WALL as f64,
-                    (RIGHT_WALL - LEFT_WALL) as f64,
-                    (BOTTOM_WALL - TOP_WALL) as f64,
-                ],
-                &Default::default(),
-                ctx.transform,
-                g,
-            );
-
-            // Draw bricks
-            for brick in &self.state.bricks {
-                let rect = [
-                    (brick.x + brick.w) as f64,
-                    (brick.y + brick.h) as f64,
-                    brick.w as f64,
-                    brick.h as f64,
-                ];
-                rectangle(rgb!(200, 200, 100), rect, ctx.transform, g);
-            }
+    fn takes_keyboard(&self) -> bool {
+        self.pause.is_some()
+    }
 
-            // Draw paddle
-            let paddle_rect = [
-                (self.state.paddle.x + self.state.paddle.w) as f64,
-                (self.state.paddle.y + self.state.paddle.h) as f64,
-                self.state.paddle.w as f64,
-                self.state.paddle.h as f64,
-            ];
-            rectangle(rgb!(150, 150, 150), paddle_rect, ctx.transform, g);
-
-            // Draw ball
-            let ball_rect = [
-                (self.state.ball.rect.x + self.state.ball.rect.w) as f64,
-                (self.state.ball.rect.y + self.state.ball.rect.h) as f64,
-                self.state.ball.rect.w as f64,
-                self.state.ball.rect.h as f64,
-            ];
-            rectangle(rgb!(100, 200, 100), ball_rect, ctx.transform, g);
+    // the ball never stops, so Back always pauses
+    fn takes_back(&self) -> bool {
+        true
+    }
+
+    fn pause(&mut self) {
+        if self.pause.is_none() {
+            self.pause = Some(PauseMenu::new());
+        }
+    }
 
-            // Run
+    fn quit_requested(&mut self) -> bool {
+        std::mem::take(&mut self.quit)
+    }
+
+    fn update(&mut self, dt: f64, _config: &mut Config, _highscores: &mut HighScores) {
+        if self.pause.is_some() {
+            return;
+        }
+        self.lag += dt;
+        while self.lag >= TICK {
             self.state.update(None);
+            self.lag -= TICK;
+        }
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, config: &Config, _highscores: &HighScores) {
+        r.clear(config.theme.game_background);
+
+        // draw buttons
+        let reset = GameLayout::new(r.size()).buttons(1)[0];
+        self.focus.draw(r, &config.theme, &[(&self.reset, reset)]);
+
+        // the field keeps its size, centred below the taskbar
+        let field = layout::content(r.size()).anchor(Anchor::Top, WDITH as f64, HEIGHT as f64);
+        r.push_translate(field.x, field.y);
+
+        // Draw Walls
+        r.border_rectangle(
+            config.theme.breakout.wall,
+            1.0,
+            [
+                LEFT_WALL as f64,
+                TOP_WALL as f64,
+                (RIGHT_WALL - LEFT_WALL) as f64,
+                (BOTTOM_WALL - TOP_WALL) as f64,
+            ],
+        );
 
-            // Update glyphs before rendering
-            glyphs.factory.encoder.flush(device);
-        });
+        // Draw bricks
+        for brick in &self.state.bricks {
+            let rect = [
+                (brick.x + brick.w) as f64,
+                (brick.y + brick.h) as f64,
+                brick.w as f64,
+                brick.h as f64,
+            ];
+            r.rectangle(config.theme.breakout.brick, rect);
+        }
+
+        // Draw paddle
+        let paddle_rect = [
+            (self.state.paddle.x + self.state.paddle.w) as f64,
+            (self.state.paddle.y + self.state.paddle.h) as f64,
+            self.state.paddle.w as f64,
+            self.state.paddle.h as f64,
+        ];
+        r.rectangle(config.theme.breakout.paddle, paddle_rect);
+
+        // Draw ball
+        let ball_rect = [
+            (self.state.ball.rect.x + self.state.ball.rect.w) as f64,
+            (self.state.ball.rect.y + self.state.ball.rect.h) as f64,
+            self.state.ball.rect.w as f64,
+            self.state.ball.rect.h as f64,
+        ];
+        r.rectangle(config.theme.breakout.ball, ball_rect);
+        r.pop_transform();
+
+        if let Some(pause) = &self.pause {
+            pause.draw(r, config);
+        }
     }
 }
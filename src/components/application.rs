@@ -1,18 +1,62 @@
-use piston_window::{Event, Glyphs, PistonWindow};
+use piston_window::{Event, PistonWindow};
 
+use super::render::Renderer;
 use crate::menu::{config::Config, highscores::HighScores};
 
 pub trait MiniApp {
-    /// Called to draw on the screen.
-    fn render(
+    /// Called when the app's tab is opened.
+    fn on_enter(
+        &mut self,
+        _window: &mut PistonWindow,
+        _config: &mut Config,
+        _highscores: &mut HighScores,
+    ) {
+    }
+
+    /// Called when the app's tab is closed.
+    fn on_exit(&mut self, _config: &mut Config, _highscores: &mut HighScores) {}
+
+    /// Called for every input event while the app is open,
+    /// `size` is the size of the window the app is drawn in.
+    fn handle_event(
         &mut self,
-        window: &mut PistonWindow,
         event: &Event,
-        glyphs: &mut Glyphs,
+        size: [f64; 2],
         config: &mut Config,
         highscores: &mut HighScores,
     );
+
+    /// True while the app needs every key, like when a name is typed in.
+    /// The menu's keyboard shortcuts are off then.
+    fn takes_keyboard(&self) -> bool {
+        false
+    }
+
+    /// True while the app uses Back itself, like a running game opening its
+    /// pause menu. The menu doesn't go back to the games then.
+    fn takes_back(&self) -> bool {
+        false
+    }
+
+    /// Stops a real-time game until the player resumes it. Called when the
+    /// window loses focus and before the app's tab is closed.
+    fn pause(&mut self) {}
+
+    /// True once the player asked to leave the app, the menu opens the games then.
+    /// Only returns true once per request.
+    fn quit_requested(&mut self) -> bool {
+        false
+    }
+
+    /// Called on every update tick, `dt` is the time since the last tick in seconds.
+    fn update(&mut self, _dt: f64, _config: &mut Config, _highscores: &mut HighScores) {}
+
+    /// Called to draw on the screen.
+    fn draw(&self, r: &mut dyn Renderer, config: &Config, highscores: &HighScores);
 }
 
 pub const DEFAULT_WIDTH: u32 = 1024;
 pub const DEFAULT_HEIGHT: u32 = 576;
+/// The window can't be made smaller than this, the settings tab needs the height
+pub const MIN_WIDTH: u32 = 900;
+pub const MIN_HEIGHT: u32 = 576;
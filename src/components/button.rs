@@ -1,86 +1,88 @@
-use piston_window::{rectangle, text::Text, types::Color, Context, G2d, Glyphs, Transformed};
+use super::{
+    color::Color,
+    input::Action,
+    layout::Rect,
+    render::Renderer,
+    widgets::{left_click, Response, Widget, WidgetState},
+};
+use crate::menu::theme::Theme;
+use piston_window::Event;
 
 pub struct Pos {
     pub x: f64,
     pub y: f64,
 }
 
+/// Which theme colours a button is drawn with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ButtonStyle {
+    /// Filled with the button colour, grows when hovered
+    Primary,
+    /// Like `Primary` for buttons that reset or delete something
+    Danger,
+    /// Only text, the background lights up when hovered, used for tabs and menus
+    Flat,
+}
+
 pub struct UIButton {
     pub text: String,
-    pub color: Color,
-    pub text_color: Color,
+    pub style: ButtonStyle,
     pub size: u32,
-    pub pos: Pos,
-    pub width: f64,
-    pub height: f64,
 }
 
 impl UIButton {
-    pub fn new(
-        text: &str,
-        color: Color,
-        text_color: Color,
-        size: u32,
-        pos: Pos,
-        width: f64,
-        height: f64,
-    ) -> Self {
+    pub fn new(text: &str, style: ButtonStyle, size: u32) -> Self {
         UIButton {
             text: text.to_string(),
-            color,
-            text_color,
+            style,
             size,
-            pos,
-            width,
-            height,
         }
     }
+}
 
-    pub fn draw(&self, ctx: &Context, graphics: &mut G2d, glyphs: &mut Glyphs) {
-        rectangle(
-            self.color,
-            [self.pos.x, self.pos.y, self.width, self.height],
-            ctx.transform,
-            graphics,
-        );
+impl Widget for UIButton {
+    fn handle_event(&mut self, event: &Event, _rect: Rect, state: WidgetState) -> Response {
+        let pressed = Action::from_event(event) == Some(Action::Confirm);
+        if (state.hovered && left_click(event)) || (state.focused && pressed) {
+            Response::Clicked
+        } else {
+            Response::Ignored
+        }
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, rect: Rect, theme: &Theme, state: WidgetState) {
+        let (color, text_color) = match self.style {
+            ButtonStyle::Primary => (theme.button, theme.button_text),
+            ButtonStyle::Danger => (theme.danger, theme.button_text),
+            ButtonStyle::Flat if state.hovered => (theme.hover, theme.text),
+            ButtonStyle::Flat => (Color::CLEAR, theme.text),
+        };
+
+        // filled buttons grow a little under the mouse
+        let (rect, size) = if state.hovered && self.style != ButtonStyle::Flat {
+            (rect.pad(-3.0), self.size + 1)
+        } else {
+            (rect, self.size)
+        };
+
+        r.rectangle(color, rect.to_array());
         draw_text(
-            ctx,
-            graphics,
-            glyphs,
-            self.text_color,
+            r,
+            text_color,
             Pos {
-                x: self.pos.x + self.width / 8.5 - self.text.len() as f64,
-                y: self.pos.y + self.height / 1.5,
+                x: rect.x + rect.w / 8.5 - self.text.len() as f64,
+                y: rect.y + rect.h / 1.5,
             },
             &self.text,
-            self.size,
+            size,
         );
     }
 
-    pub fn is_over(&self, x: f64, y: f64) -> bool {
-        x > self.pos.x
-            && x < self.pos.x + self.width
-            && y > self.pos.y
-            && y < self.pos.y + self.height
+    fn focus_on_click(&self) -> bool {
+        false
     }
 }
 
-pub fn draw_text(
-    ctx: &Context,
-    graphics: &mut G2d,
-    glyphs: &mut Glyphs,
-    color: [f32; 4],
-    pos: Pos,
-    text: &str,
-    font_size: u32,
-) {
-    Text::new_color(color, font_size)
-        .draw(
-            text,
-            glyphs,
-            &ctx.draw_state,
-            ctx.transform.trans(pos.x as f64, pos.y as f64),
-            graphics,
-        )
-        .unwrap();
+pub fn draw_text(r: &mut dyn Renderer, color: [f32; 4], pos: Pos, text: &str, font_size: u32) {
+    r.text(color, [pos.x, pos.y], text, font_size);
 }
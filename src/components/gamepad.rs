@@ -0,0 +1,154 @@
+/*!
+    Reads controllers with gilrs and turns them into piston events, so the
+    games and the menu get them like any other input. Pushing the left stick
+    presses the D-pad button it points to.
+    Only built with the `gamepad` feature, gilrs needs libudev on Linux.
+*/
+
+use std::collections::HashMap;
+
+use gilrs::{Axis, Button as PadButton, EventType, Gilrs};
+use piston_window::{Button, ButtonArgs, ButtonState, ControllerButton, Event, Input};
+
+use super::input::{
+    BUTTON_EAST, BUTTON_NORTH, BUTTON_SOUTH, BUTTON_START, DPAD_DOWN, DPAD_LEFT, DPAD_RIGHT,
+    DPAD_UP,
+};
+
+// How far the stick has to be pushed to press a direction
+const DEAD_ZONE: f32 = 0.5;
+
+pub struct Gamepads {
+    // None when controllers can't be read on this system
+    gilrs: Option<Gilrs>,
+    sticks: HashMap<u32, Stick>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                eprintln!("Could not read controllers: {}", err);
+                None
+            }
+        };
+        Gamepads {
+            gilrs,
+            sticks: HashMap::new(),
+        }
+    }
+
+    /// The controller events since the last call
+    pub fn poll(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        let gilrs = match self.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => return events,
+        };
+
+        while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+            let id = usize::from(id) as u32;
+            match event {
+                EventType::ButtonPressed(button, _) => {
+                    events.extend(button_code(button).map(|b| press(id, b, ButtonState::Press)));
+                }
+                EventType::ButtonReleased(button, _) => {
+                    events.extend(button_code(button).map(|b| press(id, b, ButtonState::Release)));
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    let stick = self.sticks.entry(id).or_default();
+                    let (released, pressed) = stick.set(axis, value);
+                    events.extend(released.map(|b| press(id, b, ButtonState::Release)));
+                    events.extend(pressed.map(|b| press(id, b, ButtonState::Press)));
+                }
+                EventType::Disconnected => {
+                    self.sticks.remove(&id);
+                }
+                _ => (),
+            }
+        }
+        events
+    }
+}
+
+// Where the left stick is and which D-pad button it holds down
+#[derive(Debug, Default)]
+struct Stick {
+    x: f32,
+    y: f32,
+    held: Option<u8>,
+}
+
+impl Stick {
+    /// Moves the stick, returns the D-pad button let go and the one pressed
+    fn set(&mut self, axis: Axis, value: f32) -> (Option<u8>, Option<u8>) {
+        match axis {
+            Axis::LeftStickX => self.x = value,
+            Axis::LeftStickY => self.y = value,
+            _ => return (None, None),
+        }
+
+        // the direction the stick is pushed furthest in, y points up
+        let direction = if self.x.abs().max(self.y.abs()) < DEAD_ZONE {
+            None
+        } else if self.x.abs() > self.y.abs() {
+            Some(if self.x > 0.0 { DPAD_RIGHT } else { DPAD_LEFT })
+        } else {
+            Some(if self.y > 0.0 { DPAD_UP } else { DPAD_DOWN })
+        };
+        if direction == self.held {
+            return (None, None);
+        }
+        let released = self.held;
+        self.held = direction;
+        (released, direction)
+    }
+}
+
+// The SDL number of a controller button
+fn button_code(button: PadButton) -> Option<u8> {
+    match button {
+        PadButton::South => Some(BUTTON_SOUTH),
+        PadButton::East => Some(BUTTON_EAST),
+        PadButton::North => Some(BUTTON_NORTH),
+        PadButton::Start => Some(BUTTON_START),
+        PadButton::DPadUp => Some(DPAD_UP),
+        PadButton::DPadDown => Some(DPAD_DOWN),
+        PadButton::DPadLeft => Some(DPAD_LEFT),
+        PadButton::DPadRight => Some(DPAD_RIGHT),
+        _ => None,
+    }
+}
+
+fn press(id: u32, button: u8, state: ButtonState) -> Event {
+    Event::Input(
+        Input::Button(ButtonArgs {
+            state,
+            button: Button::Controller(ControllerButton::new(id, button)),
+            scancode: None,
+        }),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_presses_once_per_push() {
+        let mut stick = Stick::default();
+        assert_eq!(stick.set(Axis::LeftStickX, 0.3), (None, None));
+        assert_eq!(stick.set(Axis::LeftStickX, 0.8), (None, Some(DPAD_RIGHT)));
+        assert_eq!(stick.set(Axis::LeftStickX, 0.9), (None, None));
+
+        // turning the stick lets go of the old direction
+        stick.set(Axis::LeftStickX, 0.0);
+        assert_eq!(
+            stick.set(Axis::LeftStickY, -1.0),
+            (Some(DPAD_RIGHT), Some(DPAD_DOWN))
+        );
+        assert_eq!(stick.set(Axis::LeftStickY, 0.1), (Some(DPAD_DOWN), None));
+    }
+}
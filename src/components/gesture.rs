@@ -0,0 +1,204 @@
+/*!
+    Swipes with the mouse or a finger. Dragging across a board moves the
+    game the way a direction key would, a short press is a tap on the spot
+    it was made.
+*/
+
+use piston_window::*;
+
+use super::{input::Action, layout::Rect, widgets::left_click};
+
+/// How far a drag has to go before it counts as a swipe
+const SWIPE_DISTANCE: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// Dragged far enough in one direction, one of the four move actions
+    Swipe(Action),
+    /// Pressed and let go without moving much
+    Tap([f64; 2]),
+}
+
+impl Gesture {
+    /// The direction of a swipe
+    pub fn swipe(self) -> Option<Action> {
+        match self {
+            Gesture::Swipe(action) => Some(action),
+            Gesture::Tap(_) => None,
+        }
+    }
+}
+
+/// Follows one drag at a time, from the mouse or the first finger down
+#[derive(Debug, Default)]
+pub struct Swipe {
+    cursor: [f64; 2],
+    drag: Option<Drag>,
+}
+
+#[derive(Debug)]
+struct Drag {
+    start: [f64; 2],
+    // the finger doing the drag, None for the mouse
+    touch: Option<i64>,
+    // a drag only swipes once, the rest of it is ignored
+    swiped: bool,
+}
+
+impl Swipe {
+    pub fn new() -> Self {
+        Swipe::default()
+    }
+
+    /// Follows the event, drags have to start inside `area`
+    pub fn handle_event(&mut self, event: &Event, area: Rect) -> Option<Gesture> {
+        if let Some(cursor) = event.mouse_cursor_args() {
+            self.cursor = cursor;
+            return self.moved(None, cursor);
+        }
+        if left_click(event) {
+            self.start(None, self.cursor, area);
+            return None;
+        }
+        if event.release_args() == Some(Button::Mouse(MouseButton::Left)) {
+            return self.end(None, self.cursor);
+        }
+
+        let touch = event.touch_args()?;
+        let position = touch.position();
+        match touch.touch {
+            Touch::Start => {
+                self.start(Some(touch.id), position, area);
+                None
+            }
+            Touch::Move => self.moved(Some(touch.id), position),
+            Touch::End => self.end(Some(touch.id), position),
+            Touch::Cancel => {
+                self.drag = self.drag.take().filter(|drag| drag.touch != Some(touch.id));
+                None
+            }
+        }
+    }
+
+    fn start(&mut self, touch: Option<i64>, position: [f64; 2], area: Rect) {
+        let [x, y] = position;
+        if self.drag.is_none() && area.contains(x, y) {
+            self.drag = Some(Drag {
+                start: position,
+                touch,
+                swiped: false,
+            });
+        }
+    }
+
+    // swipes as soon as the drag is long enough, so the snake turns without letting go
+    fn moved(&mut self, touch: Option<i64>, position: [f64; 2]) -> Option<Gesture> {
+        let drag = self.drag.as_mut().filter(|drag| drag.touch == touch)?;
+        if drag.swiped {
+            return None;
+        }
+        let action = direction(drag.start, position)?;
+        drag.swiped = true;
+        Some(Gesture::Swipe(action))
+    }
+
+    fn end(&mut self, touch: Option<i64>, position: [f64; 2]) -> Option<Gesture> {
+        if self.drag.as_ref()?.touch != touch {
+            return None;
+        }
+        let gesture = self.moved(touch, position).or_else(|| {
+            let drag = self.drag.as_ref()?;
+            (!drag.swiped).then_some(Gesture::Tap(drag.start))
+        });
+        self.drag = None;
+        gesture
+    }
+}
+
+// The direction of the drag from `start` to `end`, if it went far enough
+fn direction(start: [f64; 2], end: [f64; 2]) -> Option<Action> {
+    let dx = end[0] - start[0];
+    let dy = end[1] - start[1];
+    if dx.abs().max(dy.abs()) < SWIPE_DISTANCE {
+        None
+    } else if dx.abs() > dy.abs() {
+        Some(if dx > 0.0 {
+            Action::Right
+        } else {
+            Action::Left
+        })
+    } else {
+        Some(if dy > 0.0 { Action::Down } else { Action::Up })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::widgets::tests::{click, cursor};
+
+    fn release() -> Event {
+        Event::Input(
+            Input::Button(ButtonArgs {
+                state: ButtonState::Release,
+                button: Button::Mouse(MouseButton::Left),
+                scancode: None,
+            }),
+            None,
+        )
+    }
+
+    fn touch(id: i64, x: f64, y: f64, touch: Touch) -> Event {
+        Event::Input(
+            Input::Move(Motion::Touch(TouchArgs::new(0, id, [x, y], 1.0, touch))),
+            None,
+        )
+    }
+
+    #[test]
+    fn drags_swipe_once() {
+        let mut swipe = Swipe::new();
+        let area = Rect::new(0.0, 0.0, 200.0, 200.0);
+
+        swipe.handle_event(&cursor(100.0, 100.0), area);
+        assert_eq!(swipe.handle_event(&click(), area), None);
+        assert_eq!(swipe.handle_event(&cursor(90.0, 110.0), area), None);
+        assert_eq!(
+            swipe.handle_event(&cursor(60.0, 110.0), area),
+            Some(Gesture::Swipe(Action::Left))
+        );
+        assert_eq!(swipe.handle_event(&cursor(20.0, 110.0), area), None);
+        assert_eq!(swipe.handle_event(&release(), area), None);
+
+        // a press without moving is a tap
+        swipe.handle_event(&click(), area);
+        assert_eq!(
+            swipe.handle_event(&release(), area),
+            Some(Gesture::Tap([20.0, 110.0]))
+        );
+
+        // drags starting outside don't count
+        swipe.handle_event(&cursor(300.0, 100.0), area);
+        swipe.handle_event(&click(), area);
+        assert_eq!(swipe.handle_event(&cursor(100.0, 100.0), area), None);
+        assert_eq!(swipe.handle_event(&release(), area), None);
+    }
+
+    #[test]
+    fn fingers_swipe() {
+        let mut swipe = Swipe::new();
+        let area = Rect::new(0.0, 0.0, 200.0, 200.0);
+
+        swipe.handle_event(&touch(1, 100.0, 150.0, Touch::Start), area);
+        // a second finger is ignored
+        swipe.handle_event(&touch(2, 50.0, 50.0, Touch::Start), area);
+        assert_eq!(
+            swipe.handle_event(&touch(2, 50.0, 0.0, Touch::End), area),
+            None
+        );
+        assert_eq!(
+            swipe.handle_event(&touch(1, 105.0, 60.0, Touch::End), area),
+            Some(Gesture::Swipe(Action::Up))
+        );
+    }
+}
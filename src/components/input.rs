@@ -0,0 +1,326 @@
+/*!
+    Actions the games and the menu respond to, whether they come from the
+    keyboard or a controller. Controller buttons are numbered like SDL game
+    controllers, the `gamepad` feature turns gilrs events into them.
+
+    The menu is moved through with fixed keys, the games read their actions
+    through `KeyBindings`, which players can change in the settings.
+*/
+
+use piston_window::{Button, Event, HatState, Key, PressEvent};
+use serde::{Deserialize, Serialize};
+
+/// What a pressed key or controller button means
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Presses the focused button, Enter or Space on the keyboard
+    Confirm,
+    /// Goes back or closes what is open, Escape on the keyboard
+    Back,
+    /// Starts the game over
+    Reset,
+    Pause,
+}
+
+// Controller buttons
+pub const BUTTON_SOUTH: u8 = 0;
+pub const BUTTON_EAST: u8 = 1;
+pub const BUTTON_NORTH: u8 = 3;
+pub const BUTTON_START: u8 = 6;
+pub const DPAD_UP: u8 = 11;
+pub const DPAD_DOWN: u8 = 12;
+pub const DPAD_LEFT: u8 = 13;
+pub const DPAD_RIGHT: u8 = 14;
+
+impl Action {
+    /// The actions that can be bound to keys, in the order they are listed
+    pub const BINDABLE: [Action; 7] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Reset,
+        Action::Pause,
+        Action::Back,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Up => "Move up",
+            Action::Down => "Move down",
+            Action::Left => "Move left",
+            Action::Right => "Move right",
+            Action::Confirm => "Confirm",
+            Action::Back => "Back",
+            Action::Reset => "Reset",
+            Action::Pause => "Pause",
+        }
+    }
+
+    /// The action of a key or controller button that was just pressed,
+    /// with the fixed keys used to move through the menu
+    pub fn from_event(event: &Event) -> Option<Action> {
+        match event.press_args()? {
+            Button::Keyboard(key) => Action::from_key(key),
+            Button::Controller(button) => Action::from_controller(button.button),
+            Button::Hat(hat) => match hat.state {
+                HatState::Up => Some(Action::Up),
+                HatState::Down => Some(Action::Down),
+                HatState::Left => Some(Action::Left),
+                HatState::Right => Some(Action::Right),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn from_key(key: Key) -> Option<Action> {
+        match key {
+            Key::Up => Some(Action::Up),
+            Key::Down => Some(Action::Down),
+            Key::Left => Some(Action::Left),
+            Key::Right => Some(Action::Right),
+            Key::Return | Key::Space => Some(Action::Confirm),
+            Key::Escape => Some(Action::Back),
+            _ => None,
+        }
+    }
+
+    pub fn from_controller(button: u8) -> Option<Action> {
+        match button {
+            DPAD_UP => Some(Action::Up),
+            DPAD_DOWN => Some(Action::Down),
+            DPAD_LEFT => Some(Action::Left),
+            DPAD_RIGHT => Some(Action::Right),
+            BUTTON_SOUTH | BUTTON_START => Some(Action::Confirm),
+            BUTTON_EAST => Some(Action::Back),
+            BUTTON_NORTH => Some(Action::Reset),
+            _ => None,
+        }
+    }
+}
+
+/// How many keys can be bound to one action
+pub const KEYS_PER_ACTION: usize = 2;
+
+/// Why a key can't be bound
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindError {
+    /// Another action uses the key
+    Taken(Action),
+    /// The menu opens a game with the key
+    Reserved,
+}
+
+/// The game the menu opens for a digit key, those can't be bound
+pub fn game_shortcut(key: Key) -> Option<usize> {
+    match key {
+        Key::D1 | Key::NumPad1 => Some(0),
+        Key::D2 | Key::NumPad2 => Some(1),
+        Key::D3 | Key::NumPad3 => Some(2),
+        Key::D4 | Key::NumPad4 => Some(3),
+        Key::D5 | Key::NumPad5 => Some(4),
+        Key::D6 | Key::NumPad6 => Some(5),
+        Key::D7 | Key::NumPad7 => Some(6),
+        Key::D8 | Key::NumPad8 => Some(7),
+        Key::D9 | Key::NumPad9 => Some(8),
+        _ => None,
+    }
+}
+
+/// The keys bound to every action of the games, saved in the config
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub up: Vec<Key>,
+    pub down: Vec<Key>,
+    pub left: Vec<Key>,
+    pub right: Vec<Key>,
+    pub reset: Vec<Key>,
+    pub pause: Vec<Key>,
+    pub back: Vec<Key>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            up: vec![Key::Up, Key::W],
+            down: vec![Key::Down, Key::S],
+            left: vec![Key::Left, Key::A],
+            right: vec![Key::Right, Key::D],
+            reset: vec![Key::R],
+            pause: vec![Key::P],
+            back: vec![Key::Escape],
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> &[Key] {
+        match action {
+            Action::Up => &self.up,
+            Action::Down => &self.down,
+            Action::Left => &self.left,
+            Action::Right => &self.right,
+            Action::Reset => &self.reset,
+            Action::Pause => &self.pause,
+            Action::Back => &self.back,
+            Action::Confirm => &[],
+        }
+    }
+
+    fn keys_mut(&mut self, action: Action) -> Option<&mut Vec<Key>> {
+        match action {
+            Action::Up => Some(&mut self.up),
+            Action::Down => Some(&mut self.down),
+            Action::Left => Some(&mut self.left),
+            Action::Right => Some(&mut self.right),
+            Action::Reset => Some(&mut self.reset),
+            Action::Pause => Some(&mut self.pause),
+            Action::Back => Some(&mut self.back),
+            Action::Confirm => None,
+        }
+    }
+
+    /// The action a game should do for the event, keys are looked up in the
+    /// bindings and controllers use their fixed buttons
+    pub fn action(&self, event: &Event) -> Option<Action> {
+        match event.press_args()? {
+            Button::Keyboard(key) => Action::BINDABLE
+                .into_iter()
+                .find(|action| self.keys(*action).contains(&key)),
+            _ => Action::from_event(event),
+        }
+    }
+
+    /// The action other than `action` that `key` is bound to
+    pub fn conflict(&self, action: Action, key: Key) -> Option<Action> {
+        Action::BINDABLE
+            .into_iter()
+            .find(|other| *other != action && self.keys(*other).contains(&key))
+    }
+
+    /// Binds `key` to `action` in the given slot, unless another action or
+    /// the menu uses it. The other keys of `action` keep their slots.
+    pub fn bind(&mut self, action: Action, slot: usize, key: Key) -> Result<(), BindError> {
+        if game_shortcut(key).is_some() {
+            return Err(BindError::Reserved);
+        }
+        if let Some(other) = self.conflict(action, key) {
+            return Err(BindError::Taken(other));
+        }
+        if let Some(keys) = self.keys_mut(action) {
+            match keys.iter().position(|bound| *bound == key) {
+                // already in another slot, the two keys trade places
+                Some(bound) if slot < keys.len() => keys.swap(bound, slot),
+                Some(_) => (),
+                None if slot < keys.len() => keys[slot] = key,
+                None if keys.len() < KEYS_PER_ACTION => keys.push(key),
+                None => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the key in the given slot of `action`
+    pub fn clear(&mut self, action: Action, slot: usize) {
+        if let Some(keys) = self.keys_mut(action) {
+            if slot < keys.len() {
+                keys.remove(slot);
+            }
+        }
+    }
+}
+
+/// How a key is shown in the settings
+pub fn key_name(key: Key) -> String {
+    match key {
+        // digits are named D0 to D9
+        Key::D0
+        | Key::D1
+        | Key::D2
+        | Key::D3
+        | Key::D4
+        | Key::D5
+        | Key::D6
+        | Key::D7
+        | Key::D8
+        | Key::D9 => format!("{:?}", key)[1..].to_string(),
+        Key::Return => "Enter".to_string(),
+        key => format!("{:?}", key),
+    }
+}
+
+/// Whether the event is a key or controller button being pressed,
+/// those go to the open game before the menu
+pub fn is_press(event: &Event) -> bool {
+    matches!(
+        event.press_args(),
+        Some(Button::Keyboard(_) | Button::Controller(_) | Button::Hat(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::widgets::tests::press;
+    use piston_window::ControllerButton;
+
+    #[test]
+    fn keys_and_buttons_map_to_the_same_actions() {
+        let pad = |button| press(Button::Controller(ControllerButton::new(0, button)));
+        assert_eq!(
+            Action::from_event(&press(Button::Keyboard(Key::Left))),
+            Some(Action::Left)
+        );
+        assert_eq!(Action::from_event(&pad(DPAD_LEFT)), Some(Action::Left));
+        assert_eq!(
+            Action::from_event(&press(Button::Keyboard(Key::Space))),
+            Some(Action::Confirm)
+        );
+        assert_eq!(
+            Action::from_event(&pad(BUTTON_SOUTH)),
+            Some(Action::Confirm)
+        );
+        assert_eq!(Action::from_event(&pad(BUTTON_EAST)), Some(Action::Back));
+        assert_eq!(Action::from_event(&pad(BUTTON_NORTH)), Some(Action::Reset));
+        assert_eq!(Action::from_event(&pad(2)), None);
+        assert_eq!(Action::from_event(&press(Button::Keyboard(Key::A))), None);
+    }
+
+    #[test]
+    fn bindings_reject_conflicts() {
+        let mut bindings = KeyBindings::default();
+        let key = |key| press(Button::Keyboard(key));
+        assert_eq!(bindings.action(&key(Key::W)), Some(Action::Up));
+
+        // vim keys for moving left
+        assert_eq!(bindings.bind(Action::Left, 1, Key::H), Ok(()));
+        assert_eq!(bindings.left, [Key::Left, Key::H]);
+        assert_eq!(bindings.action(&key(Key::H)), Some(Action::Left));
+        assert_eq!(bindings.action(&key(Key::A)), None);
+
+        assert_eq!(
+            bindings.bind(Action::Left, 0, Key::R),
+            Err(BindError::Taken(Action::Reset))
+        );
+        assert_eq!(
+            bindings.bind(Action::Left, 0, Key::D1),
+            Err(BindError::Reserved)
+        );
+        assert_eq!(bindings.left, [Key::Left, Key::H]);
+
+        // binding a key again keeps the slots in place
+        assert_eq!(bindings.bind(Action::Left, 0, Key::J), Ok(()));
+        assert_eq!(bindings.left, [Key::J, Key::H]);
+        assert_eq!(bindings.bind(Action::Left, 0, Key::H), Ok(()));
+        assert_eq!(bindings.left, [Key::H, Key::J]);
+
+        bindings.clear(Action::Left, 0);
+        assert_eq!(bindings.left, [Key::J]);
+    }
+}
@@ -0,0 +1,256 @@
+/*!
+    Places buttons, boards and text relative to the current window size
+    instead of at fixed pixel positions.
+    Sizes are in logical pixels, piston scales them to the monitor so
+    layouts look the same on HiDPI screens.
+*/
+
+use super::button::Pos;
+use crate::menu::ui::TASKBAR_HEIGHT;
+
+/// Space kept between an area and the things placed in it
+pub const PADDING: f64 = 10.0;
+
+// Widths of the columns next to the board on a game screen
+const SIDEBAR_WIDTH: f64 = 270.0;
+const CONTROLS_WIDTH: f64 = 180.0;
+
+/// Where a smaller rect is placed inside a bigger one
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, w: f64, h: f64) -> Self {
+        Rect { x, y, w, h }
+    }
+
+    /// The whole window
+    pub fn window(size: [f64; 2]) -> Self {
+        Rect::new(0.0, 0.0, size[0], size[1])
+    }
+
+    pub fn pos(&self) -> Pos {
+        Pos {
+            x: self.x,
+            y: self.y,
+        }
+    }
+
+    pub fn to_array(self) -> [f64; 4] {
+        [self.x, self.y, self.w, self.h]
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x > self.x && x < self.x + self.w && y > self.y && y < self.y + self.h
+    }
+
+    /// Shrinks the rect by `padding` on every side
+    pub fn pad(&self, padding: f64) -> Rect {
+        Rect::new(
+            self.x + padding,
+            self.y + padding,
+            (self.w - 2.0 * padding).max(0.0),
+            (self.h - 2.0 * padding).max(0.0),
+        )
+    }
+
+    /// Cuts `height` off the top, returns the top part and what is left below it
+    pub fn split_top(&self, height: f64) -> (Rect, Rect) {
+        let height = height.min(self.h);
+        (
+            Rect::new(self.x, self.y, self.w, height),
+            Rect::new(self.x, self.y + height, self.w, self.h - height),
+        )
+    }
+
+    /// Cuts `height` off the bottom, returns the bottom part and what is left above it
+    pub fn split_bottom(&self, height: f64) -> (Rect, Rect) {
+        let height = height.min(self.h);
+        (
+            Rect::new(self.x, self.y + self.h - height, self.w, height),
+            Rect::new(self.x, self.y, self.w, self.h - height),
+        )
+    }
+
+    /// Cuts `width` off the left, returns the left part and what is left next to it
+    pub fn split_left(&self, width: f64) -> (Rect, Rect) {
+        let width = width.min(self.w);
+        (
+            Rect::new(self.x, self.y, width, self.h),
+            Rect::new(self.x + width, self.y, self.w - width, self.h),
+        )
+    }
+
+    /// Cuts `width` off the right, returns the right part and what is left next to it
+    pub fn split_right(&self, width: f64) -> (Rect, Rect) {
+        let width = width.min(self.w);
+        (
+            Rect::new(self.x + self.w - width, self.y, width, self.h),
+            Rect::new(self.x, self.y, self.w - width, self.h),
+        )
+    }
+
+    /// Places a `w` by `h` rect inside this one
+    pub fn anchor(&self, anchor: Anchor, w: f64, h: f64) -> Rect {
+        let left = self.x;
+        let center = self.x + (self.w - w) / 2.0;
+        let right = self.x + self.w - w;
+        let top = self.y;
+        let middle = self.y + (self.h - h) / 2.0;
+        let bottom = self.y + self.h - h;
+
+        let (x, y) = match anchor {
+            Anchor::TopLeft => (left, top),
+            Anchor::Top => (center, top),
+            Anchor::TopRight => (right, top),
+            Anchor::Left => (left, middle),
+            Anchor::Center => (center, middle),
+            Anchor::Right => (right, middle),
+            Anchor::BottomLeft => (left, bottom),
+            Anchor::Bottom => (center, bottom),
+            Anchor::BottomRight => (right, bottom),
+        };
+        Rect::new(x, y, w, h)
+    }
+
+    /// The biggest square that fits in the middle of the rect
+    pub fn square(&self) -> Rect {
+        let side = self.w.min(self.h);
+        self.anchor(Anchor::Center, side, side)
+    }
+
+    /// Splits the rect into `count` columns of equal width with `gap` between them
+    pub fn columns(&self, count: usize, gap: f64) -> Vec<Rect> {
+        let width = (self.w - gap * (count as f64 - 1.0)) / count as f64;
+        (0..count)
+            .map(|column| {
+                Rect::new(
+                    self.x + (width + gap) * column as f64,
+                    self.y,
+                    width,
+                    self.h,
+                )
+            })
+            .collect()
+    }
+
+    /// Stacks `count` rows of a fixed `height` from the top, `gap` apart
+    pub fn stack(&self, count: usize, height: f64, gap: f64) -> Vec<Rect> {
+        (0..count)
+            .map(|row| Rect::new(self.x, self.y + (height + gap) * row as f64, self.w, height))
+            .collect()
+    }
+
+    /// The cell at `column`, `row` when the rect is divided into a grid of equal cells
+    pub fn cell(&self, columns: usize, rows: usize, column: usize, row: usize) -> Rect {
+        let w = self.w / columns as f64;
+        let h = self.h / rows as f64;
+        Rect::new(self.x + w * column as f64, self.y + h * row as f64, w, h)
+    }
+}
+
+/// The area below the taskbar, where tabs and apps draw their content
+pub fn content(size: [f64; 2]) -> Rect {
+    let (_, content) = Rect::window(size).split_top(TASKBAR_HEIGHT);
+    content.pad(PADDING)
+}
+
+/// The areas shared by the game screens: scores on the left,
+/// the board in the middle and buttons on the right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameLayout {
+    pub sidebar: Rect,
+    pub board: Rect,
+    pub controls: Rect,
+}
+
+impl GameLayout {
+    pub fn new(size: [f64; 2]) -> Self {
+        let (sidebar, rest) = content(size).split_left(SIDEBAR_WIDTH);
+        let (controls, board) = rest.split_right(CONTROLS_WIDTH);
+        GameLayout {
+            sidebar,
+            board,
+            controls: controls.pad(PADDING),
+        }
+    }
+
+    /// Rects for `count` buttons stacked at the top of the controls column
+    pub fn buttons(&self, count: usize) -> Vec<Rect> {
+        self.controls
+            .anchor(Anchor::Top, 160.0, self.controls.h)
+            .stack(count, 48.0, 44.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchors_and_splits() {
+        let rect = Rect::new(10.0, 20.0, 100.0, 50.0);
+        assert_eq!(
+            rect.anchor(Anchor::Center, 20.0, 10.0),
+            Rect::new(50.0, 40.0, 20.0, 10.0)
+        );
+        assert_eq!(
+            rect.anchor(Anchor::BottomRight, 20.0, 10.0),
+            Rect::new(90.0, 60.0, 20.0, 10.0)
+        );
+        assert_eq!(rect.pad(5.0), Rect::new(15.0, 25.0, 90.0, 40.0));
+        assert_eq!(rect.square(), Rect::new(35.0, 20.0, 50.0, 50.0));
+
+        let (left, rest) = rect.split_left(30.0);
+        assert_eq!(left, Rect::new(10.0, 20.0, 30.0, 50.0));
+        assert_eq!(rest, Rect::new(40.0, 20.0, 70.0, 50.0));
+        let (bottom, rest) = rect.split_bottom(80.0);
+        assert_eq!(bottom, rect);
+        assert_eq!(rest.h, 0.0);
+    }
+
+    #[test]
+    fn columns_and_stacks() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 70.0);
+        let columns = rect.columns(3, 5.0);
+        assert_eq!(columns[2], Rect::new(70.0, 0.0, 30.0, 70.0));
+        let stack = rect.stack(3, 20.0, 5.0);
+        assert_eq!(stack[2], Rect::new(0.0, 50.0, 100.0, 20.0));
+        assert_eq!(rect.cell(4, 7, 1, 6), Rect::new(25.0, 60.0, 25.0, 10.0));
+    }
+
+    #[test]
+    fn game_layout_follows_window_size() {
+        for size in [[1024.0, 576.0], [1920.0, 1080.0], [800.0, 480.0]] {
+            let layout = GameLayout::new(size);
+            // the board is in the middle, between the two columns
+            assert!(layout.sidebar.x + layout.sidebar.w <= layout.board.x);
+            assert!(layout.board.x + layout.board.w <= layout.controls.x);
+            assert_eq!(layout.board.y, TASKBAR_HEIGHT + PADDING);
+            assert_eq!(layout.board.y + layout.board.h, size[1] - PADDING);
+            assert_eq!(
+                layout.controls.x + layout.controls.w,
+                size[0] - 2.0 * PADDING
+            );
+        }
+    }
+}
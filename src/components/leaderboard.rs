@@ -0,0 +1,146 @@
+use piston_window::*;
+
+use super::{
+    button::{draw_text, Pos},
+    layout::{Anchor, Rect},
+    render::Renderer,
+    widgets::{Response, TextInput, Widget, WidgetState},
+};
+use crate::menu::{
+    config::Config,
+    leaderboard::{Leaderboard, LEADERBOARD_SIZE},
+    profiles::MAX_NAME_LEN,
+    theme::Theme,
+};
+
+/// Draws a top 10 table with its top left corner at `pos`,
+/// `highlight` marks the entry that was just added.
+pub fn draw_leaderboard(
+    r: &mut dyn Renderer,
+    board: &Leaderboard,
+    pos: Pos,
+    theme: &Theme,
+    highlight: Option<usize>,
+) {
+    let text_color = theme.text;
+    draw_text(
+        r,
+        text_color,
+        Pos {
+            x: pos.x,
+            y: pos.y + 18.0,
+        },
+        &format!("Top {}", LEADERBOARD_SIZE),
+        20,
+    );
+
+    if board.entries.is_empty() {
+        draw_text(
+            r,
+            text_color,
+            Pos {
+                x: pos.x,
+                y: pos.y + 44.0,
+            },
+            "No scores yet",
+            14,
+        );
+    }
+
+    for (index, entry) in board.entries.iter().enumerate() {
+        let y = pos.y + 44.0 + 20.0 * index as f64;
+        if highlight == Some(index) {
+            r.rectangle(theme.accent, [pos.x - 4.0, y - 15.0, 262.0, 20.0]);
+        }
+
+        let name: String = entry.name.chars().take(12).collect();
+        let cells = [
+            (0.0, format!("{}.", index + 1)),
+            (26.0, name),
+            (130.0, entry.score.to_string()),
+            (180.0, entry.date_string()),
+        ];
+        for (x, text) in cells.iter() {
+            draw_text(r, text_color, Pos { x: pos.x + x, y }, text, 14);
+        }
+    }
+}
+
+/// What a `NamePrompt` did with an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptEvent {
+    /// The event wasn't meant for the prompt
+    Ignored,
+    /// The prompt used the event
+    Consumed,
+    Submit(String),
+    Cancel,
+}
+
+/// Asks for a name when a run makes the leaderboard
+pub struct NamePrompt {
+    input: TextInput,
+}
+
+impl NamePrompt {
+    pub fn new(name: &str) -> Self {
+        NamePrompt {
+            input: TextInput::new(MAX_NAME_LEN).with_text(name),
+        }
+    }
+
+    // The prompt has the keyboard as long as it is open
+    const STATE: WidgetState = WidgetState {
+        cursor: [0.0; 2],
+        hovered: false,
+        focused: true,
+    };
+
+    fn rect(size: [f64; 2]) -> Rect {
+        Rect::window(size).anchor(Anchor::Center, 400.0, 120.0)
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> PromptEvent {
+        // only keys reach the input, so the rect doesn't matter
+        let rect = NamePrompt::rect([0.0; 2]);
+        match self.input.handle_event(event, rect, NamePrompt::STATE) {
+            Response::Submitted if !self.input.value().is_empty() => {
+                PromptEvent::Submit(self.input.value().to_string())
+            }
+            Response::Cancelled => PromptEvent::Cancel,
+            Response::Ignored => PromptEvent::Ignored,
+            _ => PromptEvent::Consumed,
+        }
+    }
+
+    /// Draws the prompt in the middle of the screen
+    pub fn draw(&self, r: &mut dyn Renderer, config: &Config) {
+        let (background, text_color) = (config.theme.panel, config.theme.text);
+        let rect = NamePrompt::rect(r.size());
+
+        r.rectangle(background, rect.to_array());
+        r.border_rectangle(text_color, 1.0, rect.to_array());
+        draw_text(
+            r,
+            text_color,
+            Pos {
+                x: rect.x + 20.0,
+                y: rect.y + 32.0,
+            },
+            "New highscore! Enter your name:",
+            20,
+        );
+        let input = Rect::new(rect.x + 20.0, rect.y + 46.0, rect.w - 40.0, 30.0);
+        self.input.draw(r, input, &config.theme, NamePrompt::STATE);
+        draw_text(
+            r,
+            text_color,
+            Pos {
+                x: rect.x + 20.0,
+                y: rect.y + 104.0,
+            },
+            "Enter to save, Escape to skip",
+            14,
+        );
+    }
+}
@@ -2,3 +2,14 @@ pub mod animations;
 pub mod application;
 pub mod button;
 pub mod color;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+pub mod gesture;
+pub mod input;
+pub mod layout;
+pub mod leaderboard;
+pub mod pause;
+pub mod registry;
+pub mod render;
+pub mod widgets;
+pub mod window;
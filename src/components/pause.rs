@@ -0,0 +1,115 @@
+/*!
+    The pause menu of the real-time games. It opens with Escape or P, when
+    the window loses focus or when another tab is opened, and the game
+    waits behind it until the player comes back.
+*/
+
+use piston_window::Event;
+use std::time::{Duration, Instant};
+
+use super::{
+    button::ButtonStyle,
+    input::{Action, KeyBindings},
+    layout::Rect,
+    render::Renderer,
+    widgets::{Dialog, Focus, Response},
+};
+use crate::menu::config::Config;
+
+/// What the player picked in the pause menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PauseEvent {
+    /// The menu used the event, the game stays paused
+    Consumed,
+    Resume,
+    Restart,
+    /// Go back to the games tab, the game stays paused
+    Quit,
+}
+
+pub struct PauseMenu {
+    dialog: Dialog,
+    focus: Focus,
+    since: Instant,
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        PauseMenu {
+            // Resume is last, so Enter picks it
+            dialog: Dialog::new("Paused", "The game waits here while you are in the menu.")
+                .with_button("   Menu", ButtonStyle::Primary)
+                .with_button("  Restart", ButtonStyle::Danger)
+                .with_button("  Resume", ButtonStyle::Primary),
+            focus: Focus::new(),
+            since: Instant::now(),
+        }
+    }
+
+    /// How long the game has been paused
+    pub fn duration(&self) -> Duration {
+        self.since.elapsed()
+    }
+
+    /// Takes every event while the menu is open, the pause key resumes
+    pub fn handle_event(
+        &mut self,
+        event: &Event,
+        size: [f64; 2],
+        bindings: &KeyBindings,
+    ) -> PauseEvent {
+        if bindings.action(event) == Some(Action::Pause) {
+            return PauseEvent::Resume;
+        }
+        let dialog = (&mut self.dialog as _, Rect::window(size));
+        match self.focus.dispatch(event, &mut [dialog]) {
+            Some((_, Response::Picked(0))) => PauseEvent::Quit,
+            Some((_, Response::Picked(1))) => PauseEvent::Restart,
+            Some((_, Response::Picked(_) | Response::Cancelled)) => PauseEvent::Resume,
+            _ => PauseEvent::Consumed,
+        }
+    }
+
+    /// Draws the menu over the whole window
+    pub fn draw(&self, r: &mut dyn Renderer, config: &Config) {
+        let window = Rect::window(r.size());
+        self.focus.draw(r, &config.theme, &[(&self.dialog, window)]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::widgets::tests::key;
+    use piston_window::Key;
+
+    #[test]
+    fn pause_menu_resumes() {
+        let mut menu = PauseMenu::new();
+        let bindings = KeyBindings::default();
+        let size = [1024.0, 576.0];
+
+        assert_eq!(
+            menu.handle_event(&key(Key::W), size, &bindings),
+            PauseEvent::Consumed
+        );
+        assert_eq!(
+            menu.handle_event(&key(Key::Return), size, &bindings),
+            PauseEvent::Resume
+        );
+        assert_eq!(
+            menu.handle_event(&key(Key::P), size, &bindings),
+            PauseEvent::Resume
+        );
+        assert_eq!(
+            menu.handle_event(&key(Key::Escape), size, &bindings),
+            PauseEvent::Resume
+        );
+
+        menu.handle_event(&key(Key::Left), size, &bindings);
+        assert_eq!(
+            menu.handle_event(&key(Key::Return), size, &bindings),
+            PauseEvent::Restart
+        );
+    }
+}
@@ -0,0 +1,90 @@
+/*!
+    Registry of every mini-app shown in the Games tab.
+    The main menu builds its game list, window titles and tab routing from
+    the registered entries, so adding a game is a single `register` call.
+*/
+
+use piston_window::types::Color;
+
+use super::application::MiniApp;
+
+pub struct AppEntry {
+    /// Stable identifier, used for lookups and saved data
+    pub id: &'static str,
+    /// Name shown in the Games tab and in the window title
+    pub name: &'static str,
+    /// Color of the icon drawn next to the name
+    pub icon: Color,
+    /// Creates a fresh instance of the app
+    pub build: fn() -> Box<dyn MiniApp>,
+}
+
+pub struct Registry {
+    entries: Vec<AppEntry>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn register(
+        &mut self,
+        id: &'static str,
+        name: &'static str,
+        icon: Color,
+        build: fn() -> Box<dyn MiniApp>,
+    ) -> &mut Self {
+        debug_assert!(self.position(id).is_none(), "app {} registered twice", id);
+        self.entries.push(AppEntry {
+            id,
+            name,
+            icon,
+            build,
+        });
+        self
+    }
+
+    pub fn entries(&self) -> &[AppEntry] {
+        &self.entries
+    }
+
+    /// Returns the index of the app with the given id
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.id == id)
+    }
+
+    /// Instantiates every registered app, in registration order
+    pub fn build_all(&self) -> Vec<Box<dyn MiniApp>> {
+        self.entries.iter().map(|entry| (entry.build)()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tictactoe::ui::TicTacToeApp;
+    use crate::twenty48::ui::Twenty48App;
+
+    #[test]
+    fn registration_order_is_kept() {
+        let mut registry = Registry::new();
+        registry
+            .register("tictactoe", "TicTacToe", [0.0; 4], || {
+                Box::new(TicTacToeApp::new())
+            })
+            .register(
+                "twenty48",
+                "2048",
+                [0.0; 4],
+                || Box::new(Twenty48App::new()),
+            );
+
+        assert_eq!(registry.entries().len(), 2);
+        assert_eq!(registry.position("twenty48"), Some(1));
+        assert_eq!(registry.position("snake"), None);
+        assert_eq!(registry.build_all().len(), 2);
+    }
+}
@@ -0,0 +1,34 @@
+/*!
+    Drawing abstraction used by every screen.
+    The piston backend draws to the window, the recording backend keeps a
+    list of draw commands (and can rasterize them to a PNG) so screens can
+    be tested without a GPU or display.
+*/
+
+pub mod piston;
+pub mod recording;
+
+pub use self::piston::PistonRenderer;
+pub use recording::RecordingRenderer;
+
+use piston_window::{types::Color, G2dTexture};
+
+pub trait Renderer {
+    /// Size of the drawable area
+    fn size(&self) -> [f64; 2];
+
+    /// Offsets all following draw calls until the matching `pop_transform`
+    fn push_translate(&mut self, x: f64, y: f64);
+    fn pop_transform(&mut self);
+
+    fn clear(&mut self, color: Color);
+    fn rectangle(&mut self, color: Color, rect: [f64; 4]);
+    fn round_rectangle(&mut self, color: Color, radius: f64, rect: [f64; 4]);
+    fn border_rectangle(&mut self, color: Color, radius: f64, rect: [f64; 4]);
+    /// Draws the outline of the ellipse that fits in `rect`
+    fn border_ellipse(&mut self, color: Color, radius: f64, rect: [f64; 4]);
+    fn line(&mut self, color: Color, radius: f64, line: [f64; 4]);
+    /// Draws text with its baseline starting at `pos`
+    fn text(&mut self, color: Color, pos: [f64; 2], text: &str, size: u32);
+    fn image(&mut self, texture: &G2dTexture, rect: [f64; 4]);
+}
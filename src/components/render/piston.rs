@@ -0,0 +1,93 @@
+use piston_window::{
+    clear, line, rectangle, text::Text, types::Color, Context, DrawState, Ellipse, G2d, G2dTexture,
+    Glyphs, Image, Rectangle, Transformed,
+};
+
+use super::Renderer;
+
+/// Draws straight to the piston window.
+pub struct PistonRenderer<'a, 'b> {
+    stack: Vec<Context>,
+    g: &'a mut G2d<'b>,
+    glyphs: &'a mut Glyphs,
+}
+
+impl<'a, 'b> PistonRenderer<'a, 'b> {
+    pub fn new(c: Context, g: &'a mut G2d<'b>, glyphs: &'a mut Glyphs) -> Self {
+        PistonRenderer {
+            stack: vec![c],
+            g,
+            glyphs,
+        }
+    }
+
+    fn context(&self) -> Context {
+        *self.stack.last().unwrap()
+    }
+}
+
+impl Renderer for PistonRenderer<'_, '_> {
+    fn size(&self) -> [f64; 2] {
+        self.stack[0].get_view_size()
+    }
+
+    fn push_translate(&mut self, x: f64, y: f64) {
+        let c = self.context().trans(x, y);
+        self.stack.push(c);
+    }
+
+    fn pop_transform(&mut self) {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+        }
+    }
+
+    fn clear(&mut self, color: Color) {
+        clear(color, self.g);
+    }
+
+    fn rectangle(&mut self, color: Color, rect: [f64; 4]) {
+        rectangle(color, rect, self.context().transform, self.g);
+    }
+
+    fn round_rectangle(&mut self, color: Color, radius: f64, rect: [f64; 4]) {
+        let c = self.context();
+        Rectangle::new_round(color, radius).draw(rect, &c.draw_state, c.transform, self.g);
+    }
+
+    fn border_rectangle(&mut self, color: Color, radius: f64, rect: [f64; 4]) {
+        let c = self.context();
+        Rectangle::new_border(color, radius).draw(rect, &c.draw_state, c.transform, self.g);
+    }
+
+    fn border_ellipse(&mut self, color: Color, radius: f64, rect: [f64; 4]) {
+        let c = self.context();
+        Ellipse::new_border(color, radius).draw(rect, &c.draw_state, c.transform, self.g);
+    }
+
+    fn line(&mut self, color: Color, radius: f64, coords: [f64; 4]) {
+        line(color, radius, coords, self.context().transform, self.g);
+    }
+
+    fn text(&mut self, color: Color, pos: [f64; 2], text: &str, size: u32) {
+        let c = self.context();
+        Text::new_color(color, size)
+            .draw(
+                text,
+                self.glyphs,
+                &c.draw_state,
+                c.transform.trans(pos[0], pos[1]),
+                self.g,
+            )
+            .unwrap();
+    }
+
+    fn image(&mut self, texture: &G2dTexture, rect: [f64; 4]) {
+        Image::new().rect(rect).draw(
+            texture,
+            &DrawState::new_alpha(),
+            self.context().transform,
+            self.g,
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use std::{fmt, path::Path};
+
+use image::{ImageResult, Rgba, RgbaImage};
+use piston_window::{types::Color, G2dTexture, ImageSize};
+
+use super::Renderer;
+
+/// A single recorded draw call, with the translation already applied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Clear(Color),
+    Rectangle {
+        color: Color,
+        rect: [f64; 4],
+    },
+    RoundRectangle {
+        color: Color,
+        radius: f64,
+        rect: [f64; 4],
+    },
+    BorderRectangle {
+        color: Color,
+        radius: f64,
+        rect: [f64; 4],
+    },
+    BorderEllipse {
+        color: Color,
+        radius: f64,
+        rect: [f64; 4],
+    },
+    Line {
+        color: Color,
+        radius: f64,
+        line: [f64; 4],
+    },
+    Text {
+        color: Color,
+        pos: [f64; 2],
+        text: String,
+        size: u32,
+    },
+    Image {
+        size: [u32; 2],
+        rect: [f64; 4],
+    },
+}
+
+/// Records draw calls instead of drawing them, used for headless tests.
+pub struct RecordingRenderer {
+    pub commands: Vec<DrawCommand>,
+    size: [f64; 2],
+    offsets: Vec<[f64; 2]>,
+}
+
+impl RecordingRenderer {
+    pub fn new(width: f64, height: f64) -> Self {
+        RecordingRenderer {
+            commands: Vec::new(),
+            size: [width, height],
+            offsets: vec![[0.0, 0.0]],
+        }
+    }
+
+    fn offset(&self) -> [f64; 2] {
+        *self.offsets.last().unwrap()
+    }
+
+    fn translate(&self, rect: [f64; 4]) -> [f64; 4] {
+        let [x, y] = self.offset();
+        [rect[0] + x, rect[1] + y, rect[2], rect[3]]
+    }
+
+    /// Returns every text that was drawn, in order
+    #[cfg(test)]
+    pub fn texts(&self) -> Vec<&str> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Software rasterizes the recorded commands.
+    /// Text is drawn as a translucent box covering roughly where the glyphs would be.
+    pub fn rasterize(&self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.size[0] as u32, self.size[1] as u32);
+        for command in self.commands.iter() {
+            match command {
+                DrawCommand::Clear(color) => {
+                    for pixel in image.pixels_mut() {
+                        *pixel = to_rgba(*color);
+                    }
+                }
+                DrawCommand::Rectangle { color, rect }
+                | DrawCommand::RoundRectangle { color, rect, .. } => {
+                    fill_rect(&mut image, *color, *rect);
+                }
+                DrawCommand::BorderRectangle {
+                    color,
+                    radius,
+                    rect,
+                } => {
+                    let [x, y, w, h] = *rect;
+                    let r = *radius;
+                    fill_rect(&mut image, *color, [x - r, y - r, w + 2.0 * r, 2.0 * r]);
+                    fill_rect(&mut image, *color, [x - r, y + h - r, w + 2.0 * r, 2.0 * r]);
+                    fill_rect(&mut image, *color, [x - r, y - r, 2.0 * r, h + 2.0 * r]);
+                    fill_rect(&mut image, *color, [x + w - r, y - r, 2.0 * r, h + 2.0 * r]);
+                }
+                DrawCommand::BorderEllipse {
+                    color,
+                    radius,
+                    rect,
+                } => {
+                    let [x, y, w, h] = *rect;
+                    let (rx, ry) = (w / 2.0, h / 2.0);
+                    let (cx, cy) = (x + rx, y + ry);
+                    let r = radius.max(0.5);
+                    for py in (y - r).floor() as i64..=(y + h + r).ceil() as i64 {
+                        for px in (x - r).floor() as i64..=(x + w + r).ceil() as i64 {
+                            // roughly the distance to the outline
+                            let dx = (px as f64 + 0.5 - cx) / rx;
+                            let dy = (py as f64 + 0.5 - cy) / ry;
+                            let distance = ((dx * dx + dy * dy).sqrt() - 1.0) * rx.min(ry);
+                            if distance.abs() <= r {
+                                fill_rect(&mut image, *color, [px as f64, py as f64, 1.0, 1.0]);
+                            }
+                        }
+                    }
+                }
+                DrawCommand::Line {
+                    color,
+                    radius,
+                    line,
+                } => {
+                    let [x1, y1, x2, y2] = *line;
+                    let r = radius.max(0.5);
+                    let steps = ((x2 - x1).abs().max((y2 - y1).abs()) / 0.5).ceil().max(1.0);
+                    for step in 0..=steps as u32 {
+                        let t = step as f64 / steps;
+                        let x = x1 + (x2 - x1) * t;
+                        let y = y1 + (y2 - y1) * t;
+                        fill_rect(&mut image, *color, [x - r, y - r, 2.0 * r, 2.0 * r]);
+                    }
+                }
+                DrawCommand::Text {
+                    color,
+                    pos,
+                    text,
+                    size,
+                } => {
+                    let size = *size as f64;
+                    let mut color = *color;
+                    color[3] *= 0.5;
+                    fill_rect(
+                        &mut image,
+                        color,
+                        [
+                            pos[0],
+                            pos[1] - size * 0.75,
+                            text.chars().count() as f64 * size * 0.5,
+                            size * 0.75,
+                        ],
+                    );
+                }
+                DrawCommand::Image { rect, .. } => {
+                    fill_rect(&mut image, [0.5, 0.5, 0.5, 1.0], *rect);
+                }
+            }
+        }
+        image
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        self.rasterize().save(path)
+    }
+}
+
+fn to_rgba(color: Color) -> Rgba<u8> {
+    Rgba(color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+}
+
+// Alpha blends the color over every pixel covered by rect
+fn fill_rect(image: &mut RgbaImage, color: Color, rect: [f64; 4]) {
+    let [x, y, w, h] = rect;
+    let x0 = x.max(0.0).round() as u32;
+    let y0 = y.max(0.0).round() as u32;
+    let x1 = ((x + w).round().max(0.0) as u32).min(image.width());
+    let y1 = ((y + h).round().max(0.0) as u32).min(image.height());
+    let alpha = color[3].clamp(0.0, 1.0);
+    for py in y0..y1 {
+        for px in x0..x1 {
+            let pixel = image.get_pixel_mut(px, py);
+            for i in 0..3 {
+                let dst = pixel[i] as f32 / 255.0;
+                let src = color[i].clamp(0.0, 1.0);
+                pixel[i] = ((src * alpha + dst * (1.0 - alpha)) * 255.0).round() as u8;
+            }
+            pixel[3] = 255;
+        }
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn size(&self) -> [f64; 2] {
+        self.size
+    }
+
+    fn push_translate(&mut self, x: f64, y: f64) {
+        let [ox, oy] = self.offset();
+        self.offsets.push([ox + x, oy + y]);
+    }
+
+    fn pop_transform(&mut self) {
+        if self.offsets.len() > 1 {
+            self.offsets.pop();
+        }
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.commands.push(DrawCommand::Clear(color));
+    }
+
+    fn rectangle(&mut self, color: Color, rect: [f64; 4]) {
+        let rect = self.translate(rect);
+        self.commands.push(DrawCommand::Rectangle { color, rect });
+    }
+
+    fn round_rectangle(&mut self, color: Color, radius: f64, rect: [f64; 4]) {
+        let rect = self.translate(rect);
+        self.commands.push(DrawCommand::RoundRectangle {
+            color,
+            radius,
+            rect,
+        });
+    }
+
+    fn border_rectangle(&mut self, color: Color, radius: f64, rect: [f64; 4]) {
+        let rect = self.translate(rect);
+        self.commands.push(DrawCommand::BorderRectangle {
+            color,
+            radius,
+            rect,
+        });
+    }
+
+    fn border_ellipse(&mut self, color: Color, radius: f64, rect: [f64; 4]) {
+        let rect = self.translate(rect);
+        self.commands.push(DrawCommand::BorderEllipse {
+            color,
+            radius,
+            rect,
+        });
+    }
+
+    fn line(&mut self, color: Color, radius: f64, line: [f64; 4]) {
+        let [x, y] = self.offset();
+        self.commands.push(DrawCommand::Line {
+            color,
+            radius,
+            line: [line[0] + x, line[1] + y, line[2] + x, line[3] + y],
+        });
+    }
+
+    fn text(&mut self, color: Color, pos: [f64; 2], text: &str, size: u32) {
+        let [x, y] = self.offset();
+        self.commands.push(DrawCommand::Text {
+            color,
+            pos: [pos[0] + x, pos[1] + y],
+            text: text.to_string(),
+            size,
+        });
+    }
+
+    fn image(&mut self, texture: &G2dTexture, rect: [f64; 4]) {
+        let rect = self.translate(rect);
+        self.commands.push(DrawCommand::Image {
+            size: [texture.get_width(), texture.get_height()],
+            rect,
+        });
+    }
+}
+
+// One command per line, rounded so snapshots are stable across platforms
+impl fmt::Display for RecordingRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn color(c: &Color) -> String {
+            let [r, g, b, a] = c.map(|c| (c * 255.0).round() as u8);
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+        fn nums(values: &[f64]) -> String {
+            values
+                .iter()
+                .map(|v| format!("{:.1}", v))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+
+        for command in self.commands.iter() {
+            match command {
+                DrawCommand::Clear(c) => writeln!(f, "clear {}", color(c))?,
+                DrawCommand::Rectangle { color: c, rect } => {
+                    writeln!(f, "rect {} [{}]", color(c), nums(rect))?
+                }
+                DrawCommand::RoundRectangle {
+                    color: c,
+                    radius,
+                    rect,
+                } => writeln!(f, "round {} {:.1} [{}]", color(c), radius, nums(rect))?,
+                DrawCommand::BorderRectangle {
+                    color: c,
+                    radius,
+                    rect,
+                } => writeln!(f, "border {} {:.1} [{}]", color(c), radius, nums(rect))?,
+                DrawCommand::BorderEllipse {
+                    color: c,
+                    radius,
+                    rect,
+                } => writeln!(f, "ellipse {} {:.1} [{}]", color(c), radius, nums(rect))?,
+                DrawCommand::Line {
+                    color: c,
+                    radius,
+                    line,
+                } => writeln!(f, "line {} {:.1} [{}]", color(c), radius, nums(line))?,
+                DrawCommand::Text {
+                    color: c,
+                    pos,
+                    text,
+                    size,
+                } => writeln!(f, "text {} {} [{}] {:?}", color(c), size, nums(pos), text)?,
+                DrawCommand::Image { size, rect } => {
+                    writeln!(f, "image {}x{} [{}]", size[0], size[1], nums(rect))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares the recorded commands against `snapshots/<name>.txt`.
+/// Run the tests with `UPDATE_SNAPSHOTS=1` to write new snapshots instead.
+#[cfg(test)]
+pub fn assert_snapshot(name: &str, renderer: &RecordingRenderer) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(format!("{}.txt", name));
+    let actual = renderer.to_string();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {}, run with UPDATE_SNAPSHOTS=1", name));
+    assert!(
+        expected == actual,
+        "snapshot {} does not match, run with UPDATE_SNAPSHOTS=1 to update it\n{}",
+        name,
+        actual
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translation_is_applied() {
+        let mut r = RecordingRenderer::new(100.0, 100.0);
+        r.push_translate(10.0, 20.0);
+        r.rectangle([1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 5.0, 5.0]);
+        r.pop_transform();
+        r.rectangle([1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 5.0, 5.0]);
+
+        assert_eq!(
+            r.commands[0],
+            DrawCommand::Rectangle {
+                color: [1.0, 0.0, 0.0, 1.0],
+                rect: [10.0, 20.0, 5.0, 5.0]
+            }
+        );
+        assert_eq!(
+            r.commands[1],
+            DrawCommand::Rectangle {
+                color: [1.0, 0.0, 0.0, 1.0],
+                rect: [0.0, 0.0, 5.0, 5.0]
+            }
+        );
+    }
+
+    #[test]
+    fn rasterize_fills_pixels() {
+        let mut r = RecordingRenderer::new(20.0, 10.0);
+        r.clear([1.0, 1.0, 1.0, 1.0]);
+        r.rectangle([1.0, 0.0, 0.0, 1.0], [5.0, 5.0, 5.0, 5.0]);
+
+        let image = r.rasterize();
+        assert_eq!(image.dimensions(), (20, 10));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(7, 7), Rgba([255, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,176 @@
+use piston_window::*;
+
+use super::{draw_focus_ring, Response, Widget, WidgetState};
+use crate::components::{
+    button::{draw_text, ButtonStyle, Pos, UIButton},
+    input::Action,
+    layout::{Anchor, Rect},
+    render::Renderer,
+};
+use crate::menu::theme::Theme;
+
+/// A question shown over everything else, its rect is the whole window.
+/// It takes every event until one of its buttons is picked or Escape is pressed.
+pub struct Dialog {
+    pub title: String,
+    pub message: String,
+    buttons: Vec<UIButton>,
+    // the button Enter picks, moved with the arrow keys
+    focused: usize,
+}
+
+impl Dialog {
+    pub fn new(title: &str, message: &str) -> Self {
+        Dialog {
+            title: title.to_string(),
+            message: message.to_string(),
+            buttons: Vec::new(),
+            focused: 0,
+        }
+    }
+
+    pub fn with_button(mut self, text: &str, style: ButtonStyle) -> Self {
+        self.buttons.push(UIButton::new(text, style, 20));
+        // the last button is the safe choice
+        self.focused = self.buttons.len() - 1;
+        self
+    }
+
+    /// Asks to confirm something that can't be undone,
+    /// `Picked(0)` means it was confirmed
+    pub fn confirm(title: &str, message: &str, action: &str) -> Self {
+        Dialog::new(title, message)
+            .with_button(action, ButtonStyle::Danger)
+            .with_button(" Cancel", ButtonStyle::Primary)
+    }
+
+    fn panel(rect: Rect) -> Rect {
+        rect.anchor(Anchor::Center, 460.0, 170.0)
+    }
+
+    // Buttons are lined up at the bottom right of the panel
+    fn button_rects(&self, rect: Rect) -> Vec<Rect> {
+        let (row, _) = Dialog::panel(rect).pad(20.0).split_bottom(40.0);
+        let count = self.buttons.len() as f64;
+        let width = 120.0 * count + 10.0 * (count - 1.0).max(0.0);
+        row.anchor(Anchor::Right, width, row.h)
+            .columns(self.buttons.len(), 10.0)
+    }
+}
+
+impl Widget for Dialog {
+    fn handle_event(&mut self, event: &Event, rect: Rect, state: WidgetState) -> Response {
+        let [x, y] = state.cursor;
+        let rects = self.button_rects(rect);
+        for (index, (button, button_rect)) in self.buttons.iter_mut().zip(rects).enumerate() {
+            // Enter is handled below, the buttons only take clicks
+            let state = WidgetState {
+                hovered: button_rect.contains(x, y),
+                focused: false,
+                ..state
+            };
+            if button.handle_event(event, button_rect, state) == Response::Clicked {
+                return Response::Picked(index);
+            }
+        }
+        match Action::from_event(event) {
+            Some(Action::Confirm) => Response::Picked(self.focused),
+            Some(Action::Back) => Response::Cancelled,
+            Some(Action::Left) => {
+                self.focused = self.focused.saturating_sub(1);
+                Response::Consumed
+            }
+            Some(Action::Right) => {
+                self.focused = (self.focused + 1).min(self.buttons.len().saturating_sub(1));
+                Response::Consumed
+            }
+            _ => Response::Consumed,
+        }
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, rect: Rect, theme: &Theme, state: WidgetState) {
+        // dim what is behind the dialog
+        r.rectangle([0.0, 0.0, 0.0, 0.5], rect.to_array());
+
+        let panel = Dialog::panel(rect);
+        r.rectangle(theme.panel, panel.to_array());
+        r.border_rectangle(theme.text, 1.0, panel.to_array());
+        let text = panel.pad(20.0);
+        draw_text(
+            r,
+            theme.text,
+            Pos {
+                x: text.x,
+                y: text.y + 20.0,
+            },
+            &self.title,
+            22,
+        );
+        draw_text(
+            r,
+            theme.text,
+            Pos {
+                x: text.x,
+                y: text.y + 54.0,
+            },
+            &self.message,
+            16,
+        );
+
+        let [x, y] = state.cursor;
+        let rects = self.button_rects(rect);
+        for (index, (button, button_rect)) in self.buttons.iter().zip(rects).enumerate() {
+            let state = WidgetState {
+                hovered: button_rect.contains(x, y),
+                focused: index == self.focused,
+                ..state
+            };
+            button.draw(r, button_rect, theme, state);
+            if state.focused {
+                draw_focus_ring(r, theme, button_rect);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{click, key};
+    use super::*;
+
+    #[test]
+    fn dialog_takes_every_event() {
+        let mut dialog = Dialog::confirm("Delete profile?", "It can't be undone.", "Delete");
+        let rect = Rect::window([1024.0, 576.0]);
+        let cancel = dialog.button_rects(rect)[1];
+        let state = |x, y| WidgetState {
+            cursor: [x, y],
+            hovered: true,
+            focused: false,
+        };
+
+        assert_eq!(
+            dialog.handle_event(&click(), rect, state(10.0, 10.0)),
+            Response::Consumed
+        );
+        assert_eq!(
+            dialog.handle_event(&click(), rect, state(cancel.x + 5.0, cancel.y + 5.0)),
+            Response::Picked(1)
+        );
+        assert_eq!(
+            dialog.handle_event(&key(Key::Escape), rect, state(0.0, 0.0)),
+            Response::Cancelled
+        );
+
+        // Enter picks Cancel unless the arrow keys moved to another button
+        assert_eq!(
+            dialog.handle_event(&key(Key::Return), rect, state(0.0, 0.0)),
+            Response::Picked(1)
+        );
+        dialog.handle_event(&key(Key::Left), rect, state(0.0, 0.0));
+        assert_eq!(
+            dialog.handle_event(&key(Key::Return), rect, state(0.0, 0.0)),
+            Response::Picked(0)
+        );
+    }
+}
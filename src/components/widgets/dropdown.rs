@@ -0,0 +1,234 @@
+use piston_window::*;
+
+use super::{left_click, Response, Widget, WidgetState};
+use crate::components::{
+    button::{draw_text, Pos},
+    input::Action,
+    layout::{Anchor, Rect},
+    render::Renderer,
+};
+use crate::menu::theme::Theme;
+
+/// Shows the picked option after a label, clicking it opens a list
+/// of all options below it
+pub struct Dropdown {
+    pub label: String,
+    pub options: Vec<String>,
+    pub selected: usize,
+    pub open: bool,
+    pub font_size: u32,
+    // the option Enter picks while the list is open
+    highlighted: usize,
+}
+
+impl Dropdown {
+    pub fn new(label: &str, options: Vec<String>, selected: usize) -> Self {
+        Dropdown {
+            label: label.to_string(),
+            options,
+            selected,
+            open: false,
+            font_size: 20,
+            highlighted: selected,
+        }
+    }
+
+    /// Selects the option with the given text, if there is one
+    pub fn select(&mut self, option: &str) {
+        if let Some(index) = self.options.iter().position(|o| o == option) {
+            self.selected = index;
+        }
+    }
+
+    // The rect of an option in the open list
+    fn option_rect(&self, rect: Rect, index: usize) -> Rect {
+        Rect::new(rect.x, rect.y + rect.h * (index + 1) as f64, rect.w, rect.h)
+    }
+}
+
+impl Widget for Dropdown {
+    fn handle_event(&mut self, event: &Event, rect: Rect, state: WidgetState) -> Response {
+        let [x, y] = state.cursor;
+        if left_click(event) {
+            if self.open {
+                self.open = false;
+                let picked = (0..self.options.len())
+                    .find(|index| self.option_rect(rect, *index).contains(x, y));
+                return match picked {
+                    Some(index) => {
+                        self.selected = index;
+                        Response::Picked(index)
+                    }
+                    None if rect.contains(x, y) => Response::Consumed,
+                    // a click somewhere else closes the list and goes on
+                    None => Response::Ignored,
+                };
+            }
+            if state.hovered {
+                self.open = true;
+                self.highlighted = self.selected;
+                return Response::Consumed;
+            }
+        }
+
+        if !state.focused && !self.open {
+            return Response::Ignored;
+        }
+        let last = self.options.len().saturating_sub(1);
+        match Action::from_event(event) {
+            Some(Action::Confirm) if !self.open => {
+                self.open = true;
+                self.highlighted = self.selected;
+                Response::Consumed
+            }
+            Some(Action::Confirm) if self.highlighted <= last => {
+                self.open = false;
+                self.selected = self.highlighted;
+                Response::Picked(self.selected)
+            }
+            Some(Action::Up) if self.open => {
+                self.highlighted = self.highlighted.saturating_sub(1);
+                Response::Consumed
+            }
+            Some(Action::Down) if self.open => {
+                self.highlighted = (self.highlighted + 1).min(last);
+                Response::Consumed
+            }
+            Some(Action::Back) if self.open => {
+                self.open = false;
+                Response::Consumed
+            }
+            _ => Response::Ignored,
+        }
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, rect: Rect, theme: &Theme, state: WidgetState) {
+        let [x, y] = state.cursor;
+        if state.hovered && rect.contains(x, y) {
+            r.rectangle(theme.hover, rect.to_array());
+        }
+        let selected = self.options.get(self.selected).map_or("", String::as_str);
+        let text_pos = |rect: Rect| Pos {
+            x: rect.x + 6.0,
+            y: rect.y + rect.h / 1.5,
+        };
+        draw_text(
+            r,
+            theme.text,
+            text_pos(rect),
+            &format!("{}: {}", self.label, selected),
+            self.font_size,
+        );
+
+        // small arrow on the right, pointing down while closed
+        let arrow = rect.pad(6.0).anchor(Anchor::Right, 12.0, 6.0);
+        let (top, bottom) = if self.open {
+            (arrow.y + arrow.h, arrow.y)
+        } else {
+            (arrow.y, arrow.y + arrow.h)
+        };
+        let middle = arrow.x + arrow.w / 2.0;
+        r.line(theme.text, 1.0, [arrow.x, top, middle, bottom]);
+        r.line(theme.text, 1.0, [middle, bottom, arrow.x + arrow.w, top]);
+
+        if !self.open {
+            return;
+        }
+        let list = Rect::new(
+            rect.x,
+            rect.y + rect.h,
+            rect.w,
+            rect.h * self.options.len() as f64,
+        );
+        r.rectangle(theme.panel, list.to_array());
+        for (index, option) in self.options.iter().enumerate() {
+            let option_rect = self.option_rect(rect, index);
+            if index == self.selected {
+                r.rectangle(theme.accent, option_rect.to_array());
+            } else if option_rect.contains(x, y) || index == self.highlighted {
+                r.rectangle(theme.hover, option_rect.to_array());
+            }
+            draw_text(r, theme.text, text_pos(option_rect), option, self.font_size);
+        }
+        r.border_rectangle(theme.text, 1.0, list.to_array());
+    }
+
+    fn hit_rect(&self, rect: Rect) -> Rect {
+        if self.open {
+            Rect::new(
+                rect.x,
+                rect.y,
+                rect.w,
+                rect.h * (self.options.len() + 1) as f64,
+            )
+        } else {
+            rect
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{click, cursor, key};
+    use super::super::{Focus, Widget};
+    use super::*;
+    use crate::components::button::{ButtonStyle, UIButton};
+
+    #[test]
+    fn open_list_covers_widgets_below() {
+        let options = ["Dark", "Light", "High Contrast"];
+        let mut dropdown = Dropdown::new("Theme", options.map(String::from).to_vec(), 0);
+        let mut button = UIButton::new("Reset", ButtonStyle::Danger, 20);
+        let mut focus = Focus::new();
+        let mut widgets: [(&mut dyn Widget, Rect); 2] = [
+            (&mut dropdown, Rect::new(0.0, 0.0, 200.0, 40.0)),
+            (&mut button, Rect::new(0.0, 60.0, 200.0, 40.0)),
+        ];
+
+        focus.dispatch(&cursor(100.0, 20.0), &mut widgets);
+        assert_eq!(
+            focus.dispatch(&click(), &mut widgets),
+            Some((0, Response::Consumed))
+        );
+
+        // the button is under the second option
+        focus.dispatch(&cursor(100.0, 90.0), &mut widgets);
+        assert_eq!(
+            focus.dispatch(&click(), &mut widgets),
+            Some((0, Response::Picked(1)))
+        );
+
+        // closed again, so the button gets the click
+        assert_eq!(
+            focus.dispatch(&click(), &mut widgets),
+            Some((1, Response::Clicked))
+        );
+        assert_eq!(dropdown.selected, 1);
+    }
+
+    #[test]
+    fn keys_pick_options() {
+        let options = ["Dark", "Light", "High Contrast"];
+        let mut dropdown = Dropdown::new("Theme", options.map(String::from).to_vec(), 0);
+        let rect = Rect::new(0.0, 0.0, 200.0, 40.0);
+        let focused = WidgetState {
+            focused: true,
+            ..WidgetState::default()
+        };
+
+        assert_eq!(
+            dropdown.handle_event(&key(Key::Down), rect, focused),
+            Response::Ignored
+        );
+        dropdown.handle_event(&key(Key::Return), rect, focused);
+        assert!(dropdown.open);
+        for _ in 0..3 {
+            dropdown.handle_event(&key(Key::Down), rect, focused);
+        }
+        assert_eq!(
+            dropdown.handle_event(&key(Key::Return), rect, focused),
+            Response::Picked(2)
+        );
+        assert!(!dropdown.open);
+    }
+}
@@ -0,0 +1,169 @@
+use piston_window::*;
+
+use super::{left_click, Response, Widget, WidgetState};
+use crate::components::{
+    button::{draw_text, Pos},
+    input::Action,
+    layout::{Anchor, Rect},
+    render::Renderer,
+};
+use crate::menu::theme::Theme;
+
+/// Rows of text where one can be selected,
+/// scrolls with the mouse wheel when they don't all fit
+pub struct List {
+    pub items: Vec<String>,
+    pub selected: Option<usize>,
+    row_height: f64,
+    // first row shown
+    scroll: usize,
+}
+
+impl List {
+    pub fn new(row_height: f64) -> Self {
+        List {
+            items: Vec::new(),
+            selected: None,
+            row_height,
+            scroll: 0,
+        }
+    }
+
+    fn visible_rows(&self, rect: Rect) -> usize {
+        (rect.h / self.row_height) as usize
+    }
+
+    /// Shows `items`, scrolling back if the list got shorter
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.scroll = self.scroll.min(self.items.len().saturating_sub(1));
+    }
+
+    // The rect of the item at `index`, if it is scrolled into view
+    fn row_rect(&self, rect: Rect, index: usize) -> Option<Rect> {
+        let row = index.checked_sub(self.scroll)?;
+        if row >= self.visible_rows(rect) {
+            return None;
+        }
+        Some(Rect::new(
+            rect.x,
+            rect.y + self.row_height * row as f64,
+            rect.w,
+            self.row_height - 4.0,
+        ))
+    }
+}
+
+impl Widget for List {
+    fn handle_event(&mut self, event: &Event, rect: Rect, state: WidgetState) -> Response {
+        if state.focused {
+            // Up and Down pick the row next to the selected one,
+            // at the ends they are left for moving the focus
+            let next = match (Action::from_event(event), self.selected) {
+                (Some(Action::Up), Some(index)) => index.checked_sub(1),
+                (Some(Action::Down), Some(index)) => Some(index + 1),
+                (Some(Action::Down), None) => Some(0),
+                _ => None,
+            };
+            if let Some(index) = next.filter(|index| *index < self.items.len()) {
+                self.selected = Some(index);
+                // keep the picked row in view
+                let rows = self.visible_rows(rect).max(1);
+                self.scroll = self.scroll.min(index).max((index + 1).saturating_sub(rows));
+                return Response::Picked(index);
+            }
+        }
+        if !state.hovered {
+            return Response::Ignored;
+        }
+        if let Some([_, dy]) = event.mouse_scroll_args() {
+            let last = self.items.len().saturating_sub(self.visible_rows(rect));
+            self.scroll = if dy > 0.0 {
+                self.scroll.saturating_sub(1)
+            } else {
+                (self.scroll + 1).min(last)
+            };
+            return Response::Consumed;
+        }
+        if left_click(event) {
+            let [x, y] = state.cursor;
+            let picked = (0..self.items.len()).find(|index| {
+                self.row_rect(rect, *index)
+                    .is_some_and(|row| row.contains(x, y))
+            });
+            if let Some(index) = picked {
+                self.selected = Some(index);
+                return Response::Picked(index);
+            }
+            return Response::Consumed;
+        }
+        Response::Ignored
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, rect: Rect, theme: &Theme, state: WidgetState) {
+        let [x, y] = state.cursor;
+        for (index, item) in self.items.iter().enumerate() {
+            let row = match self.row_rect(rect, index) {
+                Some(row) => row,
+                None => continue,
+            };
+            let text_color = if self.selected == Some(index) {
+                r.rectangle(theme.button, row.to_array());
+                theme.button_text
+            } else {
+                if state.hovered && row.contains(x, y) {
+                    r.rectangle(theme.hover, row.to_array());
+                }
+                theme.text
+            };
+            draw_text(
+                r,
+                text_color,
+                Pos {
+                    x: row.x + 6.0,
+                    y: row.y + row.h / 1.5,
+                },
+                item,
+                20,
+            );
+        }
+
+        // scrollbar, only when some rows are hidden
+        let visible = self.visible_rows(rect);
+        if self.items.len() > visible {
+            let height = rect.h * visible as f64 / self.items.len() as f64;
+            let top = rect.h * self.scroll as f64 / self.items.len() as f64;
+            let bar = rect.anchor(Anchor::TopRight, 4.0, height);
+            r.rectangle(theme.text, [bar.x, bar.y + top, bar.w, bar.h]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::click;
+    use super::*;
+
+    #[test]
+    fn scrolled_rows_are_picked() {
+        let mut list = List::new(40.0);
+        list.set_items((1..=5).map(|n| format!("Profile {}", n)).collect());
+        let rect = Rect::new(0.0, 0.0, 200.0, 120.0);
+        let state = WidgetState {
+            cursor: [50.0, 10.0],
+            hovered: true,
+            focused: false,
+        };
+
+        let scroll = Event::Input(Input::Move(Motion::MouseScroll([0.0, -1.0])), None);
+        for _ in 0..4 {
+            list.handle_event(&scroll, rect, state);
+        }
+        // only three rows fit, so it stops at the third one
+        assert_eq!(
+            list.handle_event(&click(), rect, state),
+            Response::Picked(2)
+        );
+        assert_eq!(list.selected, Some(2));
+    }
+}
@@ -0,0 +1,332 @@
+/*!
+    Widgets shared by the menu and the games: buttons, toggles, sliders,
+    text inputs, dropdowns, lists and dialogs.
+
+    Widgets keep their own state, like the text typed or whether a dropdown
+    is open, but not where they are. The owner works out a rect from the
+    layout every time and passes it in, so widgets follow the window size.
+    `Focus` sends events to a group of widgets and remembers which one
+    has the keyboard, Tab and Shift+Tab move the keyboard between them.
+*/
+
+use piston_window::*;
+
+use super::{layout::Rect, render::Renderer};
+use crate::menu::theme::Theme;
+
+pub mod dialog;
+pub mod dropdown;
+pub mod list;
+pub mod slider;
+pub mod text_input;
+pub mod toggle;
+
+pub use dialog::Dialog;
+pub use dropdown::Dropdown;
+pub use list::List;
+pub use slider::Slider;
+pub use text_input::TextInput;
+pub use toggle::Toggle;
+
+/// What a widget did with an event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Response {
+    /// The event wasn't meant for the widget
+    Ignored,
+    /// The widget used the event, but nothing changed for its owner
+    Consumed,
+    Clicked,
+    /// The value of a toggle, slider or text input changed
+    Changed,
+    /// Enter was pressed in a text input
+    Submitted,
+    /// Escape was pressed
+    Cancelled,
+    /// An entry of a dropdown, list or dialog was picked
+    Picked(usize),
+}
+
+/// How the mouse and keyboard relate to a widget when it gets an event or is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WidgetState {
+    pub cursor: [f64; 2],
+    /// The mouse is over the widget
+    pub hovered: bool,
+    /// The widget has the keyboard
+    pub focused: bool,
+}
+
+pub trait Widget {
+    fn handle_event(&mut self, event: &Event, rect: Rect, state: WidgetState) -> Response;
+
+    fn draw(&self, r: &mut dyn Renderer, rect: Rect, theme: &Theme, state: WidgetState);
+
+    /// Whether the widget can get the keyboard with Tab
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    /// Whether clicking the widget gives it the keyboard,
+    /// buttons don't keep it so the keys go back to the game
+    fn focus_on_click(&self) -> bool {
+        self.focusable()
+    }
+
+    /// The area that takes clicks, an open dropdown covers more than its rect
+    fn hit_rect(&self, rect: Rect) -> Rect {
+        rect
+    }
+}
+
+pub fn left_click(event: &Event) -> bool {
+    event.press_args() == Some(Button::Mouse(MouseButton::Left))
+}
+
+pub fn key_press(event: &Event) -> Option<Key> {
+    match event.press_args() {
+        Some(Button::Keyboard(key)) => Some(key),
+        _ => None,
+    }
+}
+
+/// Outlines the widget that has the keyboard
+pub fn draw_focus_ring(r: &mut dyn Renderer, theme: &Theme, rect: Rect) {
+    r.border_rectangle(theme.text, 2.0, rect.pad(-4.0).to_array());
+}
+
+/// Sends events to a group of widgets and keeps track of the focused one.
+/// Widgets are passed with their rects in the same order every time,
+/// the focus is remembered as an index into them.
+#[derive(Debug, Default)]
+pub struct Focus {
+    cursor: [f64; 2],
+    focused: Option<usize>,
+    // Shift+Tab goes backwards
+    shift: bool,
+    // the focus ring is only shown after moving the focus with the keyboard
+    ring: bool,
+}
+
+impl Focus {
+    pub fn new() -> Self {
+        Focus::default()
+    }
+
+    pub fn cursor(&self) -> [f64; 2] {
+        self.cursor
+    }
+
+    pub fn set_focused(&mut self, focused: Option<usize>) {
+        self.focused = focused;
+    }
+
+    /// Moves the keyboard to the next focusable widget, or the previous one
+    /// if `forward` is false, wrapping around at the ends
+    pub fn step(&mut self, forward: bool, widgets: &[(&mut dyn Widget, Rect)]) -> Option<usize> {
+        let count = widgets.len();
+        let start = match (self.focused, forward) {
+            (Some(index), true) => index + 1,
+            (Some(index), false) => index + count - 1,
+            (None, true) => 0,
+            (None, false) => count.saturating_sub(1),
+        };
+        let next = (0..count)
+            .map(|offset| {
+                if forward {
+                    (start + offset) % count
+                } else {
+                    (start + count - offset) % count
+                }
+            })
+            .find(|index| widgets[*index].0.focusable());
+        if next.is_some() {
+            self.focused = next;
+            self.ring = true;
+        }
+        next
+    }
+
+    pub fn state(&self, index: usize, widget: &dyn Widget, rect: Rect) -> WidgetState {
+        let [x, y] = self.cursor;
+        WidgetState {
+            cursor: self.cursor,
+            hovered: widget.hit_rect(rect).contains(x, y),
+            focused: self.focused == Some(index),
+        }
+    }
+
+    /// Passes the event to the widgets until one uses it,
+    /// returns which one and what it did
+    pub fn dispatch(
+        &mut self,
+        event: &Event,
+        widgets: &mut [(&mut dyn Widget, Rect)],
+    ) -> Option<(usize, Response)> {
+        if let Some(cursor) = event.mouse_cursor_args() {
+            self.cursor = cursor;
+        }
+        if let Some(ButtonArgs {
+            button: Button::Keyboard(Key::LShift | Key::RShift),
+            state,
+            ..
+        }) = event.button_args()
+        {
+            self.shift = state == ButtonState::Press;
+        }
+        if self.focused.is_some_and(|index| index >= widgets.len()) {
+            self.focused = None;
+        }
+
+        // the focused widget goes first, so an open dropdown can take clicks on its options
+        let first = self.focused;
+        if let Some(index) = first {
+            let state = self.state(index, &*widgets[index].0, widgets[index].1);
+            let (widget, rect) = &mut widgets[index];
+            match widget.handle_event(event, *rect, state) {
+                Response::Ignored => (),
+                response => return Some((index, response)),
+            }
+        }
+
+        // clicking moves the keyboard to the widget under the mouse
+        if left_click(event) {
+            let [x, y] = self.cursor;
+            self.focused = widgets.iter().position(|(widget, rect)| {
+                widget.focus_on_click() && widget.hit_rect(*rect).contains(x, y)
+            });
+            self.ring = false;
+        }
+
+        for index in (0..widgets.len()).filter(|index| Some(*index) != first) {
+            let state = self.state(index, &*widgets[index].0, widgets[index].1);
+            let (widget, rect) = &mut widgets[index];
+            match widget.handle_event(event, *rect, state) {
+                Response::Ignored => (),
+                response => return Some((index, response)),
+            }
+        }
+
+        // Tab only moves the focus when the focused widget didn't use it
+        if key_press(event) == Some(Key::Tab) {
+            let forward = !self.shift;
+            return self
+                .step(forward, widgets)
+                .map(|index| (index, Response::Consumed));
+        }
+        None
+    }
+
+    /// Draws the widgets, the focused one last so an open dropdown is on top
+    pub fn draw(&self, r: &mut dyn Renderer, theme: &Theme, widgets: &[(&dyn Widget, Rect)]) {
+        let order = (0..widgets.len())
+            .filter(|index| Some(*index) != self.focused)
+            .chain(self.focused.filter(|index| *index < widgets.len()));
+        for index in order {
+            let (widget, rect) = widgets[index];
+            widget.draw(r, rect, theme, self.state(index, widget, rect));
+            if self.ring && self.focused == Some(index) {
+                draw_focus_ring(r, theme, rect);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::components::button::{ButtonStyle, UIButton};
+
+    pub fn cursor(x: f64, y: f64) -> Event {
+        Event::Input(Input::Move(Motion::MouseCursor([x, y])), None)
+    }
+
+    pub fn press(button: Button) -> Event {
+        Event::Input(
+            Input::Button(ButtonArgs {
+                state: ButtonState::Press,
+                button,
+                scancode: None,
+            }),
+            None,
+        )
+    }
+
+    pub fn click() -> Event {
+        press(Button::Mouse(MouseButton::Left))
+    }
+
+    pub fn key(key: Key) -> Event {
+        press(Button::Keyboard(key))
+    }
+
+    pub fn text(text: &str) -> Event {
+        Event::Input(Input::Text(text.to_string()), None)
+    }
+
+    #[test]
+    fn clicks_move_the_focus() {
+        let mut focus = Focus::new();
+        let mut button = UIButton::new("Reset", ButtonStyle::Danger, 24);
+        let mut input = TextInput::new(5);
+        let button_rect = Rect::new(0.0, 0.0, 100.0, 40.0);
+        let input_rect = Rect::new(0.0, 50.0, 100.0, 40.0);
+
+        // keys go nowhere until something is focused
+        let mut widgets: [(&mut dyn Widget, Rect); 2] =
+            [(&mut button, button_rect), (&mut input, input_rect)];
+        assert_eq!(focus.dispatch(&text("ab"), &mut widgets), None);
+
+        focus.dispatch(&cursor(50.0, 70.0), &mut widgets);
+        assert_eq!(
+            focus.dispatch(&click(), &mut widgets),
+            Some((1, Response::Consumed))
+        );
+        assert_eq!(focus.focused, Some(1));
+        assert_eq!(
+            focus.dispatch(&text("ab"), &mut widgets),
+            Some((1, Response::Changed))
+        );
+
+        focus.dispatch(&cursor(50.0, 20.0), &mut widgets);
+        assert_eq!(
+            focus.dispatch(&click(), &mut widgets),
+            Some((0, Response::Clicked))
+        );
+        // clicked buttons don't take the keyboard
+        assert_eq!(focus.focused, None);
+        assert_eq!(input.text, "ab");
+    }
+
+    #[test]
+    fn tab_moves_the_focus() {
+        let mut focus = Focus::new();
+        let mut first = UIButton::new("New", ButtonStyle::Primary, 20);
+        let mut list = List::new(40.0);
+        let mut last = UIButton::new("Delete", ButtonStyle::Danger, 20);
+        let rect = Rect::new(0.0, 0.0, 100.0, 40.0);
+        let mut widgets: [(&mut dyn Widget, Rect); 3] =
+            [(&mut first, rect), (&mut list, rect), (&mut last, rect)];
+
+        assert_eq!(
+            focus.dispatch(&key(Key::Tab), &mut widgets),
+            Some((0, Response::Consumed))
+        );
+        focus.dispatch(&key(Key::Tab), &mut widgets);
+        focus.dispatch(&key(Key::Tab), &mut widgets);
+        assert_eq!(focus.focused, Some(2));
+
+        // wraps around at the end
+        focus.dispatch(&key(Key::Tab), &mut widgets);
+        assert_eq!(focus.focused, Some(0));
+
+        focus.dispatch(&key(Key::LShift), &mut widgets);
+        focus.dispatch(&key(Key::Tab), &mut widgets);
+        assert_eq!(focus.focused, Some(2));
+
+        // the focused button is pressed with Enter or Space
+        assert_eq!(
+            focus.dispatch(&key(Key::Space), &mut widgets),
+            Some((2, Response::Clicked))
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use piston_window::*;
+
+use super::{left_click, Response, Widget, WidgetState};
+use crate::components::{
+    button::{draw_text, Pos},
+    input::Action,
+    layout::{Anchor, Rect},
+    render::Renderer,
+};
+use crate::menu::theme::Theme;
+
+/// Picks a number between `min` and `max` in steps of `step`,
+/// by dragging the knob or with the arrow keys
+pub struct Slider {
+    pub label: String,
+    pub value: u32,
+    min: u32,
+    max: u32,
+    step: u32,
+    dragging: bool,
+}
+
+impl Slider {
+    pub fn new(label: &str, value: u32, min: u32, max: u32, step: u32) -> Self {
+        Slider {
+            label: label.to_string(),
+            value: value.clamp(min, max),
+            min,
+            max,
+            step,
+            dragging: false,
+        }
+    }
+
+    // The line the knob moves along, below the label
+    fn track(rect: Rect) -> Rect {
+        let (_, bottom) = rect.split_top(rect.h / 2.0);
+        bottom.pad(6.0).anchor(Anchor::Center, bottom.w - 12.0, 4.0)
+    }
+
+    // Sets the value from where the mouse is on the track
+    fn set_from(&mut self, x: f64, rect: Rect) -> Response {
+        let track = Slider::track(rect);
+        let fraction = ((x - track.x) / track.w).clamp(0.0, 1.0);
+        let steps = ((self.max - self.min) as f64 * fraction / self.step as f64).round() as u32;
+        self.set((self.min + steps * self.step).min(self.max))
+    }
+
+    fn set(&mut self, value: u32) -> Response {
+        let value = value.clamp(self.min, self.max);
+        if value == self.value {
+            Response::Consumed
+        } else {
+            self.value = value;
+            Response::Changed
+        }
+    }
+}
+
+impl Widget for Slider {
+    fn handle_event(&mut self, event: &Event, rect: Rect, state: WidgetState) -> Response {
+        if state.hovered && left_click(event) {
+            self.dragging = true;
+            return self.set_from(state.cursor[0], rect);
+        }
+        if self.dragging {
+            if event.release_args() == Some(Button::Mouse(MouseButton::Left)) {
+                self.dragging = false;
+                return Response::Consumed;
+            }
+            if let Some([x, _]) = event.mouse_cursor_args() {
+                return self.set_from(x, rect);
+            }
+        }
+        if state.focused {
+            match Action::from_event(event) {
+                Some(Action::Left) => return self.set(self.value.saturating_sub(self.step)),
+                Some(Action::Right) => return self.set(self.value + self.step),
+                _ => (),
+            }
+        }
+        Response::Ignored
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, rect: Rect, theme: &Theme, state: WidgetState) {
+        draw_text(
+            r,
+            theme.text,
+            Pos {
+                x: rect.x + 6.0,
+                y: rect.y + rect.h / 2.0 - 4.0,
+            },
+            &format!("{}: {}", self.label, self.value),
+            18,
+        );
+
+        let track = Slider::track(rect);
+        r.rectangle(theme.text, track.to_array());
+        let fraction = (self.value - self.min) as f64 / (self.max - self.min).max(1) as f64;
+        let knob = Rect::new(
+            track.x + track.w * fraction - 7.0,
+            track.y - 8.0,
+            14.0,
+            20.0,
+        );
+        let color = if state.hovered || self.dragging {
+            theme.hover
+        } else {
+            theme.button
+        };
+        r.round_rectangle(color, 3.0, knob.to_array());
+        r.border_rectangle(theme.text, 1.0, knob.to_array());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{click, cursor, key};
+    use super::*;
+
+    #[test]
+    fn dragging_snaps_to_steps() {
+        let mut slider = Slider::new("Size", 20, 10, 40, 5);
+        let rect = Rect::new(0.0, 0.0, 112.0, 40.0);
+        let track = Slider::track(rect);
+        let state = |x: f64, focused| WidgetState {
+            cursor: [x, 30.0],
+            hovered: rect.contains(x, 30.0),
+            focused,
+        };
+
+        // pressing at the right end jumps there
+        let x = track.x + track.w;
+        assert_eq!(
+            slider.handle_event(&click(), rect, state(x, false)),
+            Response::Changed
+        );
+        assert_eq!(slider.value, 40);
+
+        // dragging keeps going outside the rect until the button is let go
+        let x = track.x + track.w * 0.45;
+        slider.handle_event(&cursor(x, 100.0), rect, state(x, false));
+        assert_eq!(slider.value, 25);
+
+        assert_eq!(
+            slider.handle_event(&key(Key::Left), rect, state(x, true)),
+            Response::Changed
+        );
+        assert_eq!(slider.value, 20);
+    }
+}
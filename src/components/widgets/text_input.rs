@@ -0,0 +1,143 @@
+use piston_window::*;
+
+use super::{key_press, left_click, Response, Widget, WidgetState};
+use crate::components::{
+    button::{draw_text, Pos},
+    layout::Rect,
+    render::Renderer,
+};
+use crate::menu::theme::Theme;
+
+/// A single line of text typed on the keyboard
+pub struct TextInput {
+    pub text: String,
+    max_len: usize,
+    // turns a typed character into the one added, None drops it
+    filter: fn(char) -> Option<char>,
+    pub font_size: u32,
+}
+
+impl TextInput {
+    pub fn new(max_len: usize) -> Self {
+        TextInput {
+            text: String::new(),
+            max_len,
+            filter: |c| Some(c).filter(|c| !c.is_control()),
+            font_size: 18,
+        }
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text = text.chars().take(self.max_len).collect();
+        self
+    }
+
+    pub fn with_filter(mut self, filter: fn(char) -> Option<char>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// The typed text without spaces around it
+    pub fn value(&self) -> &str {
+        self.text.trim()
+    }
+}
+
+impl Widget for TextInput {
+    fn handle_event(&mut self, event: &Event, _rect: Rect, state: WidgetState) -> Response {
+        if !state.focused {
+            return Response::Ignored;
+        }
+
+        if let Some(text) = event.text_args() {
+            let before = self.text.len();
+            for c in text.chars().filter_map(self.filter) {
+                if self.text.chars().count() < self.max_len {
+                    self.text.push(c);
+                }
+            }
+            return if self.text.len() == before {
+                Response::Consumed
+            } else {
+                Response::Changed
+            };
+        }
+
+        // the input takes every key while it has the keyboard
+        match key_press(event) {
+            Some(Key::Backspace) => match self.text.pop() {
+                Some(_) => Response::Changed,
+                None => Response::Consumed,
+            },
+            Some(Key::Return) => Response::Submitted,
+            Some(Key::Escape) => Response::Cancelled,
+            Some(_) => Response::Consumed,
+            None if left_click(event) && state.hovered => Response::Consumed,
+            None => Response::Ignored,
+        }
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, rect: Rect, theme: &Theme, state: WidgetState) {
+        r.border_rectangle(theme.text, 1.0, rect.to_array());
+        // the caret shows which input has the keyboard
+        let text = if state.focused {
+            format!("{}|", self.text)
+        } else {
+            self.text.clone()
+        };
+        draw_text(
+            r,
+            theme.text,
+            Pos {
+                x: rect.x + 8.0,
+                y: rect.y + (rect.h + self.font_size as f64) / 2.0 - 2.0,
+            },
+            &text,
+            self.font_size,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{key, text};
+    use super::*;
+
+    #[test]
+    fn typing_is_filtered_and_limited() {
+        let mut input = TextInput::new(3)
+            .with_filter(|c| Some(c.to_ascii_uppercase()).filter(char::is_ascii_alphabetic));
+        let rect = Rect::new(0.0, 0.0, 100.0, 30.0);
+        let focused = WidgetState {
+            focused: true,
+            ..WidgetState::default()
+        };
+
+        assert_eq!(
+            input.handle_event(&text("a"), rect, WidgetState::default()),
+            Response::Ignored
+        );
+        assert_eq!(
+            input.handle_event(&text("a1bcd"), rect, focused),
+            Response::Changed
+        );
+        assert_eq!(input.text, "ABC");
+        assert_eq!(
+            input.handle_event(&text("e"), rect, focused),
+            Response::Consumed
+        );
+        assert_eq!(
+            input.handle_event(&key(Key::Backspace), rect, focused),
+            Response::Changed
+        );
+        assert_eq!(input.text, "AB");
+        assert_eq!(
+            input.handle_event(&key(Key::Return), rect, focused),
+            Response::Submitted
+        );
+        assert_eq!(
+            input.handle_event(&key(Key::Escape), rect, focused),
+            Response::Cancelled
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use piston_window::*;
+
+use super::{left_click, Response, Widget, WidgetState};
+use crate::components::{
+    button::{draw_text, Pos},
+    input::Action,
+    layout::{Anchor, Rect},
+    render::Renderer,
+};
+use crate::menu::theme::Theme;
+
+/// A label with a switch that turns something on or off
+pub struct Toggle {
+    pub label: String,
+    pub on: bool,
+}
+
+impl Toggle {
+    pub fn new(label: &str, on: bool) -> Self {
+        Toggle {
+            label: label.to_string(),
+            on,
+        }
+    }
+}
+
+impl Widget for Toggle {
+    fn handle_event(&mut self, event: &Event, _rect: Rect, state: WidgetState) -> Response {
+        let pressed = Action::from_event(event) == Some(Action::Confirm);
+        if (state.hovered && left_click(event)) || (state.focused && pressed) {
+            self.on = !self.on;
+            Response::Changed
+        } else {
+            Response::Ignored
+        }
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, rect: Rect, theme: &Theme, state: WidgetState) {
+        if state.hovered {
+            r.rectangle(theme.hover, rect.to_array());
+        }
+        draw_text(
+            r,
+            theme.text,
+            Pos {
+                x: rect.x + 6.0,
+                y: rect.y + rect.h / 1.5,
+            },
+            &self.label,
+            20,
+        );
+
+        // the knob sits on the right when on
+        let track = rect.pad(6.0).anchor(Anchor::Right, 44.0, 22.0);
+        let (track_color, knob) = if self.on {
+            (theme.accent, track.anchor(Anchor::Right, 22.0, 22.0))
+        } else {
+            (theme.panel, track.anchor(Anchor::Left, 22.0, 22.0))
+        };
+        r.round_rectangle(track_color, 11.0, track.to_array());
+        r.border_rectangle(theme.text, 1.0, track.to_array());
+        r.round_rectangle(theme.text, 9.0, knob.pad(2.0).to_array());
+    }
+}
@@ -0,0 +1,145 @@
+/*!
+    Opens the main window and switches it between windowed, borderless and fullscreen.
+    Where the window was and how big it was is kept in the config,
+    so the next launch opens it in the same place.
+*/
+
+use piston_window::{AdvancedWindow, EventLoop, PistonWindow, Position, Window, WindowSettings};
+use serde::{Deserialize, Serialize};
+use winit::{dpi::LogicalSize, window::Fullscreen};
+
+use super::application::{DEFAULT_HEIGHT, DEFAULT_WIDTH, MIN_HEIGHT, MIN_WIDTH};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    /// A normal window without the title bar and borders
+    Borderless,
+    /// Covers the whole monitor the window is on
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [
+        WindowMode::Windowed,
+        WindowMode::Borderless,
+        WindowMode::Fullscreen,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "Windowed",
+            WindowMode::Borderless => "Borderless",
+            WindowMode::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+/// Where the window is, how big it is and how it is shown
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct WindowGeometry {
+    /// Top left corner in logical pixels, the system places the window if unknown
+    pub position: Option<[i32; 2]>,
+    /// Size in logical pixels outside of fullscreen
+    pub size: [u32; 2],
+    pub mode: WindowMode,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        WindowGeometry {
+            position: None,
+            size: [DEFAULT_WIDTH, DEFAULT_HEIGHT],
+            mode: WindowMode::Windowed,
+        }
+    }
+}
+
+impl WindowGeometry {
+    /// The saved size, but never smaller than the window is allowed to be
+    pub fn clamped_size(&self) -> [u32; 2] {
+        [self.size[0].max(MIN_WIDTH), self.size[1].max(MIN_HEIGHT)]
+    }
+}
+
+pub fn create_window(geometry: &WindowGeometry) -> PistonWindow {
+    let mut window = WindowSettings::new("UnoLife", geometry.clamped_size())
+        .resizable(true)
+        .decorated(geometry.mode == WindowMode::Windowed)
+        .build::<PistonWindow>()
+        .unwrap()
+        .max_fps(60);
+
+    // sizes are logical, so this is the same on HiDPI screens
+    window
+        .window
+        .ctx
+        .window()
+        .set_min_inner_size(Some(LogicalSize::new(MIN_WIDTH, MIN_HEIGHT)));
+
+    if let Some([x, y]) = geometry.position {
+        if is_on_screen(&window, [x, y]) {
+            window.set_position(Position { x, y });
+        }
+    }
+    if geometry.mode == WindowMode::Fullscreen {
+        set_mode(&mut window, WindowMode::Fullscreen);
+    }
+    window
+}
+
+/// Shows the window in the given mode
+pub fn set_mode(window: &mut PistonWindow, mode: WindowMode) {
+    let window = window.window.ctx.window();
+    window.set_decorations(mode == WindowMode::Windowed);
+    window.set_fullscreen(match mode {
+        WindowMode::Fullscreen => Some(Fullscreen::Borderless(window.current_monitor())),
+        WindowMode::Windowed | WindowMode::Borderless => None,
+    });
+}
+
+/// Stores the current position and size of the window in `geometry`.
+/// Nothing is taken while fullscreen, so leaving it restores the old window.
+pub fn update_geometry(window: &PistonWindow, geometry: &mut WindowGeometry) {
+    if geometry.mode == WindowMode::Fullscreen {
+        return;
+    }
+    let size = window.size();
+    geometry.size = [size.width as u32, size.height as u32];
+    geometry.position = window
+        .get_position()
+        .map(|position| [position.x, position.y]);
+}
+
+// A saved position is only used if it is still on one of the monitors,
+// the window would be lost after unplugging a second screen otherwise
+fn is_on_screen(window: &PistonWindow, position: [i32; 2]) -> bool {
+    let window = window.window.ctx.window();
+    let scale = window.scale_factor();
+    // monitors are measured in physical pixels
+    let x = (position[0] as f64 * scale) as i32;
+    let y = (position[1] as f64 * scale) as i32;
+    window.available_monitors().any(|monitor| {
+        let (corner, size) = (monitor.position(), monitor.size());
+        x >= corner.x
+            && x < corner.x + size.width as i32
+            && y >= corner.y
+            && y < corner.y + size.height as i32
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_are_clamped() {
+        let geometry = WindowGeometry {
+            size: [300, 2000],
+            ..WindowGeometry::default()
+        };
+        assert_eq!(geometry.clamped_size(), [MIN_WIDTH, 2000]);
+    }
+}
@@ -1,10 +1,23 @@
 extern crate piston_window;
 
-use std::{fs, path::PathBuf};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
-use crate::components::application::MiniApp;
+use components::{
+    application::{DEFAULT_HEIGHT, DEFAULT_WIDTH},
+    render::RecordingRenderer,
+    window::{self, create_window},
+};
 use lazy_static::lazy_static;
-use menu::{config::Config, highscores::HighScores, ui::MainMenu};
+use menu::{
+    archive::{self, ImportMode},
+    config::Config,
+    highscores::HighScores,
+    profiles::Profiles,
+    ui::{MainMenu, Tab},
+};
 use piston_window::*;
 use winit::window::Icon;
 
@@ -16,6 +29,7 @@ mod reddit_meme;
 mod snake;
 mod tictactoe;
 mod twenty48;
+mod ultimate;
 mod wordle;
 
 lazy_static! {
@@ -24,19 +38,76 @@ lazy_static! {
         .unwrap();
 }
 
-fn create_window(dim: [u32; 2]) -> PistonWindow {
-    WindowSettings::new("UnoLife", dim)
-        .resizable(false)
-        .build::<PistonWindow>()
-        .unwrap()
-        .max_fps(60)
+/// Draws a tab without opening a window and saves it as a PNG.
+/// Used as `timathon --screenshot <tab or app id> <file.png>`.
+fn screenshot(id: &str, file: &str) {
+    let mut main_menu = MainMenu::new();
+    main_menu.profiles = Profiles::fetch_profiles();
+    let profile = main_menu.profiles.active();
+    let config = Config::fetch_config(profile.config_location());
+    let highscores = HighScores::fetch_scores(profile.highscores_location());
+
+    main_menu.tab = match main_menu.tab_by_id(id) {
+        Some(tab) => tab,
+        None => {
+            eprintln!("Unknown tab or app: {}", id);
+            std::process::exit(1);
+        }
+    };
+    main_menu.sync_settings(&config);
+
+    let mut renderer = RecordingRenderer::new(DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64);
+    main_menu.draw(&mut renderer, &config, &highscores);
+    if let Err(err) = renderer.save_png(file) {
+        eprintln!("Could not save screenshot: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Exports or imports the save state of the active profile.
+/// Used as `timathon --export <file>` and `timathon --import <file> [--merge | --replace]`.
+fn archive(file: &str, import: Option<ImportMode>) {
+    let profiles = Profiles::fetch_profiles();
+    let profile = profiles.active();
+    let mut config = Config::fetch_config(profile.config_location());
+    let mut highscores = HighScores::fetch_scores(profile.highscores_location());
+
+    let path = Path::new(file);
+    let result = match import {
+        Some(mode) => archive::import(path, mode, &mut config, &mut highscores),
+        None => archive::export(path, &config, &highscores),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
 }
 
 fn main() {
-    let mut window = create_window([
-        components::application::DEFAULT_WIDTH,
-        components::application::DEFAULT_HEIGHT,
-    ]);
+    let args: Vec<String> = env::args().collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [_, "--screenshot", id, file] => return screenshot(id, file),
+        [_, "--export", file] => return archive(file, None),
+        [_, "--import", file] | [_, "--import", file, "--merge"] => {
+            return archive(file, Some(ImportMode::Merge))
+        }
+        [_, "--import", file, "--replace"] => return archive(file, Some(ImportMode::Replace)),
+        _ => (),
+    }
+
+    // Load the active profile, it knows where the window was left
+    let mut main_menu = MainMenu::new();
+    main_menu.profiles = Profiles::fetch_profiles();
+    let profile = main_menu.profiles.active();
+    let mut config = Config::fetch_config(profile.config_location());
+    let mut highscores = HighScores::fetch_scores(profile.highscores_location());
+
+    let mut window = create_window(&config.options.window);
 
     // Set the Icon
     let file = ASSETS.join("unolife_logo.rgba");
@@ -48,15 +119,26 @@ fn main() {
         .set_window_icon(Icon::from_rgba(data, 500, 500).ok());
 
     // Initialize main menu
-    let mut main_menu = MainMenu::new();
-    let mut config = Config::fetch_config();
-    let mut highscores = HighScores::fetch_scores();
+    main_menu.switch_tab(Tab::Home, &mut window, &mut config, &mut highscores);
 
     // Load font
     let mut glyphs = window.load_font(ASSETS.join("Roboto-Regular.ttf")).unwrap();
 
+    #[cfg(feature = "gamepad")]
+    let mut gamepads = components::gamepad::Gamepads::new();
+
     while let Some(event) = window.next() {
-        main_menu.render(
+        #[cfg(feature = "gamepad")]
+        for event in gamepads.poll() {
+            main_menu.event(
+                &mut window,
+                &event,
+                &mut glyphs,
+                &mut config,
+                &mut highscores,
+            );
+        }
+        main_menu.event(
             &mut window,
             &event,
             &mut glyphs,
@@ -64,4 +146,8 @@ fn main() {
             &mut highscores,
         );
     }
+
+    // remember the window for the next launch
+    window::update_geometry(&window, &mut config.options.window);
+    config.save_config();
 }
@@ -0,0 +1,222 @@
+/*!
+    Exports the whole save state of a profile to a single json file and
+    imports it again, so records can be moved between machines.
+
+    Every section keeps its own `schema_version`, so archives made by older
+    versions are migrated the same way as the regular save files.
+*/
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use super::{
+    config::{Config, ConfigOptions},
+    highscores::{HighScoreOptions, HighScores},
+    stats::Stats,
+    storage::{self, Schema, StorageError},
+};
+
+/// Version of the archive layout itself
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    /// Keeps the best of both for every game and the current settings
+    Merge,
+    /// Overwrites settings, highscores and stats
+    Replace,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Archive {
+    pub config: ConfigOptions,
+    pub highscores: HighScoreOptions,
+    pub stats: Stats,
+}
+
+// Parses an optional section, migrating it from older versions
+fn section<T: Schema>(json: &Value, name: &str) -> Result<T, String> {
+    match json.get(name) {
+        None | Some(Value::Null) => Ok(T::default()),
+        Some(value) if value.is_object() => {
+            storage::upgrade(value.clone()).map_err(|error| format!("{}: {}", name, error))
+        }
+        Some(_) => Err(format!("{} is not an object", name)),
+    }
+}
+
+impl Archive {
+    pub fn from_save(config: &Config, highscores: &HighScores) -> Self {
+        Archive {
+            config: config.options.clone(),
+            highscores: highscores.scores.clone(),
+            stats: highscores.stats.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "unolife_archive": ARCHIVE_VERSION,
+            "config": storage::versioned(&self.config),
+            "highscores": storage::versioned(&self.highscores),
+            "stats": storage::versioned(&self.stats),
+        })
+    }
+
+    /// Checks that `json` is an archive and parses every section
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        match json.get("unolife_archive").and_then(Value::as_u64) {
+            Some(version) if version <= ARCHIVE_VERSION as u64 => (),
+            Some(version) => {
+                return Err(format!(
+                    "made by a newer version of UnoLife (archive version {})",
+                    version
+                ))
+            }
+            None => return Err("not an UnoLife export".to_string()),
+        }
+
+        Ok(Archive {
+            config: section(json, "config")?,
+            highscores: section(json, "highscores")?,
+            stats: section(json, "stats")?,
+        })
+    }
+
+    /// Applies the archive to the current save state
+    pub fn apply(self, mode: ImportMode, config: &mut Config, highscores: &mut HighScores) {
+        match mode {
+            ImportMode::Merge => {
+                highscores.scores.merge(&self.highscores);
+                highscores.stats.merge(&self.stats);
+            }
+            ImportMode::Replace => {
+                config.options = self.config;
+                highscores.scores = self.highscores;
+                highscores.stats = self.stats;
+            }
+        }
+    }
+}
+
+/// Where archives are exported to and imported from by default
+pub fn default_location() -> PathBuf {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join("unolife_export.json")
+}
+
+pub fn export(path: &Path, config: &Config, highscores: &HighScores) -> Result<(), StorageError> {
+    storage::write_json(path, &Archive::from_save(config, highscores).to_json())
+}
+
+/// Imports an archive and saves the result.
+/// Nothing is changed if the file isn't a valid archive.
+pub fn import(
+    path: &Path,
+    mode: ImportMode,
+    config: &mut Config,
+    highscores: &mut HighScores,
+) -> Result<(), StorageError> {
+    let contents = fs::read(path).map_err(|error| StorageError::Io(path.to_path_buf(), error))?;
+    let json: Value = serde_json::from_slice(&contents)
+        .map_err(|error| StorageError::Invalid(path.to_path_buf(), error.to_string()))?;
+    let archive = Archive::from_json(&json)
+        .map_err(|reason| StorageError::Invalid(path.to_path_buf(), reason))?;
+
+    archive.apply(mode, config, highscores);
+    config.load_theme();
+    config.try_save_config()?;
+    highscores.try_save_scores()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::leaderboard::{Entry, Order};
+
+    fn save(score: u32, wordle: u32) -> (Config, HighScores) {
+        let mut config = Config::new();
+        config.options.theme = "Light".to_string();
+        let mut highscores = HighScores::new();
+        highscores.scores.wordle = wordle;
+        highscores
+            .scores
+            .snake
+            .insert(Entry::new("Sam", score, "30x30"), Order::Highest);
+        (config, highscores)
+    }
+
+    #[test]
+    fn round_trip() {
+        let (config, highscores) = save(12, 3);
+        let archive = Archive::from_save(&config, &highscores);
+        assert_eq!(Archive::from_json(&archive.to_json()), Ok(archive));
+    }
+
+    #[test]
+    fn merge_keeps_best() {
+        let (config, highscores) = save(12, 3);
+        let archive = Archive::from_save(&config, &highscores);
+
+        let (mut config, mut highscores) = save(20, 1);
+        config.options.theme = "Dark".to_string();
+        archive.apply(ImportMode::Merge, &mut config, &mut highscores);
+
+        assert_eq!(config.options.theme, "Dark");
+        assert_eq!(highscores.scores.wordle, 3);
+        let scores: Vec<u32> = highscores
+            .scores
+            .snake
+            .entries
+            .iter()
+            .map(|e| e.score)
+            .collect();
+        assert_eq!(scores, [20, 12]);
+    }
+
+    #[test]
+    fn replace_overwrites() {
+        let (config, highscores) = save(12, 3);
+        let archive = Archive::from_save(&config, &highscores);
+
+        let (mut config, mut highscores) = save(20, 1);
+        config.options.theme = "Dark".to_string();
+        archive.apply(ImportMode::Replace, &mut config, &mut highscores);
+
+        assert_eq!(config.options.theme, "Light");
+        assert_eq!(highscores.scores.wordle, 3);
+        assert_eq!(highscores.scores.snake.entries.len(), 1);
+    }
+
+    #[test]
+    fn old_sections_are_migrated() {
+        let highscores: Value =
+            serde_json::from_str(include_str!("fixtures/highscores_v0.json")).unwrap();
+        let archive = Archive::from_json(&json!({
+            "unolife_archive": 1,
+            "highscores": highscores,
+        }))
+        .unwrap();
+
+        assert_eq!(archive.highscores.snake.best(), Some(17));
+        assert_eq!(archive.stats, Stats::default());
+    }
+
+    #[test]
+    fn invalid_archives_are_rejected() {
+        assert!(Archive::from_json(&json!({ "snake": 5 })).is_err());
+        assert!(Archive::from_json(&json!({ "unolife_archive": 99 })).is_err());
+        assert!(Archive::from_json(&json!({
+            "unolife_archive": 1,
+            "highscores": { "schema_version": 2, "wordle": "lots" },
+        }))
+        .is_err());
+    }
+}
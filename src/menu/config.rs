@@ -1,62 +1,181 @@
-use std::{env, fs::File, io::Write, path::Path};
+use std::path::PathBuf;
 
 extern crate serde;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::components::{input::KeyBindings, window::WindowGeometry};
+use crate::tictactoe::Level;
+
+use super::{
+    storage::{self, Schema, StorageError},
+    theme::{self, ColorVision, Theme, DEFAULT_THEME},
+};
 
 pub struct Config {
-    pub location: String,
+    pub location: PathBuf,
     pub options: ConfigOptions,
+    /// Colours of the theme named in `options`
+    pub theme: Theme,
+    /// Last error from loading or saving, shown as a warning in the menu
+    pub error: Option<StorageError>,
 }
 
 impl Config {
     pub fn new() -> Self {
         Config {
-            location: "unolife_config.json".to_string(),
+            location: PathBuf::from("unolife_config.json"),
             options: ConfigOptions::default(),
+            theme: Theme::default(),
+            error: None,
         }
     }
 
-    pub fn fetch_config() -> Self {
+    pub fn fetch_config(location: PathBuf) -> Self {
         let mut config = Config::new();
+        config.location = location;
 
-        if let Ok(folder) = env::var("localappdata") {
-            // make folder if it doesnt exist
-            std::fs::create_dir_all(folder.clone() + "\\UnoLife").unwrap();
+        // create file on system if it doesnt exist
+        let result = if config.location.exists() {
+            config.load_config()
+        } else {
+            config.try_save_config()
+        };
+        config.error = result.err();
+        config.load_theme();
+        config
+    }
 
-            config.location = folder + "\\UnoLife\\config.json";
+    /// Looks up the theme named in the options, it falls back to the
+    /// default theme if the file was removed
+    pub fn load_theme(&mut self) {
+        let (themes, error) = theme::load_all(&theme::dir());
+        self.theme = themes
+            .into_iter()
+            .find(|theme| theme.name == self.options.theme)
+            .unwrap_or_default()
+            .with_vision(self.options.color_vision);
+        if self.error.is_none() {
+            self.error = error;
         }
+    }
 
-        // create file on system if it doesnt exist
-        if !Path::new(&config.location).exists() {
-            let mut config_file = File::create(config.location.clone()).unwrap();
-            config_file
-                .write_all(
-                    serde_json::to_string(&ConfigOptions::default())
-                        .unwrap()
-                        .as_bytes(),
-                )
-                .unwrap();
-        }
+    /// Switches to the theme called `name`, used by the picker in the settings
+    pub fn set_theme(&mut self, name: &str) {
+        self.options.theme = name.to_string();
+        self.load_theme();
+    }
 
-        config.load_config(config.location.clone());
-        config
+    /// Switches to another colour blind palette, used by the picker in the settings
+    pub fn set_color_vision(&mut self, vision: ColorVision) {
+        self.options.color_vision = vision;
+        self.load_theme();
+    }
+
+    pub fn load_config(&mut self) -> Result<(), StorageError> {
+        // a corrupt config falls back to the defaults
+        self.options = ConfigOptions::default();
+        self.options = storage::read_versioned(&self.location)?;
+        Ok(())
     }
 
-    pub fn load_config(&mut self, file: String) {
-        let config_file = File::open(file).unwrap();
-        let config_json: ConfigOptions = serde_json::from_reader(config_file).unwrap_or_default();
-        self.options = config_json;
+    pub fn try_save_config(&self) -> Result<(), StorageError> {
+        storage::write_versioned(&self.location, &self.options)
     }
 
-    pub fn save_config(&self, file: String) {
-        let config_json = serde_json::to_string(&self.options).unwrap();
-        let mut config_file = File::create(file).unwrap();
-        config_file.write_all(config_json.as_bytes()).unwrap();
+    /// Saves the config, remembering the error instead of returning it
+    pub fn save_config(&mut self) {
+        if let Err(error) = self.try_save_config() {
+            self.error = Some(error);
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
 pub struct ConfigOptions {
-    pub white_theme: bool,
+    /// Name of the picked theme
+    pub theme: String,
+    /// Colour blind palette applied on top of the theme
+    pub color_vision: ColorVision,
+    /// Draws X and O on TicTacToe cells and marks Wordle tiles,
+    /// so the games can be played without telling colours apart
+    pub shape_cues: bool,
+    /// Where the window was left, restored on the next launch
+    pub window: WindowGeometry,
+    /// Keys the games are played with, changed in the settings
+    pub bindings: KeyBindings,
+    /// How well the TicTacToe AI plays
+    pub tictactoe_level: Level,
     // TODO: add more config options
 }
+
+impl Default for ConfigOptions {
+    fn default() -> Self {
+        ConfigOptions {
+            theme: DEFAULT_THEME.to_string(),
+            color_vision: ColorVision::Normal,
+            shape_cues: true,
+            window: WindowGeometry::default(),
+            bindings: KeyBindings::default(),
+            tictactoe_level: Level::default(),
+        }
+    }
+}
+
+impl Schema for ConfigOptions {
+    const VERSION: u32 = 2;
+
+    fn migrate(version: u32, json: &mut Value) {
+        match version {
+            // version 0 only lacked the version number
+            0 => (),
+            // the light/dark toggle became a theme name
+            1 => {
+                let white_theme = json.get("white_theme").and_then(Value::as_bool);
+                json["theme"] = Value::from(if white_theme == Some(true) {
+                    "Light"
+                } else {
+                    DEFAULT_THEME
+                });
+                if let Some(json) = json.as_object_mut() {
+                    json.remove("white_theme");
+                }
+            }
+            _ => unreachable!("no migration from config version {}", version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One fixture for every version that was ever saved, oldest first
+    const FIXTURES: [&str; ConfigOptions::VERSION as usize + 1] = [
+        include_str!("fixtures/config_v0.json"),
+        include_str!("fixtures/config_v1.json"),
+        include_str!("fixtures/config_v2.json"),
+    ];
+
+    #[test]
+    fn every_version_loads() {
+        for fixture in FIXTURES {
+            let options: ConfigOptions =
+                storage::upgrade(serde_json::from_str(fixture).unwrap()).unwrap();
+            assert_eq!(options.theme, "Light");
+            assert_eq!(options.window, WindowGeometry::default());
+            assert!(options.shape_cues);
+            assert_eq!(options.bindings, KeyBindings::default());
+            assert_eq!(options.tictactoe_level, Level::Perfect);
+        }
+    }
+
+    #[test]
+    fn dark_mode_becomes_dark_theme() {
+        let options: ConfigOptions =
+            storage::upgrade(serde_json::json!({ "schema_version": 1, "white_theme": false }))
+                .unwrap();
+        assert_eq!(options, ConfigOptions::default());
+    }
+}
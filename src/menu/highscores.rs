@@ -1,79 +1,197 @@
 /*!
     Store and fetch highscores from a json file.
+    Statistics of every finished game are kept in `stats.json` next to it.
     It's not very secure, but it's all local anyway so it doesn't really
     matter if it's tampered with.
 */
 
-use std::{env, fs::File, io::Write, path::Path};
+use std::path::PathBuf;
 
 extern crate serde;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::leaderboard::{Leaderboard, Order};
+use super::stats::Stats;
+use super::storage::{self, Schema, StorageError};
 
 pub struct HighScores {
-    pub location: String,
+    pub location: PathBuf,
     pub scores: HighScoreOptions,
+    pub stats: Stats,
+    /// Last error from loading or saving, shown as a warning in the menu
+    pub error: Option<StorageError>,
 }
 
 impl HighScores {
     pub fn new() -> Self {
         HighScores {
-            location: "unolife_highscores.json".to_string(),
+            location: PathBuf::from("unolife_highscores.json"),
             scores: HighScoreOptions::default(),
+            stats: Stats::default(),
+            error: None,
         }
     }
 
-    pub fn fetch_scores() -> Self {
+    pub fn fetch_scores(location: PathBuf) -> Self {
         let mut highscores = HighScores::new();
-
-        if let Ok(folder) = env::var("localappdata") {
-            // make folder if it doesnt exist
-            std::fs::create_dir_all(folder.clone() + "\\UnoLife").unwrap();
-
-            highscores.location = folder + "\\UnoLife\\highscores.json";
-        }
+        highscores.location = location;
 
         // create file on system if it doesnt exist
-        if !Path::new(&highscores.location).exists() {
-            let mut highscores_file = File::create(&highscores.location).unwrap();
-            highscores_file
-                .write_all(
-                    serde_json::to_string(&HighScoreOptions::default())
-                        .unwrap()
-                        .as_bytes(),
-                )
-                .unwrap();
-        }
-
-        highscores.load_scores();
+        let result = if highscores.location.exists() {
+            highscores.load_scores()
+        } else {
+            highscores.try_save_scores()
+        };
+        highscores.error = result.err();
         highscores
     }
 
-    pub fn load_scores(&mut self) {
-        let highscores_file = File::open(&self.location).unwrap();
-        let highscores_json: HighScoreOptions =
-            serde_json::from_reader(highscores_file).unwrap_or_default();
-        self.scores = highscores_json;
+    pub fn stats_location(&self) -> PathBuf {
+        self.location.with_file_name("stats.json")
+    }
+
+    pub fn load_scores(&mut self) -> Result<(), StorageError> {
+        // a corrupt file starts over from zero, the old one is kept as a backup
+        self.scores = HighScoreOptions::default();
+        self.stats = Stats::default();
+        // both files are read, the error of the scores is the one shown first
+        let scores = storage::read_versioned(&self.location).map(|scores| self.scores = scores);
+        let stats = storage::read_versioned(&self.stats_location()).map(|stats| self.stats = stats);
+        scores?;
+        stats
     }
 
-    pub fn save_scores(&self) {
-        let highscores_json = serde_json::to_string(&self.scores).unwrap();
-        let mut highscores_file = File::create(&self.location).unwrap();
-        highscores_file
-            .write_all(highscores_json.as_bytes())
-            .unwrap();
+    pub fn try_save_scores(&self) -> Result<(), StorageError> {
+        storage::write_versioned(&self.location, &self.scores)?;
+        storage::write_versioned(&self.stats_location(), &self.stats)
+    }
+
+    /// Saves the scores, remembering the error instead of returning it
+    pub fn save_scores(&mut self) {
+        if let Err(error) = self.try_save_scores() {
+            self.error = Some(error);
+        }
     }
 
     pub fn reset_highscores(&mut self) {
         self.scores = HighScoreOptions::default();
+        self.stats = Stats::default();
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
+// Missing fields get their default so adding a game never wipes the other scores
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(default)]
 pub struct HighScoreOptions {
     pub tictactoe_lime: u32,
     pub tictactoe_purple: u32,
     pub wordle: u32,
-    pub snake: u32,
-    pub twenty48: u32,
-    pub puzzle15: u32,
+    pub snake: Leaderboard,
+    pub twenty48: Leaderboard,
+    /// Fewest moves first
+    pub puzzle15: Leaderboard,
+}
+
+impl HighScoreOptions {
+    /// Keeps the best of both for every game
+    pub fn merge(&mut self, other: &HighScoreOptions) {
+        self.tictactoe_lime = self.tictactoe_lime.max(other.tictactoe_lime);
+        self.tictactoe_purple = self.tictactoe_purple.max(other.tictactoe_purple);
+        self.wordle = self.wordle.max(other.wordle);
+        self.snake.merge(&other.snake, Order::Highest);
+        self.twenty48.merge(&other.twenty48, Order::Highest);
+        self.puzzle15.merge(&other.puzzle15, Order::Lowest);
+    }
+}
+
+impl Schema for HighScoreOptions {
+    const VERSION: u32 = 2;
+
+    fn migrate(version: u32, json: &mut Value) {
+        match version {
+            // puzzle15 used 0 for "not solved yet", which also meant it could never be beaten
+            0 => {
+                if json.get("puzzle15") == Some(&Value::from(0)) {
+                    json["puzzle15"] = Value::Null;
+                }
+            }
+            // single best scores became top 10 tables
+            1 => {
+                for (game, settings) in
+                    [("snake", "30x30"), ("twenty48", "4x4"), ("puzzle15", "4x4")]
+                {
+                    let entries = match json.get(game).and_then(Value::as_u64) {
+                        Some(score) if score > 0 => serde_json::json!([{
+                            "name": "Player",
+                            "score": score,
+                            "date": 0,
+                            "settings": settings,
+                        }]),
+                        _ => serde_json::json!([]),
+                    };
+                    json[game] = serde_json::json!({ "entries": entries });
+                }
+            }
+            _ => unreachable!("no migration from highscores version {}", version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One fixture for every version that was ever saved, oldest first
+    const FIXTURES: [&str; HighScoreOptions::VERSION as usize + 1] = [
+        include_str!("fixtures/highscores_v0.json"),
+        include_str!("fixtures/highscores_v1.json"),
+        include_str!("fixtures/highscores_v2.json"),
+    ];
+
+    fn load(json: &str) -> HighScoreOptions {
+        storage::upgrade(serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn every_version_loads() {
+        for fixture in FIXTURES {
+            let scores = load(fixture);
+            assert_eq!(scores.tictactoe_lime, 3);
+            assert_eq!(scores.tictactoe_purple, 1);
+            assert_eq!(scores.wordle, 2);
+            assert_eq!(scores.snake.best(), Some(17));
+            assert_eq!(scores.twenty48.best(), Some(2048));
+        }
+    }
+
+    #[test]
+    fn unsolved_puzzle15_is_migrated() {
+        assert_eq!(load(FIXTURES[0]).puzzle15.best(), None);
+        assert_eq!(load(FIXTURES[1]).puzzle15.best(), Some(95));
+        assert_eq!(load(FIXTURES[2]).puzzle15.best(), Some(95));
+    }
+
+    #[test]
+    fn best_scores_become_leaderboards() {
+        let scores = load(FIXTURES[1]);
+        let entry = &scores.snake.entries[0];
+        assert_eq!(scores.snake.entries.len(), 1);
+        assert_eq!(
+            (entry.name.as_str(), entry.date, entry.settings.as_str()),
+            ("Player", 0, "30x30")
+        );
+    }
+
+    #[test]
+    fn missing_fields_keep_other_scores() {
+        let scores = load(r#"{"schema_version": 2, "wordle": 5}"#);
+        assert_eq!(
+            scores,
+            HighScoreOptions {
+                wordle: 5,
+                ..Default::default()
+            }
+        );
+    }
 }
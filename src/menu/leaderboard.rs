@@ -0,0 +1,182 @@
+/*!
+    Top 10 tables for the games that keep a score.
+    Every entry remembers who set it, when, and with which game settings.
+*/
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+extern crate serde;
+use serde::{Deserialize, Serialize};
+
+/// Number of entries kept per game
+pub const LEADERBOARD_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    /// Higher scores are better, like points
+    Highest,
+    /// Lower scores are better, like moves
+    Lowest,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub score: u32,
+    /// Seconds since the unix epoch, 0 if unknown
+    pub date: u64,
+    /// Settings the game was played with, like the board size
+    pub settings: String,
+}
+
+impl Entry {
+    /// Creates an entry dated now
+    pub fn new(name: &str, score: u32, settings: &str) -> Self {
+        Entry {
+            name: name.to_string(),
+            score,
+            date: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or(0),
+            settings: settings.to_string(),
+        }
+    }
+
+    /// Formats the date as `YYYY-MM-DD`
+    pub fn date_string(&self) -> String {
+        if self.date == 0 {
+            return "-".to_string();
+        }
+
+        // days to civil date, from Howard Hinnant's date algorithms
+        let days = (self.date / 86400) as i64 + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+/// Sorted best first, never longer than `LEADERBOARD_SIZE`
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(default)]
+pub struct Leaderboard {
+    pub entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    #[cfg(test)]
+    pub fn best(&self) -> Option<u32> {
+        self.entries.first().map(|entry| entry.score)
+    }
+
+    /// Returns the place a score would get, or `None` if it doesn't make the table.
+    /// Ties go below the entries that were there first.
+    pub fn place(&self, score: u32, order: Order) -> Option<usize> {
+        let place = self
+            .entries
+            .iter()
+            .position(|entry| match order {
+                Order::Highest => score > entry.score,
+                Order::Lowest => score < entry.score,
+            })
+            .unwrap_or(self.entries.len());
+
+        if place < LEADERBOARD_SIZE {
+            Some(place)
+        } else {
+            None
+        }
+    }
+
+    /// Adds the entry if it makes the table and returns its place
+    pub fn insert(&mut self, entry: Entry, order: Order) -> Option<usize> {
+        let place = self.place(entry.score, order)?;
+        self.entries.insert(place, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(place)
+    }
+
+    /// Adds the entries of `other` that aren't already in the table
+    pub fn merge(&mut self, other: &Leaderboard, order: Order) {
+        for entry in other.entries.iter() {
+            if !self.entries.contains(entry) {
+                self.insert(entry.clone(), order);
+            }
+        }
+    }
+
+    /// Name of the most recent entry, used to prefill the name prompt
+    pub fn last_name(&self) -> Option<&str> {
+        self.entries
+            .iter()
+            .max_by_key(|entry| entry.date)
+            .map(|entry| entry.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32) -> Entry {
+        Entry::new("Player", score, "4x4")
+    }
+
+    #[test]
+    fn keeps_best_ten_in_order() {
+        let mut board = Leaderboard::default();
+        for score in [5, 50, 20, 1, 7, 30, 2, 9, 40, 3, 60] {
+            board.insert(entry(score), Order::Highest);
+        }
+
+        let scores: Vec<u32> = board.entries.iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, [60, 50, 40, 30, 20, 9, 7, 5, 3, 2]);
+        assert_eq!(board.place(1, Order::Highest), None);
+        assert_eq!(board.place(8, Order::Highest), Some(6));
+        assert_eq!(board.insert(entry(2), Order::Highest), None);
+    }
+
+    #[test]
+    fn lowest_first() {
+        let mut board = Leaderboard::default();
+        for score in [120, 80, 95] {
+            board.insert(entry(score), Order::Lowest);
+        }
+        assert_eq!(board.best(), Some(80));
+        // ties go after the older score
+        assert_eq!(board.insert(entry(95), Order::Lowest), Some(2));
+    }
+
+    #[test]
+    fn merge_skips_duplicates() {
+        let mut board = Leaderboard::default();
+        let mut other = Leaderboard::default();
+        let shared = entry(30);
+        board.insert(shared.clone(), Order::Highest);
+        board.insert(entry(10), Order::Highest);
+        other.insert(shared, Order::Highest);
+        other.insert(entry(20), Order::Highest);
+
+        board.merge(&other, Order::Highest);
+        let scores: Vec<u32> = board.entries.iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, [30, 20, 10]);
+    }
+
+    #[test]
+    fn date_format() {
+        let mut entry = entry(1);
+        entry.date = 1_650_000_000;
+        assert_eq!(entry.date_string(), "2022-04-15");
+        entry.date = 0;
+        assert_eq!(entry.date_string(), "-");
+    }
+}
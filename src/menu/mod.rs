@@ -1,3 +1,9 @@
+pub mod archive;
 pub mod config;
 pub mod highscores;
+pub mod leaderboard;
+pub mod profiles;
+pub mod stats;
+pub mod storage;
+pub mod theme;
 pub mod ui;
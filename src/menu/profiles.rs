@@ -0,0 +1,307 @@
+/*!
+    Player profiles, so several people can share one machine.
+    Every profile has its own config, highscores and stats in
+    `profiles/<id>` inside the config and data folders.
+    The list of profiles and the last active one are kept in `profiles.json`.
+*/
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+extern crate serde;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::storage::{self, Kind, Schema, StorageError};
+
+/// Most profiles that fit in the settings tab
+pub const MAX_PROFILES: usize = 6;
+/// Longest name a profile can have
+pub const MAX_NAME_LEN: usize = 16;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Profile {
+    /// Folder name, never changes after the profile is created
+    pub id: String,
+    pub name: String,
+}
+
+impl Profile {
+    pub fn config_location(&self) -> PathBuf {
+        storage::file(
+            Kind::Config,
+            ["profiles", &self.id, "config.json"]
+                .iter()
+                .collect::<PathBuf>(),
+        )
+    }
+
+    pub fn highscores_location(&self) -> PathBuf {
+        storage::file(
+            Kind::Data,
+            ["profiles", &self.id, "highscores.json"]
+                .iter()
+                .collect::<PathBuf>(),
+        )
+    }
+
+    /// Deletes the config, highscores and stats of the profile
+    pub fn remove_files(&self) {
+        // never anything outside of the profile's own folder
+        if !is_plain_name(&self.id) {
+            return;
+        }
+        for kind in [Kind::Config, Kind::Data] {
+            fs::remove_dir_all(storage::dir(kind).join("profiles").join(&self.id)).ok();
+        }
+    }
+}
+
+// Whether the id is a single folder name. Ids come from profiles.json,
+// which may have been edited by hand.
+fn is_plain_name(id: &str) -> bool {
+    let mut components = Path::new(id).components();
+    matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+        && !id.contains(['/', '\\'])
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ProfileOptions {
+    pub profiles: Vec<Profile>,
+    /// Id of the last active profile
+    pub active: String,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        ProfileOptions {
+            profiles: vec![Profile {
+                id: "default".to_string(),
+                name: "Player".to_string(),
+            }],
+            active: "default".to_string(),
+        }
+    }
+}
+
+impl Schema for ProfileOptions {
+    const VERSION: u32 = 1;
+
+    fn migrate(version: u32, _json: &mut Value) {
+        match version {
+            // profiles were always saved with a version
+            0 => (),
+            _ => unreachable!("no migration from profiles version {}", version),
+        }
+    }
+}
+
+pub struct Profiles {
+    pub location: PathBuf,
+    pub options: ProfileOptions,
+    /// Last error from loading or saving, shown as a warning in the menu
+    pub error: Option<StorageError>,
+}
+
+impl Profiles {
+    pub fn new() -> Self {
+        Profiles {
+            location: PathBuf::from("unolife_profiles.json"),
+            options: ProfileOptions::default(),
+            error: None,
+        }
+    }
+
+    pub fn fetch_profiles() -> Self {
+        let mut profiles = Profiles::new();
+        profiles.location = storage::file(Kind::Config, "profiles.json");
+
+        let result = if profiles.location.exists() {
+            storage::read_versioned(&profiles.location).map(|options| profiles.options = options)
+        } else {
+            // first run with profiles, move the files of older versions into the first one
+            let profile = profiles.active();
+            let config = profile.config_location();
+            let highscores = profile.highscores_location();
+            let stats = highscores.with_file_name("stats.json");
+            for (old, new) in [
+                (storage::dir(Kind::Config).join("config.json"), &config),
+                (PathBuf::from("unolife_config.json"), &config),
+                (
+                    storage::dir(Kind::Data).join("highscores.json"),
+                    &highscores,
+                ),
+                (PathBuf::from("unolife_highscores.json"), &highscores),
+                (storage::dir(Kind::Data).join("stats.json"), &stats),
+            ] {
+                storage::migrate(&old, new);
+            }
+            profiles.try_save_profiles()
+        };
+        profiles.error = result.err();
+
+        // the active profile may have been removed by hand, and ids that
+        // aren't folder names would put files anywhere
+        profiles
+            .options
+            .profiles
+            .retain(|profile| is_plain_name(&profile.id));
+        if profiles.options.profiles.is_empty() {
+            profiles.options = ProfileOptions::default();
+        }
+        if profiles.position(&profiles.options.active).is_none() {
+            profiles.options.active = profiles.options.profiles[0].id.clone();
+        }
+        profiles
+    }
+
+    pub fn try_save_profiles(&self) -> Result<(), StorageError> {
+        storage::write_versioned(&self.location, &self.options)
+    }
+
+    /// Saves the profiles, remembering the error instead of returning it
+    pub fn save_profiles(&mut self) {
+        if let Err(error) = self.try_save_profiles() {
+            self.error = Some(error);
+        }
+    }
+
+    pub fn list(&self) -> &[Profile] {
+        &self.options.profiles
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.options
+            .profiles
+            .iter()
+            .position(|profile| profile.id == id)
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.position(&self.options.active).unwrap_or(0)
+    }
+
+    pub fn active(&self) -> &Profile {
+        &self.options.profiles[self.active_index()]
+    }
+
+    pub fn switch(&mut self, index: usize) {
+        self.options.active = self.options.profiles[index].id.clone();
+    }
+
+    /// Adds a profile and returns its index, or `None` if there are too many
+    pub fn create(&mut self, name: &str) -> Option<usize> {
+        if self.options.profiles.len() >= MAX_PROFILES {
+            return None;
+        }
+
+        // build a folder name out of the profile name
+        let base: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        let base = if base.is_empty() {
+            "profile".to_string()
+        } else {
+            base
+        };
+        let mut id = base.clone();
+        let mut suffix = 1;
+        while self.position(&id).is_some() {
+            suffix += 1;
+            id = format!("{}{}", base, suffix);
+        }
+
+        self.options.profiles.push(Profile {
+            id,
+            name: name.to_string(),
+        });
+        Some(self.options.profiles.len() - 1)
+    }
+
+    pub fn rename(&mut self, index: usize, name: &str) {
+        self.options.profiles[index].name = name.to_string();
+    }
+
+    /// Removes a profile from the list, the last profile can't be removed.
+    /// If it was active the first remaining profile becomes active.
+    pub fn delete(&mut self, index: usize) -> Option<Profile> {
+        if self.options.profiles.len() <= 1 {
+            return None;
+        }
+        let profile = self.options.profiles.remove(index);
+        if profile.id == self.options.active {
+            self.options.active = self.options.profiles[0].id.clone();
+        }
+        Some(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_gives_unique_ids() {
+        let mut profiles = Profiles::new();
+        let first = profiles.create("Jo Smith").unwrap();
+        let second = profiles.create("jo-smith").unwrap();
+        let third = profiles.create("!!").unwrap();
+
+        assert_eq!(profiles.list()[first].id, "josmith");
+        assert_eq!(profiles.list()[second].id, "josmith2");
+        assert_eq!(profiles.list()[third].id, "profile");
+        assert_eq!(profiles.list()[second].name, "jo-smith");
+    }
+
+    #[test]
+    fn profile_limit() {
+        let mut profiles = Profiles::new();
+        for _ in 1..MAX_PROFILES {
+            assert!(profiles.create("Player").is_some());
+        }
+        assert_eq!(profiles.create("Player"), None);
+    }
+
+    #[test]
+    fn rename_keeps_id() {
+        let mut profiles = Profiles::new();
+        profiles.rename(0, "Alex");
+        assert_eq!(
+            profiles.active(),
+            &Profile {
+                id: "default".to_string(),
+                name: "Alex".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn ids_are_folder_names() {
+        assert!(is_plain_name("default"));
+        assert!(is_plain_name("josmith2"));
+        for id in ["", ".", "..", "../config", "a/b", "a\\b", "/tmp", "sam/"] {
+            assert!(!is_plain_name(id), "{:?} is no folder name", id);
+        }
+    }
+
+    #[test]
+    fn delete_switches_active() {
+        let mut profiles = Profiles::new();
+        let index = profiles.create("Sam").unwrap();
+        profiles.switch(index);
+        assert_eq!(profiles.active().name, "Sam");
+
+        assert_eq!(profiles.delete(index).unwrap().name, "Sam");
+        assert_eq!(profiles.active().name, "Player");
+
+        // the last profile stays
+        assert_eq!(profiles.delete(0), None);
+        assert_eq!(profiles.list().len(), 1);
+    }
+}
@@ -0,0 +1,213 @@
+/*!
+    Keeps a record of every finished game so the Stats tab can show more
+    than a single highscore, like win rates, streaks and Wordle guesses.
+    Stored next to the highscores in `stats.json`.
+*/
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+extern crate serde;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::storage::Schema;
+
+/// Most guesses a Wordle game can take
+pub const WORDLE_GUESSES: usize = 6;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    Draw,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    /// Registry id of the game
+    pub game: String,
+    /// Seconds since the unix epoch when the game ended
+    pub finished: u64,
+    /// How long the game took in seconds
+    pub duration: f64,
+    pub outcome: Outcome,
+    pub score: Option<u32>,
+    /// Guesses taken to solve a Wordle
+    pub guesses: Option<u32>,
+}
+
+impl GameRecord {
+    /// Creates a record for a game that started at `started` and just ended
+    pub fn new(game: &str, outcome: Outcome, started: Instant) -> Self {
+        GameRecord {
+            game: game.to_string(),
+            finished: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or(0),
+            duration: started.elapsed().as_secs_f64(),
+            outcome,
+            score: None,
+            guesses: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[serde(default)]
+pub struct Stats {
+    pub records: Vec<GameRecord>,
+}
+
+/// Summary of every recorded game of one kind
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Summary {
+    pub played: u32,
+    pub wins: u32,
+    pub current_streak: u32,
+    pub best_streak: u32,
+    pub average_score: Option<f64>,
+    /// How many Wordles were solved in 1 to 6 guesses
+    pub guess_distribution: [u32; WORDLE_GUESSES],
+}
+
+impl Summary {
+    pub fn win_rate(&self) -> Option<f64> {
+        if self.played == 0 {
+            None
+        } else {
+            Some(self.wins as f64 / self.played as f64)
+        }
+    }
+}
+
+impl Stats {
+    pub fn record(&mut self, record: GameRecord) {
+        self.records.push(record);
+    }
+
+    /// Adds the records of `other` that aren't already here, oldest first
+    pub fn merge(&mut self, other: &Stats) {
+        for record in other.records.iter() {
+            if !self.records.contains(record) {
+                self.records.push(record.clone());
+            }
+        }
+        self.records.sort_by_key(|record| record.finished);
+    }
+
+    pub fn summary(&self, game: &str) -> Summary {
+        let mut summary = Summary::default();
+        let mut streak = 0;
+        let mut score_total = 0.0;
+        let mut score_count = 0;
+
+        for record in self.records.iter().filter(|record| record.game == game) {
+            summary.played += 1;
+
+            if record.outcome == Outcome::Won {
+                summary.wins += 1;
+                streak += 1;
+                summary.best_streak = summary.best_streak.max(streak);
+            } else {
+                streak = 0;
+            }
+
+            if let Some(score) = record.score {
+                score_total += score as f64;
+                score_count += 1;
+            }
+
+            if let Some(guesses) = record.guesses {
+                if (1..=WORDLE_GUESSES as u32).contains(&guesses) {
+                    summary.guess_distribution[guesses as usize - 1] += 1;
+                }
+            }
+        }
+
+        summary.current_streak = streak;
+        if score_count > 0 {
+            summary.average_score = Some(score_total / score_count as f64);
+        }
+        summary
+    }
+}
+
+impl Schema for Stats {
+    const VERSION: u32 = 1;
+
+    fn migrate(version: u32, _json: &mut Value) {
+        match version {
+            // stats were always saved with a version
+            0 => (),
+            _ => unreachable!("no migration from stats version {}", version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::storage;
+
+    fn record(game: &str, outcome: Outcome, score: Option<u32>) -> GameRecord {
+        GameRecord {
+            score,
+            ..GameRecord::new(game, outcome, Instant::now())
+        }
+    }
+
+    #[test]
+    fn streaks_and_win_rate() {
+        let mut stats = Stats::default();
+        for outcome in [
+            Outcome::Won,
+            Outcome::Won,
+            Outcome::Won,
+            Outcome::Lost,
+            Outcome::Won,
+        ] {
+            stats.record(record("wordle", outcome, None));
+        }
+        stats.record(record("snake", Outcome::Lost, Some(4)));
+
+        let summary = stats.summary("wordle");
+        assert_eq!(summary.played, 5);
+        assert_eq!(summary.wins, 4);
+        assert_eq!(summary.win_rate(), Some(0.8));
+        assert_eq!(summary.current_streak, 1);
+        assert_eq!(summary.best_streak, 3);
+        assert_eq!(summary.average_score, None);
+
+        assert_eq!(stats.summary("puzzle15").win_rate(), None);
+    }
+
+    #[test]
+    fn average_score_and_guesses() {
+        let mut stats = Stats::default();
+        stats.record(record("snake", Outcome::Lost, Some(4)));
+        stats.record(record("snake", Outcome::Lost, Some(10)));
+        for guesses in [3, 3, 6] {
+            stats.record(GameRecord {
+                guesses: Some(guesses),
+                ..record("wordle", Outcome::Won, None)
+            });
+        }
+
+        assert_eq!(stats.summary("snake").average_score, Some(7.0));
+        assert_eq!(
+            stats.summary("wordle").guess_distribution,
+            [0, 0, 2, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn every_version_loads() {
+        let fixture = include_str!("fixtures/stats_v1.json");
+        let stats: Stats = storage::upgrade(serde_json::from_str(fixture).unwrap()).unwrap();
+
+        assert_eq!(stats.records.len(), 2);
+        assert_eq!(stats.summary("wordle").guess_distribution[3], 1);
+        assert_eq!(stats.summary("snake").average_score, Some(12.0));
+    }
+}
@@ -0,0 +1,416 @@
+/*!
+    Resolves where config and highscores are stored on each platform.
+
+    - `UNOLIFE_HOME` overrides everything when set
+    - Windows uses `%LOCALAPPDATA%\UnoLife`
+    - macOS uses `~/Library/Application Support/UnoLife`
+    - Other platforms follow XDG, `$XDG_CONFIG_HOME/unolife` for config and
+      `$XDG_DATA_HOME/unolife` for highscores
+
+    Older versions wrote their files to the current working directory,
+    those are moved into the first profile when it is created.
+
+    Files are written to a temporary file first and then renamed over the
+    old one, so a crash mid-save never leaves a half written file behind.
+    Files that fail to parse are kept next to the original as `.bak`.
+
+    Saved files carry a `schema_version`, older files are upgraded one
+    version at a time by the type's `Schema::migrate` before they are parsed.
+*/
+
+use std::{
+    env,
+    error::Error,
+    ffi::OsString,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+#[derive(Debug)]
+pub enum StorageError {
+    /// The file could not be read or written
+    Io(PathBuf, io::Error),
+    /// The file could not be parsed, it was moved to `backup` if possible
+    Corrupt {
+        path: PathBuf,
+        backup: Option<PathBuf>,
+        error: serde_json::Error,
+    },
+    /// A file given by the user isn't in the expected format, it is left untouched
+    Invalid(PathBuf, String),
+}
+
+impl Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::Io(path, error) => {
+                write!(f, "Could not access {}: {}", path.display(), error)
+            }
+            StorageError::Corrupt {
+                path,
+                backup: Some(backup),
+                ..
+            } => write!(
+                f,
+                "{} was corrupt and has been reset, the old file was kept as {}",
+                path.display(),
+                backup.display()
+            ),
+            StorageError::Corrupt { path, .. } => {
+                write!(f, "{} was corrupt and has been reset", path.display())
+            }
+            StorageError::Invalid(path, reason) => {
+                write!(
+                    f,
+                    "{} is not a valid UnoLife file: {}",
+                    path.display(),
+                    reason
+                )
+            }
+        }
+    }
+}
+
+impl Error for StorageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StorageError::Io(_, error) => Some(error),
+            StorageError::Corrupt { error, .. } => Some(error),
+            StorageError::Invalid(..) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Config,
+    Data,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Platform {
+    Windows,
+    MacOs,
+    Unix,
+}
+
+impl Platform {
+    fn current() -> Self {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Unix
+        }
+    }
+}
+
+// Looks up an environment variable, ignoring empty values
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn resolve(
+    kind: Kind,
+    platform: Platform,
+    var: impl Fn(&str) -> Option<String>,
+) -> Option<PathBuf> {
+    if let Some(home) = var("UNOLIFE_HOME") {
+        return Some(PathBuf::from(home));
+    }
+
+    match platform {
+        Platform::Windows => var("LOCALAPPDATA")
+            .or_else(|| var("localappdata"))
+            .map(|folder| PathBuf::from(folder).join("UnoLife")),
+        Platform::MacOs => var("HOME").map(|home| {
+            PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+                .join("UnoLife")
+        }),
+        Platform::Unix => {
+            let (xdg, fallback) = match kind {
+                Kind::Config => ("XDG_CONFIG_HOME", ".config"),
+                Kind::Data => ("XDG_DATA_HOME", ".local/share"),
+            };
+            var(xdg)
+                .map(PathBuf::from)
+                .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(fallback)))
+                .map(|folder| folder.join("unolife"))
+        }
+    }
+}
+
+/// Returns the directory for the given kind of file, falling back to the
+/// current directory if no suitable location can be found.
+pub fn dir(kind: Kind) -> PathBuf {
+    resolve(kind, Platform::current(), var).unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Returns the path of `name` inside the directory for `kind`.
+/// The folders leading up to it are made when the file is written.
+pub fn file<P: AsRef<Path>>(kind: Kind, name: P) -> PathBuf {
+    dir(kind).join(name)
+}
+
+// Makes the folder `path` goes in if it doesn't exist yet
+fn create_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+}
+
+/// Moves a file written by an older version to its new location,
+/// unless the new location already has one.
+pub fn migrate(legacy: &Path, target: &Path) {
+    if !legacy.is_file() || target.exists() || create_parent(target).is_err() {
+        return;
+    }
+    if fs::rename(legacy, target).is_err() {
+        // rename doesn't work across file systems
+        if fs::copy(legacy, target).is_ok() {
+            fs::remove_file(legacy).ok();
+        }
+    }
+}
+
+// Appends an extension, so `config.json` becomes `config.json.bak`
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Reads a json file, a missing file gives the default value.
+/// A corrupt file is moved to `<file>.bak` so the next save doesn't overwrite it.
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, StorageError> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(error) => return Err(StorageError::Io(path.to_path_buf(), error)),
+    };
+
+    serde_json::from_slice(&contents).map_err(|error| back_up(path, error))
+}
+
+// Moves a file that could not be parsed out of the way
+fn back_up(path: &Path, error: serde_json::Error) -> StorageError {
+    let backup = with_suffix(path, ".bak");
+    StorageError::Corrupt {
+        path: path.to_path_buf(),
+        backup: fs::rename(path, &backup).ok().map(|_| backup),
+        error,
+    }
+}
+
+/// Writes a json file atomically by writing a temporary file and renaming it.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    // serializing plain structs can't fail
+    let json = serde_json::to_string(value).unwrap();
+    let temp = with_suffix(path, ".tmp");
+
+    create_parent(path)
+        .and_then(|_| fs::write(&temp, json))
+        .and_then(|_| fs::rename(&temp, path))
+        .map_err(|error| {
+            fs::remove_file(&temp).ok();
+            StorageError::Io(path.to_path_buf(), error)
+        })
+}
+
+/// A saved file format that can be upgraded from older versions.
+/// Files written before versioning existed are version 0.
+pub trait Schema: Serialize + DeserializeOwned + Default {
+    /// The version written by this build
+    const VERSION: u32;
+
+    /// Upgrades `json` from `version` to `version + 1`
+    fn migrate(version: u32, json: &mut Value);
+}
+
+/// Parses a json value of any known version, migrating it to the current one.
+/// Files from a newer build are parsed as they are, unknown fields are ignored.
+pub fn upgrade<T: Schema>(mut json: Value) -> Result<T, serde_json::Error> {
+    let version = json
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+
+    for version in version..T::VERSION {
+        T::migrate(version, &mut json);
+    }
+    if let Value::Object(map) = &mut json {
+        map.remove("schema_version");
+    }
+    serde_json::from_value(json)
+}
+
+/// Like `read_json`, but migrates files written by older versions.
+pub fn read_versioned<T: Schema>(path: &Path) -> Result<T, StorageError> {
+    match read_json::<Option<Value>>(path)? {
+        Some(json) => upgrade(json).map_err(|error| back_up(path, error)),
+        None => Ok(T::default()),
+    }
+}
+
+/// Converts a value to json stamped with the current schema version.
+pub fn versioned<T: Schema>(value: &T) -> Value {
+    let mut json = serde_json::to_value(value).unwrap();
+    if let Value::Object(map) = &mut json {
+        map.insert("schema_version".to_string(), T::VERSION.into());
+    }
+    json
+}
+
+/// Like `write_json`, but stamps the file with the current schema version.
+pub fn write_versioned<T: Schema>(path: &Path, value: &T) -> Result<(), StorageError> {
+    write_json(path, &versioned(value))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn override_wins() {
+        let env = env(&[("UNOLIFE_HOME", "/tmp/uno"), ("HOME", "/home/me")]);
+        for platform in [Platform::Windows, Platform::MacOs, Platform::Unix] {
+            assert_eq!(
+                resolve(Kind::Data, platform, &env),
+                Some(PathBuf::from("/tmp/uno"))
+            );
+        }
+    }
+
+    #[test]
+    fn xdg_dirs() {
+        let env = env(&[("HOME", "/home/me"), ("XDG_CONFIG_HOME", "/home/me/cfg")]);
+        assert_eq!(
+            resolve(Kind::Config, Platform::Unix, &env),
+            Some(PathBuf::from("/home/me/cfg/unolife"))
+        );
+        assert_eq!(
+            resolve(Kind::Data, Platform::Unix, &env),
+            Some(PathBuf::from("/home/me/.local/share/unolife"))
+        );
+    }
+
+    #[test]
+    fn platform_dirs() {
+        let env = env(&[("HOME", "/Users/me"), ("LOCALAPPDATA", "C:\\AppData")]);
+        assert_eq!(
+            resolve(Kind::Config, Platform::MacOs, &env),
+            Some(PathBuf::from(
+                "/Users/me/Library/Application Support/UnoLife"
+            ))
+        );
+        assert_eq!(
+            resolve(Kind::Config, Platform::Windows, &env),
+            Some(PathBuf::from("C:\\AppData").join("UnoLife"))
+        );
+        assert_eq!(resolve(Kind::Config, Platform::Unix, |_| None), None);
+    }
+
+    /// A directory of its own for the files of a test, removed when dropped
+    pub struct TempDir {
+        pub path: PathBuf,
+    }
+
+    impl TempDir {
+        pub fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("unolife-{}-{}", name, std::process::id()));
+            // left over when the test panicked last time
+            fs::remove_dir_all(&path).ok();
+            fs::create_dir_all(&path).unwrap();
+            TempDir { path }
+        }
+
+        pub fn join(&self, file: &str) -> PathBuf {
+            self.path.join(file)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.path).ok();
+        }
+    }
+
+    #[test]
+    fn legacy_file_is_moved() {
+        let dir = TempDir::new("migrate");
+        let legacy = dir.join("unolife_config.json");
+        let target = dir.join("default").join("config.json");
+        fs::write(&legacy, "{}").unwrap();
+
+        migrate(&legacy, &target);
+        assert!(!legacy.exists());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "{}");
+
+        // an existing file is never overwritten
+        fs::write(&legacy, "old").unwrap();
+        migrate(&legacy, &target);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "{}");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let dir = TempDir::new("json");
+        let path = dir.join("profiles").join("scores.json");
+
+        // missing files give the default
+        assert_eq!(read_json::<Vec<u32>>(&path).unwrap(), Vec::<u32>::new());
+
+        write_json(&path, &vec![1, 2, 3]).unwrap();
+        assert_eq!(read_json::<Vec<u32>>(&path).unwrap(), vec![1, 2, 3]);
+        assert!(!with_suffix(&path, ".tmp").exists());
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up() {
+        let dir = TempDir::new("corrupt");
+        let path = dir.join("scores.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        match read_json::<Vec<u32>>(&path) {
+            Err(StorageError::Corrupt {
+                backup: Some(backup),
+                ..
+            }) => {
+                assert_eq!(backup, dir.join("scores.json.bak"));
+                assert_eq!(fs::read_to_string(&backup).unwrap(), "{ not json");
+            }
+            other => panic!("expected a corrupt file error, got {:?}", other),
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn unwritable_file_is_an_error() {
+        let dir = TempDir::new("unwritable");
+        // a file is in the way of the folder
+        fs::write(dir.join("profiles"), "").unwrap();
+        let path = dir.join("profiles").join("scores.json");
+
+        assert!(matches!(
+            write_json(&path, &1),
+            Err(StorageError::Io(p, _)) if p == path
+        ));
+    }
+}
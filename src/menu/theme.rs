@@ -0,0 +1,486 @@
+/*!
+    Colours of the menu and of every game.
+
+    Light, dark and high contrast themes are built in. More can be added by
+    putting `.json` or `.toml` files into the `themes` folder next to the
+    highscores. A theme file only needs the colours it changes, the rest is
+    taken from the built-in theme named by `base`, or the dark theme if it
+    has none. Colours are written as `#rrggbb` or `#rrggbbaa`.
+*/
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+extern crate serde;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::storage::{self, Kind, StorageError};
+
+/// Theme used when none is picked or the picked one is gone
+pub const DEFAULT_THEME: &str = "Dark";
+
+lazy_static! {
+    static ref BUILT_IN: Vec<Theme> = [
+        include_str!("themes/dark.toml"),
+        include_str!("themes/light.toml"),
+        include_str!("themes/high_contrast.toml"),
+    ]
+    .iter()
+    .map(|text| toml::from_str(text).expect("built-in themes are valid"))
+    .collect();
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    /// Background of the menu tabs
+    #[serde(with = "hex")]
+    pub background: [f32; 4],
+    /// Background behind the games
+    #[serde(with = "hex")]
+    pub game_background: [f32; 4],
+    #[serde(with = "hex")]
+    pub taskbar: [f32; 4],
+    /// Line between the taskbar and the content
+    #[serde(with = "hex")]
+    pub separator: [f32; 4],
+    #[serde(with = "hex")]
+    pub text: [f32; 4],
+    #[serde(with = "hex")]
+    pub button: [f32; 4],
+    #[serde(with = "hex")]
+    pub button_text: [f32; 4],
+    /// Buttons that reset or delete something
+    #[serde(with = "hex")]
+    pub danger: [f32; 4],
+    #[serde(with = "hex")]
+    pub hover: [f32; 4],
+    /// Marks the selected row of a list, like a new highscore
+    #[serde(with = "hex")]
+    pub accent: [f32; 4],
+    /// Background of dialogs
+    #[serde(with = "hex")]
+    pub panel: [f32; 4],
+    /// Background of the storage warning
+    #[serde(with = "hex")]
+    pub warning: [f32; 4],
+    #[serde(with = "hex")]
+    pub warning_text: [f32; 4],
+    #[serde(with = "hex")]
+    pub win_text: [f32; 4],
+    #[serde(with = "hex")]
+    pub lose_text: [f32; 4],
+    pub tictactoe: TicTacToePalette,
+    pub wordle: WordlePalette,
+    pub snake: SnakePalette,
+    pub twenty48: Twenty48Palette,
+    pub puzzle15: Puzzle15Palette,
+    pub breakout: BreakoutPalette,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TicTacToePalette {
+    #[serde(with = "hex")]
+    pub x: [f32; 4],
+    #[serde(with = "hex")]
+    pub o: [f32; 4],
+    #[serde(with = "hex")]
+    pub empty: [f32; 4],
+    /// Colour of the X and O drawn on the cells
+    #[serde(with = "hex")]
+    pub glyph: [f32; 4],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WordlePalette {
+    /// Right letter in the right place
+    #[serde(with = "hex")]
+    pub correct: [f32; 4],
+    /// Right letter in the wrong place
+    #[serde(with = "hex")]
+    pub present: [f32; 4],
+    /// Letter that isn't in the word
+    #[serde(with = "hex")]
+    pub absent: [f32; 4],
+    /// Rows that haven't been guessed yet
+    #[serde(with = "hex")]
+    pub empty: [f32; 4],
+    #[serde(with = "hex")]
+    pub border: [f32; 4],
+    #[serde(with = "hex")]
+    pub letter: [f32; 4],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnakePalette {
+    #[serde(with = "hex")]
+    pub snake: [f32; 4],
+    #[serde(with = "hex")]
+    pub food: [f32; 4],
+    #[serde(with = "hex")]
+    pub grid: [f32; 4],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Twenty48Palette {
+    /// Colour of the empty tile followed by the tiles 2, 4, 8 and so on
+    #[serde(with = "hex_list")]
+    pub tiles: Vec<[f32; 4]>,
+    /// Tiles past the end of `tiles`
+    #[serde(with = "hex")]
+    pub beyond: [f32; 4],
+    #[serde(with = "hex")]
+    pub text: [f32; 4],
+}
+
+impl Twenty48Palette {
+    pub fn tile(&self, val: u32) -> [f32; 4] {
+        let index = if val == 0 {
+            0
+        } else {
+            val.trailing_zeros() as usize
+        };
+        self.tiles.get(index).copied().unwrap_or(self.beyond)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Puzzle15Palette {
+    #[serde(with = "hex")]
+    pub tile: [f32; 4],
+    #[serde(with = "hex")]
+    pub empty: [f32; 4],
+    #[serde(with = "hex")]
+    pub text: [f32; 4],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BreakoutPalette {
+    #[serde(with = "hex")]
+    pub brick: [f32; 4],
+    #[serde(with = "hex")]
+    pub paddle: [f32; 4],
+    #[serde(with = "hex")]
+    pub ball: [f32; 4],
+    #[serde(with = "hex")]
+    pub wall: [f32; 4],
+}
+
+/// Palettes for colour blind players, they replace the game colours
+/// that can't be told apart with the given kind of colour blindness
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorVision {
+    #[default]
+    Normal,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl ColorVision {
+    pub const ALL: [ColorVision; 4] = [
+        ColorVision::Normal,
+        ColorVision::Deuteranopia,
+        ColorVision::Protanopia,
+        ColorVision::Tritanopia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorVision::Normal => "Normal",
+            ColorVision::Deuteranopia => "Deuteranopia",
+            ColorVision::Protanopia => "Protanopia",
+            ColorVision::Tritanopia => "Tritanopia",
+        }
+    }
+
+    // Colours that replace the ones of the theme, in the theme file format
+    fn overrides(self) -> Option<&'static str> {
+        match self {
+            ColorVision::Normal => None,
+            ColorVision::Deuteranopia => Some(include_str!("themes/vision/deuteranopia.toml")),
+            ColorVision::Protanopia => Some(include_str!("themes/vision/protanopia.toml")),
+            ColorVision::Tritanopia => Some(include_str!("themes/vision/tritanopia.toml")),
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the theme with the palette for `vision` applied
+    pub fn with_vision(&self, vision: ColorVision) -> Theme {
+        let overrides: toml::Value = match vision.overrides() {
+            Some(text) => toml::from_str(text).expect("colour vision palettes are valid"),
+            None => return self.clone(),
+        };
+
+        let mut json = serde_json::to_value(self).expect("themes can be serialized");
+        merge(&mut json, serde_json::to_value(overrides).unwrap());
+        serde_json::from_value(json).expect("colour vision palettes are valid")
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        built_in()
+            .iter()
+            .find(|theme| theme.name == DEFAULT_THEME)
+            .cloned()
+            .unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// Picks the format from the file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+}
+
+pub fn built_in() -> &'static [Theme] {
+    &BUILT_IN
+}
+
+/// Folder user themes are loaded from
+pub fn dir() -> PathBuf {
+    storage::dir(Kind::Data).join("themes")
+}
+
+// Overwrites the values of `base` that are set in `theme`, table by table
+fn merge(base: &mut Value, theme: Value) {
+    match (base, theme) {
+        (Value::Object(base), Value::Object(theme)) => {
+            for (key, value) in theme {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, theme) => *base = theme,
+    }
+}
+
+/// Builds a theme from a possibly partial theme file
+pub fn from_value(theme: Value) -> Result<Theme, String> {
+    let base = theme
+        .get("base")
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_THEME);
+    let base = built_in()
+        .iter()
+        .find(|theme| theme.name == base)
+        .ok_or_else(|| format!("unknown base theme {}", base))?;
+
+    let mut json = serde_json::to_value(base).map_err(|error| error.to_string())?;
+    merge(&mut json, theme);
+    serde_json::from_value(json).map_err(|error| error.to_string())
+}
+
+/// Loads a user theme, it is named after the file unless it has a name
+pub fn load_file(path: &Path, format: Format) -> Result<Theme, StorageError> {
+    let invalid = |error: String| StorageError::Invalid(path.to_path_buf(), error);
+
+    let text =
+        fs::read_to_string(path).map_err(|error| StorageError::Io(path.to_path_buf(), error))?;
+    let mut theme: Value = match format {
+        Format::Json => serde_json::from_str(&text).map_err(|error| invalid(error.to_string()))?,
+        Format::Toml => toml::from_str::<toml::Value>(&text)
+            .map_err(|error| error.to_string())
+            .and_then(|theme| serde_json::to_value(theme).map_err(|error| error.to_string()))
+            .map_err(invalid)?,
+    };
+
+    if let (Some(theme), Some(stem)) = (theme.as_object_mut(), path.file_stem()) {
+        theme
+            .entry("name")
+            .or_insert_with(|| stem.to_string_lossy().into());
+    }
+    from_value(theme).map_err(invalid)
+}
+
+/// Returns the built-in themes followed by the ones in `dir`.
+/// A user theme replaces an earlier theme with the same name.
+/// Broken files are skipped, the last error is returned next to the themes.
+pub fn load_all(dir: &Path) -> (Vec<Theme>, Option<StorageError>) {
+    let mut themes = built_in().to_vec();
+    let mut error = None;
+
+    // a missing folder just means there are no user themes
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+
+    for path in paths {
+        let format = match Format::from_path(&path) {
+            Some(format) => format,
+            None => continue,
+        };
+        match load_file(&path, format) {
+            Ok(theme) => match themes.iter().position(|other| other.name == theme.name) {
+                Some(index) => themes[index] = theme,
+                None => themes.push(theme),
+            },
+            Err(err) => error = Some(err),
+        }
+    }
+    (themes, error)
+}
+
+// Colours are written as `#rrggbb` or `#rrggbbaa`
+mod hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn to_string(color: &[f32; 4]) -> String {
+        let [r, g, b, a] = color.map(|channel| (channel * 255.0).round() as u8);
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<[f32; 4]> {
+        let digits = text.strip_prefix('#')?;
+        if !digits.is_ascii() || (digits.len() != 6 && digits.len() != 8) {
+            return None;
+        }
+
+        let mut color = [1.0; 4];
+        for (index, channel) in color.iter_mut().take(digits.len() / 2).enumerate() {
+            let byte = u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16).ok()?;
+            *channel = byte as f32 / 255.0;
+        }
+        Some(color)
+    }
+
+    pub fn serialize<S: Serializer>(color: &[f32; 4], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_string(color))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[f32; 4], D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).ok_or_else(|| {
+            D::Error::custom(format!(
+                "invalid colour {}, expected #rrggbb or #rrggbbaa",
+                text
+            ))
+        })
+    }
+}
+
+mod hex_list {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(colors: &[[f32; 4]], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(colors.iter().map(super::hex::to_string))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<[f32; 4]>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|text| {
+                super::hex::parse(text)
+                    .ok_or_else(|| D::Error::custom(format!("invalid colour {}", text)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::storage::tests::TempDir;
+    use crate::rgb;
+    use serde_json::json;
+
+    #[test]
+    fn built_in_themes() {
+        let names: Vec<&str> = built_in().iter().map(|theme| theme.name.as_str()).collect();
+        assert_eq!(names, ["Dark", "Light", "High Contrast"]);
+
+        // the dark theme keeps the colours of the old dark mode
+        let dark = Theme::default();
+        assert_eq!(dark.game_background, rgb!(100, 100, 100));
+        assert_eq!(dark.button, rgb!(18, 156, 255));
+        assert_eq!(dark.twenty48.tile(0), rgb!(204, 192, 179));
+        assert_eq!(dark.twenty48.tile(2048), rgb!(237, 194, 46));
+        assert_eq!(dark.twenty48.tile(4096), rgb!(255, 255, 255));
+    }
+
+    #[test]
+    fn partial_theme_uses_base() {
+        let theme = from_value(json!({
+            "name": "Mint",
+            "base": "Light",
+            "text": "#102030",
+            "tictactoe": { "x": "#00ff0080" },
+        }))
+        .unwrap();
+
+        let light = &built_in()[1];
+        assert_eq!(theme.name, "Mint");
+        assert_eq!(theme.text, rgb!(16, 32, 48));
+        assert_eq!(theme.tictactoe.x, rgb!(0, 255, 0, 128.0 / 255.0));
+        assert_eq!(theme.tictactoe.o, light.tictactoe.o);
+        assert_eq!(theme.background, light.background);
+    }
+
+    #[test]
+    fn invalid_themes_are_rejected() {
+        assert!(from_value(json!({ "text": "white" })).is_err());
+        assert!(from_value(json!({ "text": "#fffff" })).is_err());
+        assert!(from_value(json!({ "base": "Neon" })).is_err());
+    }
+
+    #[test]
+    fn vision_palettes() {
+        let theme = &built_in()[1];
+        for vision in ColorVision::ALL {
+            let adjusted = theme.with_vision(vision);
+            assert_eq!(adjusted.name, theme.name);
+            assert_eq!(adjusted.background, theme.background);
+            assert_ne!(adjusted.wordle.correct, adjusted.wordle.present);
+            assert_ne!(adjusted.tictactoe.x, adjusted.tictactoe.o);
+        }
+
+        let adjusted = theme.with_vision(ColorVision::Deuteranopia);
+        assert_eq!(adjusted.wordle.correct, rgb!(245, 121, 58));
+    }
+
+    #[test]
+    fn user_theme_files() {
+        let dir = TempDir::new("themes");
+        fs::write(dir.join("sunset.toml"), "background = \"#ff8000\"\n").unwrap();
+        fs::write(
+            dir.join("dark.json"),
+            r##"{"name": "Dark", "text": "#ff0000"}"##,
+        )
+        .unwrap();
+        fs::write(dir.join("broken.json"), "{").unwrap();
+        fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+
+        let (themes, error) = load_all(&dir.path);
+        let names: Vec<&str> = themes.iter().map(|theme| theme.name.as_str()).collect();
+        assert_eq!(names, ["Dark", "Light", "High Contrast", "sunset"]);
+        assert_eq!(themes[0].text, rgb!(255, 0, 0));
+        assert_eq!(themes[3].background, rgb!(255, 128, 0));
+        assert!(matches!(error, Some(StorageError::Invalid(..))));
+    }
+}
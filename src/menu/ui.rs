@@ -1,600 +1,1563 @@
 // use crate::breakout::ui::BreakoutApp;
 use crate::puzzle15::ui::Puzzle15App;
-use crate::reddit_meme::ui::{MemeApp, UPDATE};
+use crate::reddit_meme::ui::MemeApp;
 use crate::snake::ui::SnakeApp;
 use crate::tictactoe::ui::TicTacToeApp;
 use crate::twenty48::ui::Twenty48App;
+use crate::ultimate::ui::UltimateApp;
 use crate::wordle::ui::WordleApp;
 
 use crate::{
     components::{
         application::MiniApp,
-        button::{draw_text, Pos, UIButton},
-        color::Color,
+        button::{draw_text, ButtonStyle, Pos, UIButton},
+        input::{self, game_shortcut, key_name, Action, BindError, KeyBindings, KEYS_PER_ACTION},
+        layout::{self, Anchor, Rect},
+        registry::Registry,
+        render::{PistonRenderer, Renderer},
+        widgets::{
+            key_press, left_click, Dialog, Dropdown, Focus, List, Response, TextInput, Toggle,
+            Widget, WidgetState,
+        },
+        window::{self, WindowMode},
     },
     rgb,
 };
 
-use super::{config::Config, highscores::HighScores};
+use super::{
+    archive::{self, ImportMode},
+    config::Config,
+    highscores::HighScores,
+    profiles::{Profiles, MAX_NAME_LEN, MAX_PROFILES},
+    theme::{self, ColorVision},
+};
 use piston_window::*;
 
-pub const TOP_PAD: f64 = 104.0;
 pub const TASKBAR_HEIGHT: f64 = 85.0;
+const BANNER_HEIGHT: f64 = 34.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tab {
+    Home,
+    Games,
+    Stats,
+    Settings,
+    About,
+    /// Key bindings, opened from the Settings tab
+    Controls,
+    /// Index into the app registry
+    App(usize),
+}
+
+// Tabs shown in the taskbar, in order
+const TABS: [Tab; 5] = [Tab::Home, Tab::Games, Tab::Stats, Tab::Settings, Tab::About];
+
+// Labels and widths of the taskbar tabs
+const TAB_LABELS: [(&str, f64); 5] = [
+    (" Home", 100.0),
+    (" Games", 115.0),
+    (" Stats", 100.0),
+    (" Settings", 120.0),
+    ("About Us", 120.0),
+];
+
+// Games that record statistics, in the order shown in the Stats tab
+const STATS_GAMES: [&str; 6] = [
+    "tictactoe",
+    "ultimate",
+    "wordle",
+    "snake",
+    "twenty48",
+    "puzzle15",
+];
+
+/// What a profile name being typed in the Settings tab is for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProfileEdit {
+    Create,
+    /// Renames the active profile
+    Rename,
+}
+
+/// Something that can't be undone, asked about in a dialog first
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Confirm {
+    ResetHighscores,
+    ImportReplace,
+    DeleteProfile,
+}
+
+/// What a widget of the menu is for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuWidget {
+    Tab(usize),
+    /// Index into the app registry
+    Game(usize),
+    Theme,
+    Colours,
+    Window,
+    ShapeCues,
+    ResetHighscores,
+    Export,
+    ImportMerge,
+    ImportReplace,
+    Profiles,
+    NewProfile,
+    RenameProfile,
+    DeleteProfile,
+    KeyBindings,
+    /// Index into `Action::BINDABLE` and which of its keys
+    Binding(usize, usize),
+    ResetBindings,
+    ControlsBack,
+}
+
+/// Registers every mini-app shown in the Games tab.
+fn register_apps(registry: &mut Registry) {
+    registry
+        .register("tictactoe", "TicTacToe", rgb!(160, 237, 128), || {
+            Box::new(TicTacToeApp::new())
+        })
+        .register("ultimate", "Ultimate TTT", rgb!(120, 190, 96), || {
+            Box::new(UltimateApp::new())
+        })
+        .register("wordle", "Wordle", rgb!(77, 143, 69), || {
+            Box::new(WordleApp::new())
+        })
+        .register("snake", "Snake", rgb!(255, 0, 0), || {
+            Box::new(SnakeApp::new())
+        })
+        .register("twenty48", "2048", rgb!(237, 194, 46), || {
+            Box::new(Twenty48App::new())
+        })
+        .register("puzzle15", "15 Puzzle", rgb!(204, 192, 179), || {
+            Box::new(Puzzle15App::new())
+        })
+        .register("meme", "Memes", rgb!(18, 156, 255), || {
+            Box::new(MemeApp::new())
+        });
+    // .register("breakout", "Breakout", rgb!(200, 200, 100), || {
+    //     Box::new(BreakoutApp::new())
+    // });
+}
+
+// The widgets of the taskbar and the menu tabs
+struct MenuWidgets {
+    tabs: Vec<UIButton>,
+    games: Vec<UIButton>,
+    theme: Dropdown,
+    colours: Dropdown,
+    window: Dropdown,
+    shape_cues: Toggle,
+    reset_highscores: UIButton,
+    export: UIButton,
+    import_merge: UIButton,
+    import_replace: UIButton,
+    profiles: List,
+    new_profile: UIButton,
+    rename_profile: UIButton,
+    delete_profile: UIButton,
+    key_bindings: UIButton,
+    // one button for every key of every action
+    bindings: Vec<[UIButton; KEYS_PER_ACTION]>,
+    reset_bindings: UIButton,
+    controls_back: UIButton,
+}
+
+impl MenuWidgets {
+    fn new(registry: &Registry) -> Self {
+        let colours = ColorVision::ALL
+            .iter()
+            .map(|vision| vision.name().to_string());
+        let modes = WindowMode::ALL.iter().map(|mode| mode.name().to_string());
+        MenuWidgets {
+            tabs: TAB_LABELS
+                .iter()
+                .map(|(label, _)| UIButton::new(label, ButtonStyle::Flat, 24))
+                .collect(),
+            games: registry
+                .entries()
+                .iter()
+                .map(|entry| UIButton::new(entry.name, ButtonStyle::Flat, 24))
+                .collect(),
+            theme: Dropdown::new("Theme", theme_names(), 0),
+            colours: Dropdown::new("Colours", colours.collect(), 0),
+            window: Dropdown::new("Window", modes.collect(), 0),
+            shape_cues: Toggle::new("Shape cues", true),
+            reset_highscores: UIButton::new("Reset Highscores", ButtonStyle::Danger, 24),
+            export: UIButton::new("    Export Data", ButtonStyle::Primary, 24),
+            import_merge: UIButton::new("   Import (Merge)", ButtonStyle::Primary, 24),
+            import_replace: UIButton::new(" Import (Replace)", ButtonStyle::Danger, 24),
+            profiles: List::new(44.0),
+            new_profile: UIButton::new("  New", ButtonStyle::Primary, 20),
+            rename_profile: UIButton::new("Rename", ButtonStyle::Primary, 20),
+            delete_profile: UIButton::new(" Delete", ButtonStyle::Danger, 20),
+            key_bindings: UIButton::new("   Key Bindings", ButtonStyle::Primary, 24),
+            bindings: Action::BINDABLE
+                .iter()
+                .map(|_| [(); KEYS_PER_ACTION].map(|_| UIButton::new("", ButtonStyle::Primary, 20)))
+                .collect(),
+            reset_bindings: UIButton::new("Reset to Defaults", ButtonStyle::Danger, 20),
+            controls_back: UIButton::new("     Back", ButtonStyle::Primary, 20),
+        }
+    }
+
+    /// Shows the current settings in the pickers
+    fn sync_config(&mut self, config: &Config) {
+        self.theme.select(&config.theme.name);
+        self.colours.select(config.options.color_vision.name());
+        self.window.select(config.options.window.mode.name());
+        self.shape_cues.on = config.options.shape_cues;
+    }
+
+    /// Shows the bound keys on the buttons of the Controls tab,
+    /// `capturing` is the one waiting for a key
+    fn sync_bindings(&mut self, bindings: &KeyBindings, capturing: Option<(usize, usize)>) {
+        for (action, buttons) in Action::BINDABLE.iter().zip(&mut self.bindings) {
+            let keys = bindings.keys(*action);
+            for (slot, button) in buttons.iter_mut().enumerate() {
+                button.text = keys.get(slot).map_or("-".to_string(), |key| key_name(*key));
+            }
+        }
+        if let Some((action, slot)) = capturing {
+            self.bindings[action][slot].text = "Press a key".to_string();
+        }
+    }
+
+    /// Lists the current profiles, the active one selected
+    fn sync_profiles(&mut self, profiles: &Profiles) {
+        let names = profiles.list().iter().map(|profile| profile.name.clone());
+        self.profiles.set_items(names.collect());
+        self.profiles.selected = Some(profiles.active_index());
+    }
+
+    // The widgets shown with `tab`, in the order the focus goes through them
+    fn widgets(&self, tab: Tab) -> Vec<(MenuWidget, &dyn Widget)> {
+        let mut widgets: Vec<(MenuWidget, &dyn Widget)> = Vec::new();
+        for (index, button) in self.tabs.iter().enumerate() {
+            widgets.push((MenuWidget::Tab(index), button));
+        }
+        match tab {
+            Tab::Games => {
+                for (index, button) in self.games.iter().enumerate() {
+                    widgets.push((MenuWidget::Game(index), button));
+                }
+            }
+            Tab::Settings => widgets.extend([
+                (MenuWidget::Theme, &self.theme as &dyn Widget),
+                (MenuWidget::Colours, &self.colours),
+                (MenuWidget::Window, &self.window),
+                (MenuWidget::ShapeCues, &self.shape_cues),
+                (MenuWidget::ResetHighscores, &self.reset_highscores),
+                (MenuWidget::Export, &self.export),
+                (MenuWidget::ImportMerge, &self.import_merge),
+                (MenuWidget::ImportReplace, &self.import_replace),
+                (MenuWidget::Profiles, &self.profiles),
+                (MenuWidget::NewProfile, &self.new_profile),
+                (MenuWidget::RenameProfile, &self.rename_profile),
+                (MenuWidget::DeleteProfile, &self.delete_profile),
+                (MenuWidget::KeyBindings, &self.key_bindings),
+            ]),
+            Tab::Controls => {
+                for (action, buttons) in self.bindings.iter().enumerate() {
+                    for (slot, button) in buttons.iter().enumerate() {
+                        widgets.push((MenuWidget::Binding(action, slot), button));
+                    }
+                }
+                widgets.push((MenuWidget::ResetBindings, &self.reset_bindings));
+                widgets.push((MenuWidget::ControlsBack, &self.controls_back));
+            }
+            _ => (),
+        }
+        widgets
+    }
+
+    // Same as `widgets`, for passing events to them
+    fn widgets_mut(&mut self, tab: Tab) -> Vec<(MenuWidget, &mut dyn Widget)> {
+        let mut widgets: Vec<(MenuWidget, &mut dyn Widget)> = Vec::new();
+        for (index, button) in self.tabs.iter_mut().enumerate() {
+            widgets.push((MenuWidget::Tab(index), button));
+        }
+        match tab {
+            Tab::Games => {
+                for (index, button) in self.games.iter_mut().enumerate() {
+                    widgets.push((MenuWidget::Game(index), button));
+                }
+            }
+            Tab::Settings => widgets.extend([
+                (MenuWidget::Theme, &mut self.theme as &mut dyn Widget),
+                (MenuWidget::Colours, &mut self.colours),
+                (MenuWidget::Window, &mut self.window),
+                (MenuWidget::ShapeCues, &mut self.shape_cues),
+                (MenuWidget::ResetHighscores, &mut self.reset_highscores),
+                (MenuWidget::Export, &mut self.export),
+                (MenuWidget::ImportMerge, &mut self.import_merge),
+                (MenuWidget::ImportReplace, &mut self.import_replace),
+                (MenuWidget::Profiles, &mut self.profiles),
+                (MenuWidget::NewProfile, &mut self.new_profile),
+                (MenuWidget::RenameProfile, &mut self.rename_profile),
+                (MenuWidget::DeleteProfile, &mut self.delete_profile),
+                (MenuWidget::KeyBindings, &mut self.key_bindings),
+            ]),
+            Tab::Controls => {
+                for (action, buttons) in self.bindings.iter_mut().enumerate() {
+                    for (slot, button) in buttons.iter_mut().enumerate() {
+                        widgets.push((MenuWidget::Binding(action, slot), button));
+                    }
+                }
+                widgets.push((MenuWidget::ResetBindings, &mut self.reset_bindings));
+                widgets.push((MenuWidget::ControlsBack, &mut self.controls_back));
+            }
+            _ => (),
+        }
+        widgets
+    }
+}
 
-const GAMES: usize = 6;
 pub struct MainMenu {
-    pub hover_pos: [f64; 2],
-    pub tab: usize,
-    pub apps: [Box<dyn MiniApp>; GAMES],
-    prev_tab: usize,
+    pub tab: Tab,
+    pub registry: Registry,
+    pub apps: Vec<Box<dyn MiniApp>>,
+    pub profiles: Profiles,
+    widgets: MenuWidgets,
+    focus: Focus,
+    // the profile name being typed
+    editing: Option<(ProfileEdit, TextInput)>,
+    // asks before doing something that can't be undone
+    dialog: Option<(Confirm, Dialog)>,
+    // result of the last export or import
+    archive_status: Option<String>,
+    // the key binding waiting for a key, an index into `Action::BINDABLE` and the slot
+    capturing: Option<(usize, usize)>,
+    // why the last key couldn't be bound
+    bindings_status: Option<String>,
 }
 
 impl MainMenu {
     pub fn new() -> Self {
+        let mut registry = Registry::new();
+        register_apps(&mut registry);
+
         MainMenu {
-            hover_pos: [0.0, 0.0],
-            tab: 0,
+            tab: Tab::Home,
             // make list that contains all apps
-            apps: [
-                Box::new(TicTacToeApp::new()),
-                Box::new(WordleApp::new()),
-                Box::new(SnakeApp::new()),
-                Box::new(Twenty48App::new()),
-                Box::new(Puzzle15App::new()),
-                Box::new(MemeApp::new()),
-                // Box::new(BreakoutApp::new()),
-            ],
-            prev_tab: 69,
+            apps: registry.build_all(),
+            widgets: MenuWidgets::new(&registry),
+            registry,
+            profiles: Profiles::new(),
+            focus: Focus::new(),
+            editing: None,
+            dialog: None,
+            archive_status: None,
+            capturing: None,
+            bindings_status: None,
+        }
+    }
+
+    /// Finds a taskbar tab by name or an app by its registry id
+    pub fn tab_by_id(&self, id: &str) -> Option<Tab> {
+        match id {
+            "home" => Some(Tab::Home),
+            "games" => Some(Tab::Games),
+            "stats" => Some(Tab::Stats),
+            "settings" => Some(Tab::Settings),
+            "about" => Some(Tab::About),
+            "controls" => Some(Tab::Controls),
+            _ => self.registry.position(id).map(Tab::App),
+        }
+    }
+
+    fn title(&self) -> String {
+        match self.tab {
+            Tab::Home => "Home",
+            Tab::Games => "Games",
+            Tab::Stats => "Stats",
+            Tab::Settings => "Settings",
+            Tab::About => "About Us",
+            Tab::Controls => "Key Bindings",
+            Tab::App(index) => self.registry.entries()[index].name,
+        }
+        .to_string()
+    }
+
+    /// The tab a key or controller button jumps to: Back goes to the games,
+    /// or the settings from the key bindings, unless a running game pauses
+    /// with it. The number keys open the games in the order they are listed
+    fn shortcut(&self, event: &Event, bindings: &KeyBindings) -> Option<Tab> {
+        if let Tab::App(index) = self.tab {
+            if self.apps[index].takes_keyboard() {
+                return None;
+            }
         }
+        if bindings.action(event) == Some(Action::Back) {
+            return match self.tab {
+                Tab::Games => None,
+                Tab::App(index) if self.apps[index].takes_back() => None,
+                Tab::Controls => Some(Tab::Settings),
+                _ => Some(Tab::Games),
+            };
+        }
+        let tab = Tab::App(game_shortcut(key_press(event)?)?);
+        match tab {
+            Tab::App(index) if index >= self.apps.len() => None,
+            tab if tab == self.tab => None,
+            tab => Some(tab),
+        }
+    }
+
+    /// Shows the current settings and profiles in the Settings and Controls tabs
+    pub fn sync_settings(&mut self, config: &Config) {
+        self.widgets.sync_config(config);
+        self.widgets.sync_profiles(&self.profiles);
+        self.widgets
+            .sync_bindings(&config.options.bindings, self.capturing);
     }
 }
 
-impl MiniApp for MainMenu {
-    fn render(
+impl MainMenu {
+    /// Makes the profile at `index` active and loads its config and highscores
+    fn switch_profile(
         &mut self,
+        index: usize,
         window: &mut PistonWindow,
-        event: &Event,
-        glyphs: &mut Glyphs,
         config: &mut Config,
         highscores: &mut HighScores,
     ) {
-        if let Some([cx, cy]) = event.mouse_cursor_args() {
-            self.hover_pos = [cx, cy];
+        self.profiles.switch(index);
+        self.profiles.save_profiles();
+
+        let profile = self.profiles.active();
+        *config = Config::fetch_config(profile.config_location());
+        *highscores = HighScores::fetch_scores(profile.highscores_location());
+        // the profile may use another window mode
+        window::set_mode(window, config.options.window.mode);
+    }
+
+    // Handles typing a profile name, returns true if the event was used
+    fn edit_profile(&mut self, event: &Event) -> bool {
+        let (edit, input) = match self.editing.as_mut() {
+            Some(editing) => editing,
+            None => return false,
+        };
+
+        // the input has the keyboard until the name is saved or dropped
+        let state = WidgetState {
+            focused: true,
+            ..WidgetState::default()
+        };
+        let rect = ProfileLayout::new([0.0; 2]).input;
+        match input.handle_event(event, rect, state) {
+            Response::Ignored => return false,
+            Response::Cancelled => self.editing = None,
+            Response::Submitted => {
+                let name = input.value().to_string();
+                match edit {
+                    _ if name.is_empty() => (),
+                    ProfileEdit::Create => {
+                        self.profiles.create(&name);
+                    }
+                    ProfileEdit::Rename => {
+                        let active = self.profiles.active_index();
+                        self.profiles.rename(active, &name);
+                    }
+                }
+                self.editing = None;
+                self.profiles.save_profiles();
+                self.widgets.sync_profiles(&self.profiles);
+            }
+            _ => (),
         }
+        true
+    }
 
-        // init variables
-        let size = window.size();
+    // Binds the next key pressed to the key binding waiting for one,
+    // returns true if the event was used
+    fn capture_key(&mut self, event: &Event, config: &mut Config) -> bool {
+        let (index, slot) = match self.capturing {
+            Some(capturing) => capturing,
+            None => return false,
+        };
+        let key = match key_press(event) {
+            Some(key) => key,
+            None => {
+                // clicking somewhere else stops waiting
+                if left_click(event) {
+                    self.capturing = None;
+                    self.sync_settings(config);
+                }
+                return false;
+            }
+        };
 
-        // init buttons
-        let mut tabs = [
-            UIButton::new(
-                " Home",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 30.0, y: 0.0 },
-                100.0,
-                84.0,
-            ),
-            UIButton::new(
-                " Games",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 130.0, y: 0.0 },
-                115.0,
-                84.0,
-            ),
-            UIButton::new(
-                " Settings",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 245.0, y: 0.0 },
-                120.0,
-                84.0,
-            ),
-            UIButton::new(
-                "About Us",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 370.0, y: 0.0 },
-                120.0,
-                84.0,
-            ),
-        ];
-
-        let mut game_buttons = [
-            UIButton::new(
-                "Play TicTacToe",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 40.0, y: 120.0 },
-                224.0,
-                56.0,
-            ),
-            UIButton::new(
-                "Play Wordle",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 40.0, y: 180.0 },
-                224.0,
-                56.0,
-            ),
-            UIButton::new(
-                "Play Snake",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 40.0, y: 240.0 },
-                224.0,
-                56.0,
-            ),
-            UIButton::new(
-                "Play 2048",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 40.0, y: 300.0 },
-                224.0,
-                56.0,
-            ),
-            UIButton::new(
-                "Play 15 Puzzle",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 40.0, y: 360.0 },
-                224.0,
-                56.0,
-            ),
-            UIButton::new(
-                "Show Meme",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 40.0, y: 420.0 },
-                224.0,
-                56.0,
+        let action = Action::BINDABLE[index];
+        let bindings = &mut config.options.bindings;
+        self.bindings_status = match key {
+            Key::Escape => None,
+            Key::Backspace | Key::Delete => {
+                bindings.clear(action, slot);
+                None
+            }
+            key => match bindings.bind(action, slot, key) {
+                Ok(()) => None,
+                Err(BindError::Taken(other)) => Some(format!(
+                    "{} is already used for {}",
+                    key_name(key),
+                    other.name()
+                )),
+                Err(BindError::Reserved) => {
+                    Some(format!("{} opens a game from the menu", key_name(key)))
+                }
+            },
+        };
+        self.capturing = None;
+        config.save_config();
+        self.sync_settings(config);
+        true
+    }
+
+    // Opens a dialog asking to confirm `confirm`
+    fn ask(&mut self, confirm: Confirm) {
+        let dialog = match confirm {
+            Confirm::ResetHighscores => Dialog::confirm(
+                "Reset highscores?",
+                "All scores and stats of this profile are cleared.",
+                "  Reset",
             ),
-        ];
-
-        let mut config_buttons = [
-            UIButton::new(
-                "Dark Theme",
-                Color::CLEAR,
-                Color::BLACK,
-                24,
-                Pos { x: 40.0, y: 120.0 },
-                224.0,
-                56.0,
+            Confirm::ImportReplace => Dialog::confirm(
+                "Replace saved data?",
+                "The settings and scores are replaced by the archive.",
+                " Replace",
             ),
-            UIButton::new(
-                "Reset Highscores",
-                Color::RESET,
-                Color::WHITE,
-                24,
-                Pos { x: 50.0, y: 200.0 },
-                240.0,
-                56.0,
+            Confirm::DeleteProfile => Dialog::confirm(
+                "Delete profile?",
+                &format!(
+                    "{} and its scores are removed.",
+                    self.profiles.active().name
+                ),
+                "  Delete",
             ),
-        ];
+        };
+        self.dialog = Some((confirm, dialog));
+        self.focus.set_focused(None);
+    }
+
+    // Does what the dialog asked about once it is confirmed
+    fn confirmed(
+        &mut self,
+        confirm: Confirm,
+        window: &mut PistonWindow,
+        config: &mut Config,
+        highscores: &mut HighScores,
+    ) {
+        match confirm {
+            Confirm::ResetHighscores => {
+                highscores.reset_highscores();
+                highscores.save_scores();
+            }
+            Confirm::ImportReplace => self.import(ImportMode::Replace, window, config, highscores),
+            Confirm::DeleteProfile => {
+                let active = self.profiles.active_index();
+                if let Some(profile) = self.profiles.delete(active) {
+                    profile.remove_files();
+                    self.switch_profile(0, window, config, highscores);
+                }
+            }
+        }
+    }
+
+    fn import(
+        &mut self,
+        mode: ImportMode,
+        window: &mut PistonWindow,
+        config: &mut Config,
+        highscores: &mut HighScores,
+    ) {
+        let path = archive::default_location();
+        self.archive_status = match archive::import(&path, mode, config, highscores) {
+            Ok(()) => {
+                // the imported config may use another window mode
+                window::set_mode(window, config.options.window.mode);
+                Some(format!("Imported {}", path.display()))
+            }
+            Err(error) => {
+                highscores.error = Some(error);
+                None
+            }
+        };
+    }
 
-        // change style's depending on theme
-        if !config.options.white_theme {
-            config_buttons[0].text = "Light Theme".to_string();
-            config_buttons[0].text_color = Color::WHITE;
+    /// Opens the given tab, notifying the apps that are left and entered.
+    pub fn switch_tab(
+        &mut self,
+        tab: Tab,
+        window: &mut PistonWindow,
+        config: &mut Config,
+        highscores: &mut HighScores,
+    ) {
+        if let Tab::App(index) = self.tab {
+            // a running game waits for the player to come back
+            self.apps[index].pause();
+            self.apps[index].on_exit(config, highscores);
+        }
+        self.tab = tab;
+        // the focus is an index into the widgets of the old tab
+        self.focus.set_focused(None);
+        self.capturing = None;
+        self.bindings_status = None;
+
+        // set window title, apps may override it when entered
+        window.set_title(format!("UnoLife - {}", self.title()));
+
+        if matches!(tab, Tab::Settings | Tab::Controls) {
+            // theme files may have been added since
+            self.widgets.theme.options = theme_names();
+            self.sync_settings(config);
+        }
+
+        if let Tab::App(index) = self.tab {
+            self.apps[index].on_enter(window, config, highscores);
+        }
+    }
 
-            for button in tabs.iter_mut() {
-                button.text_color = Color::WHITE;
+    /// Dispatches a window event to the menu and the open app.
+    pub fn event(
+        &mut self,
+        window: &mut PistonWindow,
+        event: &Event,
+        glyphs: &mut Glyphs,
+        config: &mut Config,
+        highscores: &mut HighScores,
+    ) {
+        if let Some(args) = event.update_args() {
+            if let Tab::App(index) = self.tab {
+                self.apps[index].update(args.dt, config, highscores);
             }
+        } else if event.render_args().is_some() {
+            window.draw_2d(event, |c, g, device| {
+                self.draw(&mut PistonRenderer::new(c, g, glyphs), config, highscores);
+
+                // Update glyphs before rendering
+                glyphs.factory.encoder.flush(device);
+            });
+        } else {
+            self.handle_event(window, event, config, highscores);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        window: &mut PistonWindow,
+        event: &Event,
+        config: &mut Config,
+        highscores: &mut HighScores,
+    ) {
+        let size = window.size();
+        let size = [size.width, size.height];
 
-            for button in game_buttons.iter_mut() {
-                button.text_color = Color::WHITE;
+        if event.focus_args() == Some(false) {
+            if let Tab::App(index) = self.tab {
+                self.apps[index].pause();
             }
         }
 
-        let left_click = event.press_args() == Some(Button::Mouse(MouseButton::Left));
+        // F11 goes in and out of fullscreen anywhere
+        if event.press_args() == Some(Button::Keyboard(Key::F11)) {
+            let mode = match config.options.window.mode {
+                WindowMode::Fullscreen => WindowMode::Windowed,
+                _ => WindowMode::Fullscreen,
+            };
+            switch_window_mode(window, mode, config);
+            self.widgets.sync_config(config);
+            return;
+        }
 
-        // handle button events
-        for (index, button) in tabs.iter_mut().enumerate() {
-            if button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-                if left_click {
-                    self.tab = index;
-                    self.prev_tab = 69;
-                } else {
-                    button.color = rgb!(120, 120, 120, 0.35);
+        // a dialog takes every event until it is answered
+        if let Some((confirm, dialog)) = self.dialog.as_mut() {
+            let confirm = *confirm;
+            match self
+                .focus
+                .dispatch(event, &mut [(dialog, Rect::window(size))])
+            {
+                Some((_, Response::Picked(0))) => {
+                    self.dialog = None;
+                    self.confirmed(confirm, window, config, highscores);
+                    self.sync_settings(config);
+                }
+                Some((_, Response::Picked(_))) | Some((_, Response::Cancelled)) => {
+                    self.dialog = None;
                 }
+                _ => (),
             }
+            return;
         }
 
-        // Handle if hovered on / pressed buttons in game tab
-        if self.tab == 1 {
-            for (index, button) in game_buttons.iter_mut().enumerate() {
-                if button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-                    if left_click {
-                        self.tab = index + tabs.len();
-                        self.prev_tab = 69;
-                    } else {
-                        button.color = rgb!(120, 120, 120, 0.35);
-                    }
-                }
+        if self.tab == Tab::Settings && self.edit_profile(event) {
+            return;
+        }
+        if self.tab == Tab::Controls && self.capture_key(event, config) {
+            return;
+        }
+
+        // dismiss the storage warning
+        let error = config.error.is_some() || highscores.error.is_some();
+        if (error || self.profiles.error.is_some())
+            && event.press_args() == Some(Button::Mouse(MouseButton::Left))
+        {
+            let [x, y] = self.focus.cursor();
+            let [left, top, w, h] = banner_rect(size);
+            if Rect::new(left, top, w, h).contains(x, y) {
+                config.error = None;
+                highscores.error = None;
+                self.profiles.error = None;
+                return;
             }
         }
 
-        // handle config button events
-        if self.tab == 2 {
-            for (index, button) in config_buttons.iter_mut().enumerate() {
-                if button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-                    if left_click {
-                        match index {
-                            0 => {
-                                config.options.white_theme = !config.options.white_theme;
-                                config.save_config(config.location.clone());
-                            }
-                            1 => {
-                                highscores.reset_highscores();
-                                highscores.save_scores();
-                            }
-                            _ => (),
-                        }
-                    } else {
-                        match index {
-                            1 => {
-                                button.width += 6.0;
-                                button.pos.x -= 3.0;
-                                button.height += 6.0;
-                                button.pos.y -= 3.0;
-                                button.size += 1;
-                            }
-                            _ => button.color = rgb!(120, 120, 120, 0.35),
+        let action = Action::from_event(event);
+        let (ids, mut widgets): (Vec<_>, Vec<_>) = self
+            .widgets
+            .widgets_mut(self.tab)
+            .into_iter()
+            .map(|(id, widget)| (id, (widget, widget_rect(id, size))))
+            .unzip();
+        let response = match (self.tab, action) {
+            // in a game the keys are the game's, apart from the shortcuts
+            (Tab::App(_), _) if input::is_press(event) => None,
+            _ => self.focus.dispatch(event, &mut widgets).or_else(|| {
+                // the arrow keys move the focus when the focused widget doesn't use them
+                let forward = match action? {
+                    Action::Up | Action::Left => false,
+                    Action::Down | Action::Right => true,
+                    _ => return None,
+                };
+                let index = self.focus.step(forward, &widgets)?;
+                Some((index, Response::Consumed))
+            }),
+        }
+        .map(|(index, response)| (ids[index], response));
+        drop(widgets);
+
+        match response {
+            Some((MenuWidget::Tab(index), Response::Clicked)) => {
+                self.switch_tab(TABS[index], window, config, highscores);
+            }
+            Some((MenuWidget::Game(index), Response::Clicked)) => {
+                self.switch_tab(Tab::App(index), window, config, highscores);
+            }
+            Some((MenuWidget::Theme, Response::Picked(index))) => {
+                let name = self.widgets.theme.options[index].clone();
+                config.set_theme(&name);
+                config.save_config();
+            }
+            Some((MenuWidget::Colours, Response::Picked(index))) => {
+                config.set_color_vision(ColorVision::ALL[index]);
+                config.save_config();
+            }
+            Some((MenuWidget::Window, Response::Picked(index))) => {
+                switch_window_mode(window, WindowMode::ALL[index], config);
+            }
+            Some((MenuWidget::ShapeCues, Response::Changed)) => {
+                config.options.shape_cues = self.widgets.shape_cues.on;
+                config.save_config();
+            }
+            Some((MenuWidget::ResetHighscores, Response::Clicked)) => {
+                self.ask(Confirm::ResetHighscores);
+            }
+            Some((MenuWidget::Export, Response::Clicked)) => {
+                let path = archive::default_location();
+                self.archive_status = match archive::export(&path, config, highscores) {
+                    Ok(()) => Some(format!("Exported to {}", path.display())),
+                    Err(error) => {
+                        highscores.error = Some(error);
+                        None
+                    }
+                };
+            }
+            Some((MenuWidget::ImportMerge, Response::Clicked)) => {
+                self.import(ImportMode::Merge, window, config, highscores);
+            }
+            Some((MenuWidget::ImportReplace, Response::Clicked)) => {
+                self.ask(Confirm::ImportReplace);
+            }
+            Some((MenuWidget::Profiles, Response::Picked(index)))
+                if index != self.profiles.active_index() =>
+            {
+                self.editing = None;
+                self.switch_profile(index, window, config, highscores);
+            }
+            Some((MenuWidget::NewProfile, Response::Clicked))
+                if self.profiles.list().len() < MAX_PROFILES =>
+            {
+                self.editing = Some((ProfileEdit::Create, TextInput::new(MAX_NAME_LEN)));
+            }
+            Some((MenuWidget::RenameProfile, Response::Clicked)) => {
+                let input = TextInput::new(MAX_NAME_LEN).with_text(&self.profiles.active().name);
+                self.editing = Some((ProfileEdit::Rename, input));
+            }
+            Some((MenuWidget::DeleteProfile, Response::Clicked)) => {
+                self.ask(Confirm::DeleteProfile);
+            }
+            Some((MenuWidget::KeyBindings, Response::Clicked)) => {
+                self.switch_tab(Tab::Controls, window, config, highscores);
+            }
+            Some((MenuWidget::Binding(action, slot), Response::Clicked)) => {
+                self.capturing = Some((action, slot));
+                self.bindings_status = None;
+            }
+            Some((MenuWidget::ResetBindings, Response::Clicked)) => {
+                config.options.bindings = KeyBindings::default();
+                config.save_config();
+                self.bindings_status = None;
+            }
+            Some((MenuWidget::ControlsBack, Response::Clicked)) => {
+                self.switch_tab(Tab::Settings, window, config, highscores);
+            }
+            Some(_) => (),
+            None => match self.shortcut(event, &config.options.bindings) {
+                Some(tab) => self.switch_tab(tab, window, config, highscores),
+                // the open app gets what the taskbar didn't use
+                None => {
+                    if let Tab::App(index) = self.tab {
+                        self.apps[index].handle_event(event, size, config, highscores);
+                        if self.apps[index].quit_requested() {
+                            self.switch_tab(Tab::Games, window, config, highscores);
                         }
                     }
                 }
-            }
+            },
+        }
+
+        if matches!(self.tab, Tab::Settings | Tab::Controls) {
+            self.sync_settings(config);
         }
+    }
+
+    pub fn draw(&self, r: &mut dyn Renderer, config: &Config, highscores: &HighScores) {
+        let size = r.size();
 
         match self.tab {
-            tab if tab < tabs.len() => {
-                window.draw_2d(event, |_, g, _| {
-                    clear(
-                        if config.options.white_theme {
-                            rgb!(212, 248, 255)
-                        } else {
-                            rgb!(30, 30, 30)
-                        },
-                        g,
-                    );
-                });
+            Tab::App(index) => self.apps[index].draw(r, config, highscores),
+            _ => r.clear(config.theme.background),
+        }
 
-                // set update to true because tab is not meme app
-                unsafe {
-                    UPDATE = true;
-                }
+        // draw taskbar
+        {
+            r.rectangle(config.theme.taskbar, [0.0, 0.0, size[0], TASKBAR_HEIGHT]);
 
-                // set window title
-                if self.prev_tab != self.tab {
-                    window.set_title(format!("UnoLife - {}", tabs[self.tab].text.clone().trim()));
-                    self.prev_tab = self.tab;
-                }
-            }
-            _ => {
-                self.apps[self.tab - tabs.len()].render(window, event, glyphs, config, highscores);
+            // draw black line sepperating the task bar from the content
+            r.line(
+                config.theme.separator,
+                0.5,
+                [0.0, TASKBAR_HEIGHT, size[0], TASKBAR_HEIGHT],
+            );
+        }
 
-                // set window title
-                if self.prev_tab != self.tab && self.tab != 9 {
-                    window.set_title(format!(
-                        "UnoLife - {}",
-                        game_buttons[self.tab - tabs.len()].text.clone().trim()
-                    ));
-                    self.prev_tab = self.tab;
-                }
-            }
-        };
+        // text pages start a bit further in than the buttons
+        let page = layout::content(size).pad(40.0);
 
-        window.draw_2d(event, |c, g, device| {
-            // draw taskbar
-            {
-                rectangle(
-                    if config.options.white_theme {
-                        Color::WHITE
-                    } else {
-                        rgb!(60, 60, 60)
+        match self.tab {
+            Tab::Home => {
+                // HOME TAB
+                // draw text
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 30.0,
                     },
-                    [0.0, 0.0, size.width, 85.0],
-                    c.transform,
-                    g,
+                    "Welcome to UnoLife!",
+                    30,
                 );
-
-                // draw black line sepperating the task bar from the content
-                line(
-                    Color::BLACK,
-                    0.5,
-                    [0.0, 85.0, size.width, 85.0],
-                    c.transform,
-                    g,
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 120.0,
+                    },
+                    "Click on the games tab to start playing dozens of games!",
+                    24,
+                );
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 155.0,
+                    },
+                    "Click on the settings tab to change the theme and other settings!",
+                    24,
+                );
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 190.0,
+                    },
+                    "Check out the about us tab for more information on the creators!",
+                    24,
+                );
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 280.0,
+                    },
+                    "And most importantly, have fun!",
+                    24,
                 );
-
-                // draw buttons
-                for button in tabs.iter() {
-                    button.draw(&c, g, glyphs);
-                }
             }
-
-            match self.tab {
-                0 => {
-                    // HOME TAB
-                    // draw text
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 80.0,
-                        },
-                        "Welcome to UnoLife!",
-                        30,
-                    );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 170.0,
-                        },
-                        "Click on the games tab to start playing dozens of games!",
-                        24,
-                    );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 205.0,
-                        },
-                        "Click on the settings tab to change the theme and other settings!",
-                        24,
-                    );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 240.0,
-                        },
-                        "Check out the about us tab for more information on the creators!",
-                        24,
-                    );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 330.0,
-                        },
-                        "And most importantly, have fun!",
-                        24,
+            Tab::Games => {
+                // GAMES TAB
+                // draw icons, the buttons are drawn with the other widgets
+                for (index, entry) in self.registry.entries().iter().enumerate() {
+                    let button = widget_rect(MenuWidget::Game(index), size);
+                    r.round_rectangle(
+                        entry.icon,
+                        4.0,
+                        [button.x - 30.0, button.y + 16.0, 24.0, 24.0],
                     );
                 }
-                1 => {
-                    // GAMES TAB
-                    // draw
-                    for button in game_buttons.iter() {
-                        button.draw(&c, g, glyphs);
-                    }
-                }
-                2 => {
-                    // SETTINGS TAB
-                    // draw
-                    for button in config_buttons {
-                        button.draw(&c, g, glyphs);
-                    }
+            }
+            Tab::Stats => self.draw_stats(r, page, config, highscores),
+            Tab::Settings => {
+                // SETTINGS TAB
+                // draw
+                self.draw_profiles(r, config);
+
+                if let Some(status) = &self.archive_status {
+                    let (line, _) = settings_columns(size).0.split_bottom(20.0);
+                    draw_text(r, config.theme.text, line.pos(), status, 16);
                 }
-                3 => {
-                    // ABOUS US TAB
-                    // draw
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 70.0,
-                        },
-                        "FirePlank",
-                        30,
-                    );
+            }
+            Tab::Controls => self.draw_controls(r, config),
+            Tab::About => {
+                // ABOUS US TAB
+                // draw
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 20.0,
+                    },
+                    "FirePlank",
+                    30,
+                );
 
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 125.0,
-                        },
-                        "Hi! I'm a 16 yr old programmer, hacker, pentester and a chess enthusiast.",
-                        20,
-                    );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 155.0,
-                        },
-                        "We made this app to test out GUI development in rust and learn more about it.",
-                        20,
-                    );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 75.0,
+                    },
+                    "Hi! I'm a 16 yr old programmer, hacker, pentester and a chess enthusiast.",
+                    20,
+                );
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 105.0,
+                    },
+                    "We made this app to test out GUI development in rust and learn more about it.",
+                    20,
+                );
+                draw_text(
+                        r,
+                        config.theme.text,
                         Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 185.0,
+                            x: page.x,
+                            y: page.y + 135.0,
                         },
                         "You can find my github as one of the contributors on the github page of UnoLife.",
                         20,
                     );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 215.0,
-                        },
-                        "I hope you enjoy the app as it took a long time to make!",
-                        20,
-                    );
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 165.0,
+                    },
+                    "I hope you enjoy the app as it took a long time to make!",
+                    20,
+                );
 
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 285.0,
-                        },
-                        "Bunch-of-cells",
-                        30,
-                    );
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 235.0,
+                    },
+                    "Bunch-of-cells",
+                    30,
+                );
 
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
+                draw_text(
+                        r,
+                        config.theme.text,
                         Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 340.0,
+                            x: page.x,
+                            y: page.y + 290.0,
                         },
                         "Hi! This is a 14yo Bunch-of-cells, who is made up of a bunch of cells (all cells ",
                         20,
                     );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
+                draw_text(
+                        r,
+                        config.theme.text,
                         Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 370.0,
+                            x: page.x,
+                            y: page.y + 320.0,
                         },
                         "aren't 14yo). I like programming and playing chess, my main programming language ",
                         20,
                     );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 400.0,
-                        },
-                        "being Rust. Check out My Github for more such awesome projects. Enjoy! (This ",
-                        20,
-                    );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        if config.options.white_theme {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        },
-                        Pos {
-                            x: 50.0,
-                            y: TASKBAR_HEIGHT + 430.0,
-                        },
-                        "project took a lot of hard work)",
-                        20,
-                    );
-                }
-                _ => (),
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 350.0,
+                    },
+                    "being Rust. Check out My Github for more such awesome projects. Enjoy! (This ",
+                    20,
+                );
+                draw_text(
+                    r,
+                    config.theme.text,
+                    Pos {
+                        x: page.x,
+                        y: page.y + 380.0,
+                    },
+                    "project took a lot of hard work)",
+                    20,
+                );
             }
+            Tab::App(_) => (),
+        }
 
-            // Update glyphs before rendering
-            glyphs.factory.encoder.flush(device);
-        });
+        let widgets: Vec<_> = self
+            .widgets
+            .widgets(self.tab)
+            .into_iter()
+            .map(|(id, widget)| (widget, widget_rect(id, size)))
+            .collect();
+        self.focus.draw(r, &config.theme, &widgets);
+
+        // draw storage warning on top of everything
+        let error = config
+            .error
+            .as_ref()
+            .or(highscores.error.as_ref())
+            .or(self.profiles.error.as_ref());
+        if let Some(error) = error {
+            let rect = banner_rect(size);
+            r.rectangle(config.theme.warning, rect);
+            draw_text(
+                r,
+                config.theme.warning_text,
+                Pos {
+                    x: rect[0] + 12.0,
+                    y: rect[1] + 23.0,
+                },
+                &format!("{} (click to dismiss)", error),
+                16,
+            );
+        }
+
+        if let Some((_, dialog)) = &self.dialog {
+            let state = self.focus.state(0, dialog, Rect::window(size));
+            dialog.draw(r, Rect::window(size), &config.theme, state);
+        }
+    }
+}
+
+impl MainMenu {
+    fn draw_profiles(&self, r: &mut dyn Renderer, config: &Config) {
+        let text_color = config.theme.text;
+        let size = r.size();
+        let layout = ProfileLayout::new(size);
+        let title = Pos {
+            x: layout.title.x,
+            y: layout.title.y + 20.0,
+        };
+        draw_text(r, text_color, title, "Profiles", 24);
+
+        // draw the name being typed
+        if let Some((edit, input)) = &self.editing {
+            let label = match edit {
+                ProfileEdit::Create => "New profile:",
+                ProfileEdit::Rename => "Rename to:",
+            };
+            let edit = layout.edit;
+            draw_text(
+                r,
+                text_color,
+                Pos {
+                    x: edit.x,
+                    y: edit.y + 20.0,
+                },
+                label,
+                18,
+            );
+            let state = WidgetState {
+                focused: true,
+                ..WidgetState::default()
+            };
+            input.draw(r, layout.input, &config.theme, state);
+            draw_text(
+                r,
+                text_color,
+                Pos {
+                    x: edit.x,
+                    y: edit.y + 55.0,
+                },
+                "Enter to save, Escape to cancel",
+                14,
+            );
+        }
+    }
+
+    fn draw_controls(&self, r: &mut dyn Renderer, config: &Config) {
+        let text_color = config.theme.text;
+        let layout = ControlsLayout::new(r.size());
+        let title = Pos {
+            x: layout.title.x,
+            y: layout.title.y + 20.0,
+        };
+        draw_text(r, text_color, title, "Key bindings", 24);
+
+        // the keys are drawn with the other widgets
+        for (action, row) in Action::BINDABLE.iter().zip(&layout.rows) {
+            let label = Pos {
+                x: row.x,
+                y: row.y + 27.0,
+            };
+            draw_text(r, text_color, label, action.name(), 20);
+        }
+
+        let status = layout.status;
+        let (text, color) = match &self.bindings_status {
+            Some(status) => (status.as_str(), config.theme.lose_text),
+            None => (
+                "Click a key to change it, Backspace clears it, Escape cancels",
+                text_color,
+            ),
+        };
+        draw_text(
+            r,
+            color,
+            Pos {
+                x: status.x,
+                y: status.y + 25.0,
+            },
+            text,
+            16,
+        );
+    }
+
+    fn draw_stats(
+        &self,
+        r: &mut dyn Renderer,
+        page: Rect,
+        config: &Config,
+        highscores: &HighScores,
+    ) {
+        let text_color = config.theme.text;
+        let table = page.anchor(Anchor::TopLeft, page.w.min(850.0), page.h);
+        let (name_column, rest) = table.split_left(200.0);
+        let columns: Vec<f64> = std::iter::once(name_column)
+            .chain(rest.columns(5, 0.0))
+            .map(|column| column.x)
+            .collect();
+
+        // STATS TAB
+        // draw table header
+        let header = ["Game", "Played", "Win rate", "Streak", "Best", "Avg score"];
+        for (x, text) in columns.iter().zip(header) {
+            draw_text(r, text_color, Pos { x: *x, y: table.y }, text, 22);
+        }
+        r.line(
+            text_color,
+            0.5,
+            [table.x, table.y + 10.0, table.x + table.w, table.y + 10.0],
+        );
+
+        // draw one row per game
+        for (row, id) in STATS_GAMES.iter().enumerate() {
+            let summary = highscores.stats.summary(id);
+            let name = match self.registry.position(id) {
+                Some(index) => self.registry.entries()[index].name,
+                None => id,
+            };
+            let cells = [
+                name.to_string(),
+                summary.played.to_string(),
+                match summary.win_rate() {
+                    Some(rate) => format!("{:.0}%", rate * 100.0),
+                    None => "-".to_string(),
+                },
+                summary.current_streak.to_string(),
+                summary.best_streak.to_string(),
+                match summary.average_score {
+                    Some(score) => format!("{:.1}", score),
+                    None => "-".to_string(),
+                },
+            ];
+            for (x, text) in columns.iter().zip(cells.iter()) {
+                draw_text(
+                    r,
+                    text_color,
+                    Pos {
+                        x: *x,
+                        y: table.y + 45.0 + 32.0 * row as f64,
+                    },
+                    text,
+                    20,
+                );
+            }
+        }
+
+        // draw wordle guess distribution
+        let distribution = highscores.stats.summary("wordle").guess_distribution;
+        let most = distribution.iter().copied().max().unwrap_or(0).max(1);
+        // below the rows of the games
+        let top = table.y + 70.0 + 32.0 * STATS_GAMES.len() as f64;
+        let x = table.x;
+        draw_text(r, text_color, Pos { x, y: top }, "Wordle guesses", 22);
+        for (index, count) in distribution.iter().enumerate() {
+            let y = top + 12.0 + 23.0 * index as f64;
+            draw_text(
+                r,
+                text_color,
+                Pos { x, y: y + 17.0 },
+                &(index + 1).to_string(),
+                18,
+            );
+            let width = 30.0 + (table.w - 350.0) * *count as f64 / most as f64;
+            r.rectangle(config.theme.wordle.correct, [x + 25.0, y, width, 20.0]);
+            draw_text(
+                r,
+                config.theme.wordle.letter,
+                Pos {
+                    x: x + 25.0 + width - 24.0,
+                    y: y + 16.0,
+                },
+                &count.to_string(),
+                16,
+            );
+        }
+    }
+}
+
+// Names of the themes that can be picked in the Settings tab
+fn theme_names() -> Vec<String> {
+    let (themes, _) = theme::load_all(&theme::dir());
+    themes.into_iter().map(|theme| theme.name).collect()
+}
+
+// Where a widget of the menu is, for the window size
+fn widget_rect(id: MenuWidget, size: [f64; 2]) -> Rect {
+    match id {
+        MenuWidget::Tab(index) => {
+            let (taskbar, _) = Rect::window(size).split_top(TASKBAR_HEIGHT - 1.0);
+            let (_, mut rest) = taskbar.split_left(30.0);
+            for (_, width) in &TAB_LABELS[..index] {
+                rest = rest.split_left(*width).1;
+            }
+            rest.split_left(TAB_LABELS[index].1).0
+        }
+        MenuWidget::Game(index) => {
+            // leave room for the icons on the left
+            let (_, list) = layout::content(size).pad(15.0).split_left(30.0);
+            let list = list.anchor(Anchor::TopLeft, 224.0, list.h);
+            list.stack(index + 1, 56.0, 4.0)[index]
+        }
+        MenuWidget::Theme | MenuWidget::Colours | MenuWidget::Window | MenuWidget::ShapeCues => {
+            let (options, _) = settings_columns(size).0.split_top(168.0);
+            let options = options.anchor(Anchor::TopLeft, 250.0, options.h);
+            let index = [
+                MenuWidget::Theme,
+                MenuWidget::Colours,
+                MenuWidget::Window,
+                MenuWidget::ShapeCues,
+            ]
+            .iter()
+            .position(|option| *option == id)
+            .unwrap_or(0);
+            options.stack(4, 40.0, 2.0)[index]
+        }
+        MenuWidget::ResetHighscores
+        | MenuWidget::Export
+        | MenuWidget::ImportMerge
+        | MenuWidget::ImportReplace => {
+            let (_, actions) = settings_columns(size).0.split_top(168.0);
+            let actions = actions.anchor(Anchor::TopLeft, 240.0, actions.h);
+            let index = [
+                MenuWidget::ResetHighscores,
+                MenuWidget::Export,
+                MenuWidget::ImportMerge,
+                MenuWidget::ImportReplace,
+            ]
+            .iter()
+            .position(|action| *action == id)
+            .unwrap_or(0);
+            actions.stack(4, 56.0, 10.0)[index]
+        }
+        MenuWidget::Profiles => ProfileLayout::new(size).list,
+        MenuWidget::NewProfile | MenuWidget::RenameProfile | MenuWidget::DeleteProfile => {
+            let actions = ProfileLayout::new(size).actions.columns(3, 10.0);
+            match id {
+                MenuWidget::NewProfile => actions[0],
+                MenuWidget::RenameProfile => actions[1],
+                _ => actions[2],
+            }
+        }
+        MenuWidget::KeyBindings => {
+            let column = settings_columns(size).1;
+            let column = column.anchor(Anchor::TopLeft, 240.0, column.h);
+            column.split_bottom(56.0).0
+        }
+        MenuWidget::Binding(action, slot) => {
+            let (_, keys) = ControlsLayout::new(size).rows[action].split_left(220.0);
+            keys.columns(KEYS_PER_ACTION, 10.0)[slot]
+        }
+        MenuWidget::ResetBindings | MenuWidget::ControlsBack => {
+            let buttons = ControlsLayout::new(size).buttons.columns(2, 20.0);
+            if id == MenuWidget::ResetBindings {
+                buttons[0]
+            } else {
+                buttons[1]
+            }
+        }
+    }
+}
+
+// Shows the window in `mode` and saves it in the config
+fn switch_window_mode(window: &mut PistonWindow, mode: WindowMode, config: &mut Config) {
+    // keep the size and position from before going fullscreen
+    window::update_geometry(window, &mut config.options.window);
+    config.options.window.mode = mode;
+    window::set_mode(window, mode);
+    config.save_config();
+}
+
+// The two columns of the Settings tab, options on the left and profiles on the right
+fn settings_columns(size: [f64; 2]) -> (Rect, Rect) {
+    let columns = layout::content(size).pad(20.0).columns(2, 40.0);
+    (columns[0], columns[1])
+}
+
+// Where the parts of the profile column are drawn
+struct ProfileLayout {
+    title: Rect,
+    list: Rect,
+    actions: Rect,
+    // the name being typed
+    edit: Rect,
+    input: Rect,
+}
+
+impl ProfileLayout {
+    fn new(size: [f64; 2]) -> Self {
+        let column = settings_columns(size).1;
+        let column = column.anchor(Anchor::TopLeft, 305.0, column.h);
+        let (title, rest) = column.split_top(35.0);
+        let (list, rest) = rest.split_top(44.0 * MAX_PROFILES as f64 + 6.0);
+        let (actions, rest) = rest.split_top(40.0);
+        let (_, edit) = rest.split_top(10.0);
+        let (input, _) = edit.split_top(30.0).0.split_right(185.0);
+        ProfileLayout {
+            title,
+            list,
+            actions,
+            edit,
+            input,
+        }
+    }
+}
+
+// Where the parts of the key bindings page are drawn
+struct ControlsLayout {
+    title: Rect,
+    // one row per action, its name and then its keys
+    rows: Vec<Rect>,
+    status: Rect,
+    buttons: Rect,
+}
+
+impl ControlsLayout {
+    fn new(size: [f64; 2]) -> Self {
+        let page = layout::content(size).pad(20.0);
+        let page = page.anchor(Anchor::TopLeft, 560.0, page.h);
+        let (title, rest) = page.split_top(35.0);
+        let count = Action::BINDABLE.len();
+        let (rows, rest) = rest.split_top(44.0 * count as f64);
+        let (status, rest) = rest.split_top(40.0);
+        let (buttons, _) = rest.split_top(48.0);
+        ControlsLayout {
+            title,
+            rows: rows.stack(count, 40.0, 4.0),
+            status,
+            buttons: buttons.anchor(Anchor::TopLeft, 420.0, 48.0),
+        }
+    }
+}
+
+// Where the storage warning banner is drawn
+fn banner_rect(size: [f64; 2]) -> [f64; 4] {
+    [0.0, size[1] - BANNER_HEIGHT, size[0], BANNER_HEIGHT]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::render::RecordingRenderer;
+    use crate::menu::storage::StorageError;
+    use std::io;
+
+    #[test]
+    fn storage_warning_is_drawn() {
+        let menu = MainMenu::new();
+        let mut config = Config::new();
+        let highscores = HighScores::new();
+
+        let mut r = RecordingRenderer::new(1024.0, 576.0);
+        menu.draw(&mut r, &config, &highscores);
+        assert!(!r.texts().iter().any(|text| text.contains("dismiss")));
+
+        config.error = Some(StorageError::Io(
+            config.location.clone(),
+            io::Error::new(io::ErrorKind::PermissionDenied, "permission denied"),
+        ));
+        let mut r = RecordingRenderer::new(1024.0, 576.0);
+        menu.draw(&mut r, &config, &highscores);
+        assert_eq!(
+            r.texts().last(),
+            Some(&"Could not access unolife_config.json: permission denied (click to dismiss)")
+        );
+    }
+
+    #[test]
+    fn stats_tab_shows_summaries() {
+        use crate::menu::stats::{GameRecord, Outcome};
+        use std::time::Instant;
+
+        let mut menu = MainMenu::new();
+        menu.tab = Tab::Stats;
+        let config = Config::new();
+        let mut highscores = HighScores::new();
+        for outcome in [Outcome::Won, Outcome::Won, Outcome::Lost] {
+            highscores.stats.record(GameRecord {
+                score: Some(10),
+                ..GameRecord::new("snake", outcome, Instant::now())
+            });
+        }
+
+        let mut r = RecordingRenderer::new(1024.0, 576.0);
+        menu.draw(&mut r, &config, &highscores);
+        let texts = r.texts();
+        let snake = texts.iter().position(|text| *text == "Snake").unwrap();
+        assert_eq!(texts[snake + 1..snake + 6], ["3", "67%", "0", "2", "10.0"]);
+    }
+
+    #[test]
+    fn keys_jump_to_games() {
+        use crate::components::widgets::tests::key;
+
+        let mut menu = MainMenu::new();
+        let bindings = KeyBindings::default();
+        assert_eq!(menu.shortcut(&key(Key::D3), &bindings), Some(Tab::App(2)));
+        assert_eq!(
+            menu.shortcut(&key(Key::NumPad1), &bindings),
+            Some(Tab::App(0))
+        );
+        assert_eq!(menu.shortcut(&key(Key::D9), &bindings), None);
+        assert_eq!(
+            menu.shortcut(&key(Key::Escape), &bindings),
+            Some(Tab::Games)
+        );
+
+        menu.tab = Tab::App(2);
+        assert_eq!(menu.shortcut(&key(Key::D3), &bindings), None);
+        assert_eq!(
+            menu.shortcut(&key(Key::Escape), &bindings),
+            Some(Tab::Games)
+        );
+
+        menu.tab = Tab::Controls;
+        assert_eq!(
+            menu.shortcut(&key(Key::Escape), &bindings),
+            Some(Tab::Settings)
+        );
+
+        menu.tab = Tab::Games;
+        assert_eq!(menu.shortcut(&key(Key::Escape), &bindings), None);
+    }
+
+    #[test]
+    fn pressed_key_is_bound() {
+        use crate::components::widgets::tests::key;
+        use crate::menu::storage::tests::TempDir;
+
+        let dir = TempDir::new("bindings");
+        let mut menu = MainMenu::new();
+        let mut config = Config::new();
+        config.location = dir.join("config.json");
+        menu.tab = Tab::Controls;
+
+        // the second key of "Move left"
+        menu.capturing = Some((2, 1));
+        assert!(menu.capture_key(&key(Key::H), &mut config));
+        assert_eq!(config.options.bindings.left, [Key::Left, Key::H]);
+        assert_eq!(menu.capturing, None);
+
+        menu.capturing = Some((2, 0));
+        assert!(menu.capture_key(&key(Key::R), &mut config));
+        assert_eq!(config.options.bindings.left, [Key::Left, Key::H]);
+        assert_eq!(
+            menu.bindings_status.as_deref(),
+            Some("R is already used for Reset")
+        );
+
+        // the digits open the games
+        menu.capturing = Some((2, 0));
+        assert!(menu.capture_key(&key(Key::D2), &mut config));
+        assert_eq!(config.options.bindings.left, [Key::Left, Key::H]);
+        assert_eq!(
+            menu.bindings_status.as_deref(),
+            Some("2 opens a game from the menu")
+        );
+    }
+
+    #[test]
+    fn typing_creates_profile() {
+        use crate::components::widgets::tests::{key, text};
+        use crate::menu::storage::tests::TempDir;
+
+        let dir = TempDir::new("profiles");
+        let mut menu = MainMenu::new();
+        menu.tab = Tab::Settings;
+        menu.editing = Some((ProfileEdit::Create, TextInput::new(MAX_NAME_LEN)));
+        menu.profiles.location = dir.join("profiles.json");
+
+        assert!(menu.edit_profile(&text("Samm")));
+        assert!(menu.edit_profile(&key(Key::Backspace)));
+        assert!(menu.edit_profile(&key(Key::Return)));
+
+        assert!(menu.editing.is_none());
+        assert_eq!(menu.profiles.list()[1].name, "Sam");
+
+        let mut r = RecordingRenderer::new(1024.0, 576.0);
+        menu.draw(&mut r, &Config::new(), &HighScores::new());
+        assert!(r.texts().contains(&"Sam"));
     }
 }
@@ -93,6 +93,35 @@ impl Game {
         }
     }
 
+    /// Slides every tile between `tile` and the blank towards the blank,
+    /// when they are in the same row or column. Counts as one move.
+    pub fn slide(&mut self, tile: usize) {
+        let blank_index = self.board.iter().position(|x| *x == 0).unwrap();
+        let step = if tile / 4 == blank_index / 4 {
+            1
+        } else if tile % 4 == blank_index % 4 {
+            4
+        } else {
+            return;
+        };
+        if tile == blank_index {
+            return;
+        }
+
+        // move the blank to the tile, pushing the tiles on the way back
+        let mut blank = blank_index;
+        while blank != tile {
+            let next = if tile > blank {
+                blank + step
+            } else {
+                blank - step
+            };
+            self.board.swap(blank, next);
+            blank = next;
+        }
+        self.moves += 1;
+    }
+
     pub fn is_over(&self) -> bool {
         self.board == [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0]
     }
@@ -1,3 +1,68 @@
 pub mod game;
 pub use game::*;
 pub mod ui;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+    use crate::components::render::{recording::assert_snapshot, RecordingRenderer};
+    use crate::components::widgets::tests::key;
+    use crate::menu::{config::Config, highscores::HighScores, storage::tests::TempDir};
+    use piston_window::Key;
+
+    #[test]
+    fn app_snapshot() {
+        let mut app = ui::Puzzle15App::new();
+        app.game.board = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 0, 15];
+
+        let mut r = RecordingRenderer::new(DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64);
+        app.draw(&mut r, &Config::new(), &HighScores::new());
+        assert_snapshot("puzzle15", &r);
+        assert!(r.texts().contains(&"Moves: 0"));
+    }
+
+    #[test]
+    fn slide_moves_the_whole_line() {
+        let mut game = Game::new();
+        game.board = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0];
+        game.moves = 0;
+
+        game.slide(12);
+        assert_eq!(game.board[12..], [0, 13, 14, 15]);
+        game.slide(0);
+        assert_eq!(
+            game.board,
+            [0, 2, 3, 4, 1, 6, 7, 8, 5, 10, 11, 12, 9, 13, 14, 15]
+        );
+        assert_eq!(game.moves, 2);
+
+        // tiles off the blank's row and column stay put
+        game.slide(5);
+        assert_eq!(game.moves, 2);
+    }
+
+    #[test]
+    fn solved_board_ignores_moves() {
+        const SIZE: [f64; 2] = [DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64];
+        let mut app = ui::Puzzle15App::new();
+        let mut config = Config::new();
+        let dir = TempDir::new("puzzle15");
+        let mut highscores = HighScores::new();
+        highscores.location = dir.join("highscores.json");
+        app.game.board = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 0, 15];
+        app.game.moves = 0;
+
+        app.handle_event(&key(Key::Left), SIZE, &mut config, &mut highscores);
+        assert!(app.game.is_over());
+        assert!(app.takes_keyboard());
+
+        // neither the open prompt nor the solved board take moves
+        app.handle_event(&key(Key::Right), SIZE, &mut config, &mut highscores);
+        app.handle_event(&key(Key::Return), SIZE, &mut config, &mut highscores);
+        app.handle_event(&key(Key::Right), SIZE, &mut config, &mut highscores);
+        assert!(app.game.is_over());
+        assert_eq!(app.game.moves, 1);
+        assert_eq!(highscores.scores.puzzle15.entries[0].score, 1);
+    }
+}
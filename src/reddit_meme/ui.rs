@@ -1,6 +1,7 @@
-use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
-use crate::components::button::{draw_text, Pos};
-use crate::components::color::Color;
+use crate::components::application::MiniApp;
+use crate::components::button::draw_text;
+use crate::components::layout::{self, Anchor, Rect};
+use crate::components::render::Renderer;
 use crate::menu::ui::TASKBAR_HEIGHT;
 use crate::menu::{config::Config, highscores::HighScores};
 use crate::{Event, ASSETS};
@@ -14,14 +15,20 @@ use std::path::Path;
 
 use http_req::{request::Request, uri::Uri};
 
-pub static mut UPDATE: bool = true;
-
+#[allow(dead_code)]
 pub struct MemeApp {
     pub ratelimit_reset: Option<Duration>,
     pub ratelimit_remaining: Option<u32>,
     texture: Option<G2dTexture>,
 }
 
+// Outcome of a single attempt at fetching a meme
+enum Fetch {
+    Loaded(G2dTexture, String),
+    Retry,
+    Failed,
+}
+
 impl MemeApp {
     pub fn new() -> Self {
         MemeApp {
@@ -30,301 +37,204 @@ impl MemeApp {
             texture: None,
         }
     }
-}
 
-impl MiniApp for MemeApp {
-    fn render(
-        &mut self,
-        window: &mut PistonWindow,
-        event: &Event,
-        glyphs: &mut Glyphs,
-        config: &mut Config,
-        highscores: &mut HighScores,
-    ) {
-        if unsafe { UPDATE } {
-            let mut writer = Vec::new(); //container for body of a response
+    fn fetch(window: &mut PistonWindow) -> Fetch {
+        let mut writer = Vec::new(); //container for body of a response
 
-            // choose randomly between 2 subreddits, but give other subreddit a higher chance
-            let subreddit = match rand::thread_rng().gen_range(0..3) {
-                0 => "memes",
-                _ => "ProgrammerHumor",
-            };
+        // choose randomly between 2 subreddits, but give other subreddit a higher chance
+        let subreddit = match rand::thread_rng().gen_range(0..3) {
+            0 => "memes",
+            _ => "ProgrammerHumor",
+        };
 
-            let res = Request::new(
-                &Uri::try_from(
-                    format!("https://api.reddit.com/r/{}/random.json", subreddit).as_str(),
-                )
+        let res = Request::new(
+            &Uri::try_from(format!("https://api.reddit.com/r/{}/random.json", subreddit).as_str())
                 .unwrap(),
-            )
-            .header(
-                "User-Agent",
-                "windows:com.fireplank.unolife:v1.0.0 (by /u/fireplank)",
-            )
-            .read_timeout(Some(Duration::from_secs(5)))
-            .write_timeout(Some(Duration::from_secs(5)))
-            .timeout(Some(Duration::from_secs(5)))
-            .send(&mut writer);
+        )
+        .header(
+            "User-Agent",
+            "windows:com.fireplank.unolife:v1.0.0 (by /u/fireplank)",
+        )
+        .read_timeout(Some(Duration::from_secs(5)))
+        .write_timeout(Some(Duration::from_secs(5)))
+        .timeout(Some(Duration::from_secs(5)))
+        .send(&mut writer);
+
+        // check if request was successful
+        if res.is_err() {
+            println!("Error: Could not connect to reddit");
+            return Fetch::Failed;
+        }
 
-            // check if request was successful
-            if res.is_err() {
-                println!("Error: Could not connect to reddit");
+        let res = res.unwrap();
 
-                unsafe {
-                    UPDATE = false;
-                }
-                self.texture = None;
-                return;
+        // check if redirect
+        if res.status_code().is_redirect() {
+            // get location header from response
+            let location = res.headers().get("location").unwrap();
+            Request::new(&Uri::try_from(location.as_str()).unwrap())
+                .header(
+                    "User-Agent",
+                    "windows:com.fireplank.unolife:v1.0.0 (by /u/fireplank)",
+                )
+                .read_timeout(Some(Duration::from_secs(10)))
+                .write_timeout(Some(Duration::from_secs(10)))
+                .timeout(Some(Duration::from_secs(5)))
+                .send(&mut writer)
+                .unwrap();
+
+            // convert body to json
+            let json: serde_json::Value = serde_json::from_slice(&writer).unwrap();
+            // get data from json
+            let meme_url = json[0]["data"]["children"][0]["data"]["url"]
+                .as_str()
+                .unwrap();
+            let meme_title = json[0]["data"]["children"][0]["data"]["title"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            let meme_author = json[0]["data"]["children"][0]["data"]["author"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            let is_nsfw = json[0]["data"]["children"][0]["data"]["over_18"]
+                .as_bool()
+                .unwrap();
+
+            // check if meme is nsfw
+            if is_nsfw {
+                println!("Meme is NSFW, Skipping...");
+                return Fetch::Retry;
             }
 
-            let res = res.unwrap();
-
-            // check if redirect
-            if res.status_code().is_redirect() {
-                // get location header from response
-                let location = res.headers().get("location").unwrap();
-                Request::new(&Uri::try_from(location.as_str()).unwrap())
-                    .header(
-                        "User-Agent",
-                        "windows:com.fireplank.unolife:v1.0.0 (by /u/fireplank)",
-                    )
-                    .read_timeout(Some(Duration::from_secs(10)))
-                    .write_timeout(Some(Duration::from_secs(10)))
-                    .timeout(Some(Duration::from_secs(5)))
-                    .send(&mut writer)
-                    .unwrap();
-
-                // convert body to json
-                let json: serde_json::Value = serde_json::from_slice(&writer).unwrap();
-                // get data from json
-                let meme_url = json[0]["data"]["children"][0]["data"]["url"]
-                    .as_str()
-                    .unwrap();
-                let meme_title = json[0]["data"]["children"][0]["data"]["title"]
-                    .as_str()
-                    .unwrap()
-                    .to_string();
-                let meme_author = json[0]["data"]["children"][0]["data"]["author"]
-                    .as_str()
-                    .unwrap()
-                    .to_string();
-                let is_nsfw = json[0]["data"]["children"][0]["data"]["over_18"]
-                    .as_bool()
-                    .unwrap();
-
-                // check if meme is nsfw
-                if is_nsfw {
-                    println!("Meme is NSFW, Skipping...");
-                    self.render(window, event, glyphs, config, highscores);
-                    return;
-                }
-
-                // download meme
-                let mut writer = Vec::new();
-                Request::new(&Uri::try_from(meme_url).unwrap())
-                    .header(
-                        "User-Agent",
-                        "windows:com.fireplank.unolife:v1.0.0 (by /u/fireplank)",
-                    )
-                    .read_timeout(Some(Duration::from_secs(10)))
-                    .write_timeout(Some(Duration::from_secs(10)))
-                    .timeout(Some(Duration::from_secs(5)))
-                    .send(&mut writer)
-                    .unwrap();
-
-                // save image
-                let file_extension = Path::new(meme_url)
-                    .extension()
-                    .unwrap_or_else(|| OsStr::new("jpg"))
-                    .to_str()
-                    .unwrap_or("jpg");
-                if file_extension != "jpg" && file_extension != "png" && file_extension != "jpeg" {
-                    // file format not supported, so skip
-                    self.render(window, event, glyphs, config, highscores);
-                    return;
-                }
-                let mut file =
-                    std::fs::File::create(ASSETS.join(format!("meme.{}", file_extension))).unwrap();
-                file.write_all(&writer).unwrap();
-                file.flush().unwrap();
-
-                let mut texture_context = window.create_texture_context();
-                // make texture for image from response
-                let texture = Texture::from_path(
-                    &mut texture_context,
-                    ASSETS.join(format!("meme.{}", file_extension)),
-                    Flip::None,
-                    &TextureSettings::new(),
-                );
-
-                // delete meme file
-                std::fs::remove_file(ASSETS.join(format!("meme.{}", file_extension)))
-                    .unwrap_or_else(|err| {
-                        println!("Error when trying to delete meme: {}", err);
-                    });
-
-                // if texture is not loaded, skip
-                if texture.is_err() {
+            // download meme
+            let mut writer = Vec::new();
+            Request::new(&Uri::try_from(meme_url).unwrap())
+                .header(
+                    "User-Agent",
+                    "windows:com.fireplank.unolife:v1.0.0 (by /u/fireplank)",
+                )
+                .read_timeout(Some(Duration::from_secs(10)))
+                .write_timeout(Some(Duration::from_secs(10)))
+                .timeout(Some(Duration::from_secs(5)))
+                .send(&mut writer)
+                .unwrap();
+
+            // save image
+            let file_extension = Path::new(meme_url)
+                .extension()
+                .unwrap_or_else(|| OsStr::new("jpg"))
+                .to_str()
+                .unwrap_or("jpg");
+            if file_extension != "jpg" && file_extension != "png" && file_extension != "jpeg" {
+                // file format not supported, so skip
+                return Fetch::Retry;
+            }
+            let mut file =
+                std::fs::File::create(ASSETS.join(format!("meme.{}", file_extension))).unwrap();
+            file.write_all(&writer).unwrap();
+            file.flush().unwrap();
+
+            let mut texture_context = window.create_texture_context();
+            // make texture for image from response
+            let texture = Texture::from_path(
+                &mut texture_context,
+                ASSETS.join(format!("meme.{}", file_extension)),
+                Flip::None,
+                &TextureSettings::new(),
+            );
+
+            // delete meme file
+            std::fs::remove_file(ASSETS.join(format!("meme.{}", file_extension))).unwrap_or_else(
+                |err| {
+                    println!("Error when trying to delete meme: {}", err);
+                },
+            );
+
+            // if texture is not loaded, skip
+            match texture {
+                Ok(texture) => Fetch::Loaded(
+                    texture,
+                    format!("UnoLife - {} (by /u/{})", meme_title, meme_author),
+                ),
+                Err(_) => {
                     println!("Error: Could not load meme");
-                    self.render(window, event, glyphs, config, highscores);
-                    return;
+                    Fetch::Retry
                 }
+            }
+        } else {
+            // something went wrong, most likely rate limited
+            println!(
+                "{} / {} / {}",
+                res.status_code(),
+                res.reason(),
+                res.headers()
+            );
+
+            Fetch::Failed
+        }
+    }
+}
 
-                let texture = texture.unwrap();
-
-                unsafe {
-                    UPDATE = false;
-                }
-
-                // set window title to meme title
-                window.set_title(format!("UnoLife - {} (by /u/{})", meme_title, meme_author));
-
-                let mut width = texture.get_width();
-                let mut height = texture.get_height();
-
-                match (
-                    height.cmp(&(DEFAULT_HEIGHT + TASKBAR_HEIGHT as u32)),
-                    width.cmp(&DEFAULT_WIDTH),
-                ) {
-                    (std::cmp::Ordering::Greater, std::cmp::Ordering::Greater) => {
-                        let h_diff = height - DEFAULT_HEIGHT - TASKBAR_HEIGHT as u32;
-                        let w_diff = width - DEFAULT_WIDTH;
-                        let diff = h_diff.max(w_diff);
-                        height -= diff;
-                        width -= diff;
-                    }
-                    (std::cmp::Ordering::Less, std::cmp::Ordering::Less) => {
-                        let h_diff = DEFAULT_HEIGHT + TASKBAR_HEIGHT as u32 - height;
-                        let w_diff = DEFAULT_WIDTH - width;
-                        let diff = h_diff.min(w_diff);
-                        height += diff;
-                        width += diff;
-                    }
-                    (std::cmp::Ordering::Greater, _) => {
-                        let diff = height - DEFAULT_HEIGHT - TASKBAR_HEIGHT as u32;
-                        height -= diff;
-                        width -= diff;
-                    }
-                    (_, std::cmp::Ordering::Greater) => {
-                        let diff = width - DEFAULT_HEIGHT;
-                        height -= diff;
-                        width -= diff;
-                    }
-                    (_, _) => (),
+impl MiniApp for MemeApp {
+    fn on_enter(
+        &mut self,
+        window: &mut PistonWindow,
+        _config: &mut Config,
+        _highscores: &mut HighScores,
+    ) {
+        // fetch a new meme every time the tab is opened
+        self.texture = loop {
+            match MemeApp::fetch(window) {
+                Fetch::Loaded(texture, title) => {
+                    // set window title to meme title
+                    window.set_title(title);
+                    break Some(texture);
                 }
+                Fetch::Retry => continue,
+                Fetch::Failed => break None,
+            }
+        };
+    }
 
-                let image = Image::new().rect([0.0, TASKBAR_HEIGHT, width as f64, height as f64]);
+    fn handle_event(
+        &mut self,
+        _event: &Event,
+        _size: [f64; 2],
+        _config: &mut Config,
+        _highscores: &mut HighScores,
+    ) {
+    }
 
-                window.draw_2d(event, |c, g, _| {
-                    clear(
-                        if config.options.white_theme {
-                            Color::WHITE
-                        } else {
-                            Color::DARK_THEME_BG
-                        },
-                        g,
-                    );
-                    // draw image with texture
-                    image.draw(
-                        self.texture.as_ref().unwrap(),
-                        &DrawState::new_alpha(),
-                        c.transform,
-                        g,
-                    );
-                });
-                self.texture = Some(texture);
-            } else {
-                // something went wrong, most likely rate limited
-                println!(
-                    "{} / {} / {}",
-                    res.status_code(),
-                    res.reason(),
-                    res.headers()
-                );
+    fn draw(&self, r: &mut dyn Renderer, config: &Config, _highscores: &HighScores) {
+        let size = r.size();
+        r.clear(config.theme.game_background);
 
-                unsafe {
-                    UPDATE = false;
-                }
-                self.texture = None;
-            }
-        } else {
-            if self.texture == None {
+        let texture = match self.texture.as_ref() {
+            Some(texture) => texture,
+            None => {
                 // error occured, so show error message
-                window.draw_2d(event, |c, g, _| {
-                    clear(
-                        if config.options.white_theme {
-                            Color::WHITE
-                        } else {
-                            Color::DARK_THEME_BG
-                        },
-                        g,
-                    );
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        Color::LOSE_TEXT,
-                        Pos { x: 50.0, y: 300.0 },
-                        "An error occured while trying to load the meme. Please try again later.",
-                        24,
-                    );
-                });
+                draw_text(
+                    r,
+                    config.theme.lose_text,
+                    layout::content(size)
+                        .pad(40.0)
+                        .anchor(Anchor::Left, 0.0, 0.0)
+                        .pos(),
+                    "An error occured while trying to load the meme. Please try again later.",
+                    24,
+                );
                 return;
             }
+        };
 
-            let mut width = self.texture.as_ref().unwrap().get_width();
+        // scale the image to fit below the taskbar, keeping its aspect ratio
+        let area = Rect::window(size).split_top(TASKBAR_HEIGHT).1;
+        let (width, height) = (texture.get_width() as f64, texture.get_height() as f64);
+        let scale = (area.w / width).min(area.h / height);
+        let rect = area.anchor(Anchor::Center, width * scale, height * scale);
 
-            let mut height = self.texture.as_ref().unwrap().get_height();
-
-            match (
-                height.cmp(&(DEFAULT_HEIGHT + TASKBAR_HEIGHT as u32)),
-                width.cmp(&DEFAULT_WIDTH),
-            ) {
-                (std::cmp::Ordering::Greater, std::cmp::Ordering::Greater) => {
-                    let h_diff = height - DEFAULT_HEIGHT - TASKBAR_HEIGHT as u32;
-                    let w_diff = width - DEFAULT_WIDTH;
-                    let diff = h_diff.max(w_diff);
-                    height -= diff;
-                    width -= diff;
-                }
-                (std::cmp::Ordering::Less, std::cmp::Ordering::Less) => {
-                    let h_diff = DEFAULT_HEIGHT + TASKBAR_HEIGHT as u32 - height;
-                    let w_diff = DEFAULT_WIDTH - width;
-                    let diff = h_diff.min(w_diff);
-                    height += diff;
-                    width += diff;
-                }
-                (std::cmp::Ordering::Greater, _) => {
-                    let diff = height - DEFAULT_HEIGHT - TASKBAR_HEIGHT as u32;
-                    height -= diff;
-                    width -= diff;
-                }
-                (_, std::cmp::Ordering::Greater) => {
-                    let diff = width - DEFAULT_HEIGHT;
-                    height -= diff;
-                    width -= diff;
-                }
-                (_, _) => (),
-            }
-
-            let image = Image::new().rect([0.0, TASKBAR_HEIGHT, width as f64, height as f64]);
-
-            window.draw_2d(event, |c, g, _| {
-                clear(
-                    if config.options.white_theme {
-                        Color::WHITE
-                    } else {
-                        Color::DARK_THEME_BG
-                    },
-                    g,
-                );
-                // draw image with texture
-                image.draw(
-                    self.texture.as_ref().unwrap(),
-                    &DrawState::new_alpha(),
-                    c.transform,
-                    g,
-                );
-            });
-        }
+        // draw image with texture
+        r.image(texture, rect.to_array());
     }
 }
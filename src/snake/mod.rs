@@ -1,3 +1,73 @@
 pub mod game;
 pub use game::*;
 pub mod ui;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::application::MiniApp;
+    use crate::components::application::{DEFAULT_HEIGHT, DEFAULT_WIDTH};
+    use crate::components::render::{recording::assert_snapshot, RecordingRenderer};
+    use crate::components::widgets::tests::key;
+    use crate::menu::{config::Config, highscores::HighScores};
+    use piston_window::*;
+
+    const SIZE: [f64; 2] = [DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64];
+
+    #[test]
+    fn snake_moves_on_update() {
+        let mut app = ui::SnakeApp::new();
+        let mut config = Config::new();
+        let mut highscores = HighScores::new();
+
+        app.handle_event(&key(Key::Right), SIZE, &mut config, &mut highscores);
+        // nothing happens until time passes
+        assert_eq!(app.game.snake.body[0].x, 1);
+
+        app.update(1.0, &mut config, &mut highscores);
+        assert_eq!(app.game.snake.body[0].x, 2);
+        assert_eq!(app.game.snake.body[0].y, 1);
+    }
+
+    #[test]
+    fn paused_snake_stays_put() {
+        let mut app = ui::SnakeApp::new();
+        let mut config = Config::new();
+        let mut highscores = HighScores::new();
+
+        // nothing to pause before the snake starts moving
+        app.pause();
+        assert!(!app.takes_keyboard());
+
+        app.handle_event(&key(Key::Right), SIZE, &mut config, &mut highscores);
+        assert!(app.takes_back());
+        app.handle_event(&key(Key::P), SIZE, &mut config, &mut highscores);
+        app.update(1.0, &mut config, &mut highscores);
+        assert_eq!(app.game.snake.body[0].x, 1);
+        assert!(app.takes_keyboard());
+
+        // Escape resumes, Quit in the menu leaves the game paused
+        app.handle_event(&key(Key::Escape), SIZE, &mut config, &mut highscores);
+        app.update(1.0, &mut config, &mut highscores);
+        assert_eq!(app.game.snake.body[0].x, 2);
+
+        app.pause();
+        app.handle_event(&key(Key::Left), SIZE, &mut config, &mut highscores);
+        app.handle_event(&key(Key::Left), SIZE, &mut config, &mut highscores);
+        app.handle_event(&key(Key::Return), SIZE, &mut config, &mut highscores);
+        assert!(app.quit_requested());
+        assert!(!app.quit_requested());
+        app.update(1.0, &mut config, &mut highscores);
+        assert_eq!(app.game.snake.body[0].x, 2);
+    }
+
+    #[test]
+    fn snake_snapshot() {
+        let mut app = ui::SnakeApp::new();
+        app.game.food = FoodCell::new(5, 5);
+
+        let mut r = RecordingRenderer::new(DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64);
+        app.draw(&mut r, &Config::new(), &HighScores::new());
+        assert_snapshot("snake", &r);
+    }
+}
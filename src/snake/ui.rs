@@ -1,25 +1,46 @@
-use std::time::Instant;
-
 use super::{Direction, Game, GameState};
-use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use crate::components::application::MiniApp;
 use crate::components::{
-    button::{draw_text, Pos, UIButton},
-    color::Color,
+    button::{draw_text, ButtonStyle, Pos, UIButton},
+    gesture::{Gesture, Swipe},
+    input::{self, Action},
+    layout::GameLayout,
+    leaderboard::{draw_leaderboard, NamePrompt, PromptEvent},
+    pause::{PauseEvent, PauseMenu},
+    render::Renderer,
+    widgets::{left_click, Focus, Response, Slider},
+};
+use crate::menu::{
+    config::Config,
+    highscores::HighScores,
+    leaderboard::{Entry, Order},
+    stats::{GameRecord, Outcome},
 };
-use crate::menu::{config::Config, highscores::HighScores, ui::TOP_PAD};
-use crate::{rgb, Event};
-use piston_window::*;
+use crate::Event;
+use std::time::Instant;
 
+// Default grid size, the slider picks sizes between 10 and 40
 const GRID_SIZE: u32 = 30;
 const FPS: u64 = 15;
 
 pub struct SnakeApp {
-    game: Game,
+    pub game: Game,
     dir: Option<Direction>,
-    size: f64,
-    now: Option<Instant>,
+    // time since the last step, None until the first key press
+    elapsed: Option<f64>,
     first_result: bool,
-    hover_pos: [f64; 2],
+    focus: Focus,
+    swipe: Swipe,
+    reset: UIButton,
+    grid_size: Slider,
+    started: Instant,
+    // asks for a name when a run makes the leaderboard
+    naming: Option<NamePrompt>,
+    // place of the last leaderboard entry, highlighted in the table
+    placed: Option<usize>,
+    pause: Option<PauseMenu>,
+    // Quit was picked in the pause menu
+    quit: bool,
 }
 
 impl SnakeApp {
@@ -27,223 +48,298 @@ impl SnakeApp {
         SnakeApp {
             game: Game::new(GRID_SIZE, GRID_SIZE),
             dir: None,
-            size: (DEFAULT_HEIGHT as f64 - TOP_PAD - GRID_SIZE as f64 / 2.0) / GRID_SIZE as f64,
-            now: None,
+            elapsed: None,
             first_result: true,
-            hover_pos: [0.0; 2],
+            focus: Focus::new(),
+            swipe: Swipe::new(),
+            reset: UIButton::new("     Reset", ButtonStyle::Danger, 24),
+            grid_size: Slider::new("Grid size", GRID_SIZE, 10, 40, 5),
+            started: Instant::now(),
+            naming: None,
+            placed: None,
+            pause: None,
+            quit: false,
         }
     }
+
+    // Starts a new game, waiting for the first key press
+    fn restart(&mut self) {
+        self.first_result = true;
+        self.naming = None;
+        self.placed = None;
+        self.game.reset();
+        self.dir = None;
+        self.elapsed = None;
+        self.pause = None;
+    }
+
+    // The snake moves on its own once the first key is pressed
+    fn running(&self) -> bool {
+        self.elapsed.is_some() && self.game.state == GameState::Playing
+    }
 }
 
 impl MiniApp for SnakeApp {
-    fn render(
+    fn handle_event(
         &mut self,
-        window: &mut PistonWindow,
         event: &Event,
-        glyphs: &mut Glyphs,
+        size: [f64; 2],
         config: &mut Config,
         highscores: &mut HighScores,
     ) {
-        if let Some([cx, cy]) = event.mouse_cursor_args() {
-            self.hover_pos = [cx, cy];
+        // the name prompt takes all keyboard input while it is open
+        if let Some(prompt) = self.naming.as_mut() {
+            match prompt.handle_event(event) {
+                PromptEvent::Submit(name) => {
+                    let entry = Entry::new(
+                        &name,
+                        self.game.score,
+                        &format!("{}x{}", self.game.width, self.game.height),
+                    );
+                    self.placed = highscores.scores.snake.insert(entry, Order::Highest);
+                    highscores.save_scores();
+                    self.naming = None;
+                    return;
+                }
+                PromptEvent::Cancel => {
+                    self.naming = None;
+                    return;
+                }
+                PromptEvent::Consumed => return,
+                PromptEvent::Ignored => (),
+            }
         }
 
-        self.dir = if let Some(Button::Keyboard(press)) = event.press_args() {
-            match press {
-                Key::Up => Some(Direction::Up),
-                Key::Down => Some(Direction::Down),
-                Key::Left => Some(Direction::Left),
-                Key::Right => Some(Direction::Right),
-                _ => None,
+        // the pause menu takes every event while it is open
+        if let Some(pause) = self.pause.as_mut() {
+            match pause.handle_event(event, size, &config.options.bindings) {
+                PauseEvent::Resume => {
+                    // the paused time doesn't count for the stats
+                    self.started += pause.duration();
+                    self.pause = None;
+                }
+                PauseEvent::Restart => self.restart(),
+                PauseEvent::Quit => self.quit = true,
+                PauseEvent::Consumed => (),
             }
-        } else {
-            self.dir
-        };
-
-        if self.dir.is_some() && self.now.is_none() {
-            self.now = Some(Instant::now());
+            return;
         }
 
-        let left_click = event.press_args() == Some(Button::Mouse(MouseButton::Left));
-
-        // init buttons
-        let mut reset_button = UIButton::new(
-            "     Reset",
-            Color::RESET,
-            Color::WHITE,
-            24,
-            Pos { x: 808.0, y: 145.0 },
-            160.0,
-            48.0,
+        // handle button events
+        let layout = GameLayout::new(size);
+        let swipe = self
+            .swipe
+            .handle_event(event, layout.board)
+            .and_then(Gesture::swipe);
+        let controls = layout.buttons(2);
+        let response = self.focus.dispatch(
+            event,
+            &mut [
+                (&mut self.reset, controls[0]),
+                (&mut self.grid_size, controls[1]),
+            ],
         );
+        // a clicked slider doesn't keep the keyboard, the arrow keys steer the snake
+        if left_click(event) {
+            self.focus.set_focused(None);
+        }
+        match response {
+            Some((0, Response::Clicked)) => self.restart(),
+            Some((1, Response::Changed)) => {
+                let size = self.grid_size.value;
+                self.game = Game::new(size, size);
+                self.restart();
+            }
+            Some(_) => (),
+            None => {
+                self.dir = match config.options.bindings.action(event).or(swipe) {
+                    Some(Action::Up) => Some(Direction::Up),
+                    Some(Action::Down) => Some(Direction::Down),
+                    Some(Action::Left) => Some(Direction::Left),
+                    Some(Action::Right) => Some(Direction::Right),
+                    Some(Action::Reset) => return self.restart(),
+                    Some(Action::Pause | Action::Back) => return self.pause(),
+                    _ if input::is_press(event) => None,
+                    _ => self.dir,
+                };
 
-        // handle button events
-        if reset_button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-            if left_click {
-                // update highscore
-                highscores.scores.snake = std::cmp::max(highscores.scores.snake, self.game.score);
-                highscores.save_scores();
-                self.first_result = true;
-
-                self.game.reset();
-                self.dir = None;
-                self.now = None;
-            } else {
-                reset_button.width += 6.0;
-                reset_button.pos.x -= 3.0;
-                reset_button.height += 6.0;
-                reset_button.pos.y -= 3.0;
-                reset_button.size += 1;
+                if self.dir.is_some() && self.elapsed.is_none() {
+                    self.elapsed = Some(0.0);
+                    self.started = Instant::now();
+                }
             }
         }
+    }
 
-        window.draw_2d(event, |c, g, device| {
-            clear(
-                if config.options.white_theme {
-                    Color::WHITE
-                } else {
-                    Color::DARK_THEME_BG
-                },
-                g,
-            );
+    fn takes_keyboard(&self) -> bool {
+        self.naming.is_some() || self.pause.is_some()
+    }
 
-            let ctx = c.trans((DEFAULT_WIDTH as f64 - (DEFAULT_HEIGHT as f64 - TOP_PAD)) / 2.0, TOP_PAD - self.size / 2.0);
+    fn takes_back(&self) -> bool {
+        self.running()
+    }
 
-            draw_text(
-                &c,
-                g,
-                glyphs,
-                if config.options.white_theme {
-                    Color::BLACK
-                } else {
-                    Color::WHITE
-                },
-                Pos { x: 10.0, y: 400.0 },
-                &format!("Score: {}", self.game.score),
-                28,
-            );
-            draw_text(
-                &c,
-                g,
-                glyphs,
-                if config.options.white_theme {
-                    Color::BLACK
-                } else {
-                    Color::WHITE
-                },
-                Pos { x: 10.0, y: 440.0 },
-                &format!("Highscore: {}", highscores.scores.snake),
-                28,
-            );
+    fn pause(&mut self) {
+        if self.running() && self.pause.is_none() {
+            self.pause = Some(PauseMenu::new());
+        }
+    }
+
+    fn quit_requested(&mut self) -> bool {
+        std::mem::take(&mut self.quit)
+    }
 
-            match self.game.state {
-                GameState::Playing => {
-                    if matches!(self.now, Some(now) if now.elapsed().as_millis() >= (1000 / FPS) as u128) {
+    fn update(&mut self, dt: f64, _config: &mut Config, highscores: &mut HighScores) {
+        match self.game.state {
+            // nothing moves behind the pause menu
+            GameState::Playing if self.pause.is_some() => (),
+            GameState::Playing => {
+                if let Some(elapsed) = self.elapsed.as_mut() {
+                    *elapsed += dt;
+                    if *elapsed >= 1.0 / FPS as f64 {
+                        *elapsed = 0.0;
                         self.game.step(self.dir);
-                        self.now = Some(Instant::now());
                     }
                 }
-                GameState::Lost => {
-                    // update highscore
-                    if self.first_result {
-                        highscores.scores.snake = std::cmp::max(
-                            highscores.scores.snake,
-                            self.game.score,
-                        );
-                        highscores.save_scores();
-                        self.first_result = false;
+            }
+            GameState::Lost | GameState::Won => {
+                // update highscore
+                if self.first_result {
+                    if self.game.score > 0
+                        && highscores
+                            .scores
+                            .snake
+                            .place(self.game.score, Order::Highest)
+                            .is_some()
+                    {
+                        let name = highscores.scores.snake.last_name().unwrap_or("Player");
+                        self.naming = Some(NamePrompt::new(name));
                     }
+                    let outcome = if self.game.state == GameState::Won {
+                        Outcome::Won
+                    } else {
+                        Outcome::Lost
+                    };
+                    highscores.stats.record(GameRecord {
+                        score: Some(self.game.score),
+                        ..GameRecord::new("snake", outcome, self.started)
+                    });
+                    highscores.save_scores();
+                    self.first_result = false;
+                }
+            }
+        }
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, config: &Config, highscores: &HighScores) {
+        let layout = GameLayout::new(r.size());
+        let sidebar = layout.sidebar;
+
+        r.clear(config.theme.game_background);
 
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        Color::LOSE_TEXT,
-                        Pos { x: 10.0, y: 528.0 },
-                        "You lost!",
-                        28,
-                    )
+        draw_text(
+            r,
+            config.theme.text,
+            Pos {
+                x: sidebar.x,
+                y: sidebar.y + 300.0,
+            },
+            &format!("Score: {}", self.game.score),
+            28,
+        );
+        draw_leaderboard(
+            r,
+            &highscores.scores.snake,
+            sidebar.pos(),
+            &config.theme,
+            self.placed,
+        );
+
+        match self.game.state {
+            GameState::Playing => (),
+            GameState::Lost => draw_text(
+                r,
+                config.theme.lose_text,
+                Pos {
+                    x: sidebar.x,
+                    y: sidebar.y + 340.0,
                 },
-                GameState::Won => {
-                    // update highscore
-                    if self.first_result {
-                        highscores.scores.snake = std::cmp::max(
-                            highscores.scores.snake,
-                            self.game.score,
-                        );
-                        highscores.save_scores();
-                        self.first_result = false;
-                    }
+                "You lost!",
+                28,
+            ),
+            GameState::Won => draw_text(
+                r,
+                config.theme.win_text,
+                Pos {
+                    x: sidebar.x,
+                    y: sidebar.y + 340.0,
+                },
+                "You win!",
+                28,
+            ),
+        }
 
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        Color::WIN_TEXT,
-                        Pos { x: 10.0, y: 528.0 },
-                        "You win!",
-                        28,
-                    )
-                }
-            }
+        let grid = layout.board.square();
+        let size = grid.w / self.game.width as f64;
+        r.push_translate(grid.x, grid.y);
 
-            // draw snake
-            for cell in &self.game.snake.body {
-                let x = (cell.x - 1) as f64 * self.size;
-                let y = (cell.y - 1) as f64 * self.size;
-
-                rectangle(
-                    Color::BLACK,
-                    [x, y, self.size, self.size],
-                    ctx.transform,
-                    g,
-                );
-            }
+        // draw snake
+        for cell in &self.game.snake.body {
+            let x = (cell.x - 1) as f64 * size;
+            let y = (cell.y - 1) as f64 * size;
+
+            r.rectangle(config.theme.snake.snake, [x, y, size, size]);
+        }
+
+        // draw food
+        r.rectangle(
+            config.theme.snake.food,
+            [
+                (self.game.food.x - 1) as f64 * size,
+                (self.game.food.y - 1) as f64 * size,
+                size,
+                size,
+            ],
+        );
 
-            // draw food
-            rectangle(
-                rgb!(255, 0, 0),
+        // draw boundaries
+        for (x, y) in (0..=self.game.width).zip(0..=self.game.height) {
+            r.line(
+                config.theme.snake.grid,
+                0.5,
                 [
-                    (self.game.food.x - 1) as f64 * self.size,
-                    (self.game.food.y - 1) as f64 * self.size,
-                    self.size,
-                    self.size,
+                    size * (x as f64),
+                    0.0,
+                    size * (x as f64),
+                    size * (self.game.height) as f64,
                 ],
-                ctx.transform,
-                g,
             );
+            r.line(
+                config.theme.snake.grid,
+                0.5,
+                [
+                    0.0,
+                    size * (y as f64),
+                    size * (self.game.width) as f64,
+                    size * (y as f64),
+                ],
+            );
+        }
+        r.pop_transform();
 
-            // draw boundaries
-            for (x, y) in (0..=self.game.width).zip(0..=self.game.height) {
-                Line::new(Color::BLACK, 0.5).draw(
-                    [
-                        self.size * (x as f64),
-                        0.0,
-                        self.size * (x as f64),
-                        self.size * (self.game.height) as f64,
-                    ],
-                    &Default::default(),
-                    ctx.transform,
-                    g,
-                );
-                Line::new(Color::BLACK, 0.5).draw(
-                    [
-                        0.0,
-                        self.size * (y as f64),
-                        self.size * (self.game.width) as f64,
-                        self.size * (y as f64),
-                    ],
-                    &Default::default(),
-                    ctx.transform,
-                    g,
-                );
-            }
-
-            // draw buttons
-            reset_button.draw(&c, g, glyphs);
+        // draw buttons
+        let controls = layout.buttons(2);
+        self.focus.draw(
+            r,
+            &config.theme,
+            &[(&self.reset, controls[0]), (&self.grid_size, controls[1])],
+        );
 
-            // Update glyphs before rendering
-            glyphs.factory.encoder.flush(device);
-        });
+        if let Some(prompt) = &self.naming {
+            prompt.draw(r, config);
+        }
+        if let Some(pause) = &self.pause {
+            pause.draw(r, config);
+        }
     }
 }
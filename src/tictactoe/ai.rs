@@ -1,100 +1,614 @@
+/*!
+    The TicTacToe AI. It searches deeper and deeper with alpha-beta negamax
+    until its time for the move is up, remembering positions it has seen in
+    a transposition table keyed by Zobrist hashes. Searches run on their own
+    thread, so the window keeps drawing while the AI thinks.
+
+    Below the perfect level the AI doesn't look as far ahead and now and
+    then plays a random move instead of the best one.
+
+    The search works on any `Position`, Ultimate TicTacToe brings its own
+    moves and scores to it.
+*/
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver},
+    Arc,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
 use super::{Board, Mark};
 
 pub const INFINITY: i32 = i32::MAX - 100;
 
-pub fn evaluate(board: &Board, ply: i32) -> i32 {
-    let res = board.is_over();
-    if res == Mark::X {
-        if board.turn == Mark::X {
-            INFINITY - ply
-        } else {
-            -INFINITY + ply
+/// How long the AI thinks about a move
+pub const MOVE_TIME: Duration = Duration::from_millis(1000);
+
+// Scores above this are wins found by the search, counted down by the plies to them
+const WIN_SCORE: i32 = INFINITY - 1000;
+
+// Boards with more cells than this only try moves next to a mark
+const NEARBY_MOVES_FROM: usize = 25;
+
+// Most entries in the transposition table, smaller searches get a smaller one
+const TABLE_SIZE: usize = 1 << 17;
+
+// The table gets this many entries times the square of the board's cells
+const ENTRIES_PER_CELL: usize = 64;
+
+// How often the clock is looked at, in searched positions
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// How well the AI plays, kept in the config
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Level {
+    Easy,
+    Medium,
+    Hard,
+    #[default]
+    Perfect,
+}
+
+impl Level {
+    pub const ALL: [Level; 4] = [Level::Easy, Level::Medium, Level::Hard, Level::Perfect];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Easy => "Easy",
+            Level::Medium => "Medium",
+            Level::Hard => "Hard",
+            Level::Perfect => "Perfect",
         }
-    } else if res == Mark::O {
-        if board.turn == Mark::X {
-            -INFINITY + ply
-        } else {
-            INFINITY - ply
+    }
+
+    // How many moves ahead the AI looks, None looks until its time is up
+    fn max_depth(self) -> Option<i32> {
+        match self {
+            Level::Easy => Some(1),
+            Level::Medium => Some(2),
+            Level::Hard => Some(4),
+            Level::Perfect => None,
         }
+    }
+
+    // How often the AI plays a random move instead of the one it found
+    fn blunder_rate(self) -> f64 {
+        match self {
+            Level::Easy => 0.4,
+            Level::Medium => 0.2,
+            Level::Hard => 0.05,
+            Level::Perfect => 0.0,
+        }
+    }
+}
+
+/// Scores a board nobody has won yet for the side to move. Lines only one
+/// side has marks in count, a line one mark short of a win is worth 4 lines
+/// with one mark less.
+pub fn evaluate(board: &Board) -> i32 {
+    let mut score = 0;
+    for (start, direction) in board.lines() {
+        let (mut x_count, mut o_count) = (0, 0);
+        for cell in board.line(start, direction) {
+            match cell {
+                Mark::X => x_count += 1,
+                Mark::O => o_count += 1,
+                Mark::None => (),
+            }
+        }
+        if o_count == 0 && x_count > 0 {
+            score += 1 << (2 * x_count);
+        } else if x_count == 0 && o_count > 0 {
+            score -= 1 << (2 * o_count);
+        }
+    }
+    if board.turn == Mark::X {
+        score
     } else {
-        0
+        -score
     }
 }
 
-pub fn generate_moves(board: &Board) -> Vec<(usize, usize)> {
+// The empty cells worth trying, without checking whether the game is over
+fn candidate_moves(board: &Board) -> Vec<(usize, usize)> {
     let mut moves = Vec::new();
-    if board.is_over() != Mark::None {
-        return moves;
-    }
-    for x in 0..3 {
-        for y in 0..3 {
-            if board.cells[x][y] == Mark::None {
+    // on big boards moves far away from everything else are left out
+    let nearby_only = board.width * board.height > NEARBY_MOVES_FROM;
+    for x in 0..board.height {
+        for y in 0..board.width {
+            if board.cells[x][y] == Mark::None && (!nearby_only || neighbours(board, x, y) > 0) {
                 moves.push((x, y));
             }
         }
     }
+    if moves.is_empty() && nearby_only && !board.is_draw() {
+        // the first move goes in the middle
+        moves.push((board.height / 2, board.width / 2));
+    }
     moves
 }
 
-pub fn negamax_root(board: &mut Board) -> (usize, usize) {
-    let moves = generate_moves(board);
-    if moves.is_empty() {
-        // Should never happen
-        return (0, 0);
+// How many of the 8 cells around (x, y) have a mark
+fn neighbours(board: &Board, x: usize, y: usize) -> usize {
+    let rows = x.saturating_sub(1)..(x + 2).min(board.height);
+    rows.flat_map(|row| {
+        let cols = y.saturating_sub(1)..(y + 2).min(board.width);
+        cols.map(move |col| (row, col))
+    })
+    .filter(|(row, col)| board.cells[*row][*col] != Mark::None)
+    .count()
+}
+
+/// Searches for the move the AI plays at `level` until `budget` is used up
+/// or `stop` is set. None when there is no move to make.
+pub fn search(
+    board: &Board,
+    level: Level,
+    budget: Duration,
+    stop: &AtomicBool,
+) -> Option<(usize, usize)> {
+    search_with(board, level, budget, stop, &mut rand::thread_rng())
+}
+
+// `search` with the random numbers for the blunders taken from `rng`
+fn search_with(
+    board: &Board,
+    level: Level,
+    budget: Duration,
+    stop: &AtomicBool,
+    rng: &mut impl Rng,
+) -> Option<(usize, usize)> {
+    if board.is_over() != Mark::None {
+        return None;
+    }
+    if rng.gen_bool(level.blunder_rate()) {
+        return candidate_moves(board).choose(rng).copied();
+    }
+    // no need to look further than the board fills up
+    let empty = board.cells.iter().flatten();
+    let mut max_depth = empty.filter(|cell| **cell == Mark::None).count() as i32;
+    if let Some(limit) = level.max_depth() {
+        max_depth = max_depth.min(limit);
+    }
+    deepen(
+        HashedBoard::new(board),
+        max_depth,
+        table_size(board, level.max_depth()),
+        Instant::now() + budget,
+        stop,
+    )
+}
+
+/// A search on its own thread. Dropping it stops the search.
+pub struct BackgroundSearch {
+    result: Receiver<(usize, usize)>,
+    stop: Arc<AtomicBool>,
+}
+
+impl BackgroundSearch {
+    pub fn start(board: &Board, level: Level, budget: Duration) -> Self {
+        let board = board.clone();
+        BackgroundSearch::spawn(move |stop| search(&board, level, budget, stop))
+    }
+
+    /// Runs any search on its own thread, it should give up once the flag is set
+    pub fn spawn<F>(search: F) -> Self
+    where
+        F: FnOnce(&AtomicBool) -> Option<(usize, usize)> + Send + 'static,
+    {
+        let (sender, result) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        thread::spawn(move || {
+            if let Some(move_) = search(&flag) {
+                // the receiver is gone when the game was reset in the meantime
+                let _ = sender.send(move_);
+            }
+        });
+        BackgroundSearch { result, stop }
+    }
+
+    /// The move, once the search is done
+    pub fn poll(&self) -> Option<(usize, usize)> {
+        self.result.try_recv().ok()
+    }
+}
+
+impl Drop for BackgroundSearch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
     }
-    let mut best_score = -INFINITY;
-    let mut best_move: (usize, usize) = (0, 0);
+}
+
+/// A game the search can play through, taking back every move it makes
+pub trait Position {
+    type Move: Copy + PartialEq;
+
+    /// The moves of the side to move, `best` first and then the ones most
+    /// likely to be good, which cut the search off soonest
+    fn ordered_moves(&self, best: Option<Self::Move>) -> Vec<Self::Move>;
 
-    let mut alpha = -INFINITY;
-    let beta = INFINITY;
+    /// Makes the move, true when it won the game
+    fn play(&mut self, move_: Self::Move) -> bool;
 
-    for move_ in moves.iter() {
-        let x = move_.0;
-        let y = move_.1;
+    /// Takes back `move_`, the last move played
+    fn undo(&mut self, move_: Self::Move);
 
-        board.make_move(x, y);
-        let score = -negamax(board, -beta, -alpha, 1);
-        board.undo_move(x, y);
+    /// Nobody can move anymore
+    fn is_full(&self) -> bool;
+
+    /// Scores a position nobody has won yet for the side to move
+    fn evaluate(&self) -> i32;
+
+    /// The key of the position in the transposition table, None leaves the table out
+    fn hash(&self) -> Option<u64> {
+        None
+    }
+}
 
-        if score > best_score {
-            best_score = score;
-            best_move = *move_;
+/// Searches one move deeper each time, up to `max_depth` moves, until
+/// `deadline` or until `stop` is set, remembering positions in a table of
+/// `table_size` entries. None when there is no move to make.
+pub fn deepen<P: Position>(
+    position: P,
+    max_depth: i32,
+    table_size: usize,
+    deadline: Instant,
+    stop: &AtomicBool,
+) -> Option<P::Move> {
+    let mut best = *position.ordered_moves(None).first()?;
+    let mut search = Search {
+        position,
+        table: vec![None; table_size],
+        deadline,
+        stop,
+        nodes: 0,
+        aborted: false,
+    };
+    for depth in 1..=max_depth {
+        let (score, move_) = search.root(depth, best);
+        if search.aborted {
+            break;
         }
-        if score > alpha {
-            alpha = score;
-            if score >= beta {
+        best = move_;
+        // a forced win or loss doesn't change with more time
+        if score.abs() > WIN_SCORE {
+            break;
+        }
+    }
+    Some(best)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    /// The score is at least this, the search was cut off
+    Lower,
+    /// The score is at most this, no move reached alpha
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry<M> {
+    hash: u64,
+    depth: i32,
+    score: i32,
+    bound: Bound,
+    best: Option<M>,
+}
+
+struct Search<'a, P: Position> {
+    position: P,
+    table: Vec<Option<Entry<P::Move>>>,
+    deadline: Instant,
+    stop: &'a AtomicBool,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl<P: Position> Search<'_, P> {
+    fn root(&mut self, depth: i32, previous: P::Move) -> (i32, P::Move) {
+        let mut alpha = -INFINITY;
+        let beta = INFINITY;
+        let mut best = previous;
+        for move_ in self.position.ordered_moves(Some(previous)) {
+            let score = self.try_move(move_, alpha, beta, 0, depth);
+            if self.aborted {
                 break;
             }
+            if score > alpha {
+                alpha = score;
+                best = move_;
+            }
         }
+        (alpha, best)
     }
-    best_move
-}
 
-pub fn negamax(board: &mut Board, mut alpha: i32, beta: i32, ply: i32) -> i32 {
-    let moves = generate_moves(board);
-    if moves.is_empty() {
-        return evaluate(board, ply);
+    // Plays the move and scores it for the side making it
+    fn try_move(&mut self, move_: P::Move, alpha: i32, beta: i32, ply: i32, depth: i32) -> i32 {
+        let score = if self.position.play(move_) {
+            INFINITY - (ply + 1)
+        } else {
+            -self.negamax(-beta, -alpha, ply + 1, depth - 1)
+        };
+        self.position.undo(move_);
+        score
     }
 
-    let mut best_score = -INFINITY;
-    for move_ in moves.iter() {
-        let x = move_.0;
-        let y = move_.1;
+    /// Scores the position for the side to move, looking `depth` moves ahead
+    fn negamax(&mut self, mut alpha: i32, beta: i32, ply: i32, depth: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes % CLOCK_INTERVAL == 0
+            && (Instant::now() >= self.deadline || self.stop.load(Ordering::Relaxed))
+        {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+        // the move that filled the board didn't win
+        if self.position.is_full() {
+            return 0;
+        }
 
-        let e = board.make_move(x, y);
-        debug_assert!(e.is_none());
-        let score = -negamax(board, -beta, -alpha, ply + 1);
-        board.undo_move(x, y);
+        let slot = self
+            .position
+            .hash()
+            .filter(|_| !self.table.is_empty())
+            .map(|hash| (hash, hash as usize % self.table.len()));
+        let entry =
+            slot.and_then(|(hash, index)| self.table[index].filter(|entry| entry.hash == hash));
+        if let Some(entry) = entry.filter(|entry| entry.depth >= depth) {
+            let score = from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => (),
+            }
+        }
+        // leaves are most of the positions, they are scored without looking at their moves
+        if depth <= 0 {
+            return self.position.evaluate();
+        }
+
+        let moves = self
+            .position
+            .ordered_moves(entry.and_then(|entry| entry.best));
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = moves[0];
+        for move_ in moves {
+            let score = self.try_move(move_, alpha, beta, ply, depth);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = move_;
+            }
+            if score > alpha {
+                alpha = score;
+                if score >= beta {
+                    break;
+                }
+            }
+        }
 
-        if score > best_score {
-            best_score = score;
+        if let Some((hash, index)) = slot {
+            let bound = if best_score >= beta {
+                Bound::Lower
+            } else if best_score <= original_alpha {
+                Bound::Upper
+            } else {
+                Bound::Exact
+            };
+            self.table[index] = Some(Entry {
+                hash,
+                depth,
+                score: to_table(best_score, ply),
+                bound,
+                best: Some(best_move),
+            });
         }
-        if score > alpha {
-            alpha = score;
-            if score >= beta {
-                return beta;
+        best_score
+    }
+}
+
+// A board with the Zobrist hash of its marks kept up to date
+struct HashedBoard {
+    board: Board,
+    // a random number for every cell and mark, the hash xors those of the marks on the board
+    keys: Vec<[u64; 2]>,
+    // flipped when O is to move
+    side_key: u64,
+    hash: u64,
+}
+
+impl HashedBoard {
+    fn new(board: &Board) -> Self {
+        // the same keys every time, so searches can be repeated
+        let mut rng = StdRng::seed_from_u64(0x7ac7ac);
+        let keys: Vec<[u64; 2]> = (0..board.width * board.height)
+            .map(|_| [rng.gen(), rng.gen()])
+            .collect();
+        let side_key = rng.gen();
+
+        let mut hash = if board.turn == Mark::O { side_key } else { 0 };
+        for (index, cell) in board.cells.iter().flatten().enumerate() {
+            if *cell != Mark::None {
+                hash ^= keys[index][*cell as usize];
             }
         }
+        HashedBoard {
+            board: board.clone(),
+            keys,
+            side_key,
+            hash,
+        }
+    }
+
+    // Puts the mark of the side to move on (row, col), or takes it back off
+    fn toggle(&mut self, row: usize, col: usize) {
+        let index = row * self.board.width + col;
+        let cell = &mut self.board.cells[row][col];
+        if *cell == Mark::None {
+            *cell = self.board.turn;
+            self.hash ^= self.keys[index][self.board.turn as usize];
+            self.board.turn = self.board.turn.invert();
+        } else {
+            self.board.turn = self.board.turn.invert();
+            self.hash ^= self.keys[index][self.board.turn as usize];
+            *cell = Mark::None;
+        }
+        self.hash ^= self.side_key;
+    }
+}
+
+impl Position for HashedBoard {
+    type Move = (usize, usize);
+
+    // the ones closest to the other marks and the middle come first
+    fn ordered_moves(&self, best: Option<(usize, usize)>) -> Vec<(usize, usize)> {
+        let board = &self.board;
+        let mut moves = candidate_moves(board);
+        let middle = ((board.height / 2) as i32, (board.width / 2) as i32);
+        moves.sort_by_key(|(row, col)| {
+            let distance = (*row as i32 - middle.0).abs() + (*col as i32 - middle.1).abs();
+            (
+                Some((*row, *col)) != best,
+                -(neighbours(board, *row, *col) as i32),
+                distance,
+            )
+        });
+        moves
+    }
+
+    fn play(&mut self, (row, col): (usize, usize)) -> bool {
+        self.toggle(row, col);
+        self.board.wins_at(row, col)
+    }
+
+    fn undo(&mut self, (row, col): (usize, usize)) {
+        self.toggle(row, col);
+    }
+
+    fn is_full(&self) -> bool {
+        self.board.is_draw()
+    }
+
+    fn evaluate(&self) -> i32 {
+        evaluate(&self.board)
+    }
+
+    fn hash(&self) -> Option<u64> {
+        Some(self.hash)
+    }
+}
+
+// Room for the positions a search can get to, up to TABLE_SIZE entries: a
+// few per cell on big boards, fewer when the search only looks a move or two ahead
+fn table_size(board: &Board, depth_limit: Option<i32>) -> usize {
+    let cells = board.width * board.height;
+    let reachable = depth_limit.map_or(usize::MAX, |depth| cells.saturating_pow(depth as u32));
+    (cells * cells * ENTRIES_PER_CELL)
+        .min(reachable)
+        .next_power_of_two()
+        .min(TABLE_SIZE)
+}
+
+// Wins are stored counted from the position, not from the root,
+// so they are right wherever the position comes up again
+fn to_table(score: i32, ply: i32) -> i32 {
+    if score > WIN_SCORE {
+        score + ply
+    } else if score < -WIN_SCORE {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i32, ply: i32) -> i32 {
+    if score > WIN_SCORE {
+        score - ply
+    } else if score < -WIN_SCORE {
+        score + ply
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_follows_the_moves() {
+        let board = Board::new();
+        let mut hashed = HashedBoard::new(&board);
+        let empty = hashed.hash;
+
+        hashed.play((0, 0));
+        hashed.play((1, 1));
+        let mut other = board.clone();
+        other.cells[0][0] = Mark::X;
+        other.cells[1][1] = Mark::O;
+        assert_eq!(hashed.hash, HashedBoard::new(&other).hash);
+
+        hashed.undo((1, 1));
+        hashed.undo((0, 0));
+        assert_eq!(hashed.hash, empty);
+        assert_eq!(hashed.board, board);
+    }
+
+    #[test]
+    fn background_search_finishes() {
+        let mut board = Board::new();
+        board.make_move(0, 0);
+        let search = BackgroundSearch::start(&board, Level::Perfect, MOVE_TIME);
+        let move_ = search
+            .result
+            .recv_timeout(MOVE_TIME * 5)
+            .expect("the search didn't send a move in time");
+        // the only move that doesn't lose against a corner
+        assert_eq!(move_, (1, 1));
+    }
+
+    #[test]
+    fn table_fits_the_search() {
+        let mut board = Board::new();
+        assert_eq!(table_size(&board, Some(1)), 16);
+        assert_eq!(table_size(&board, None), 8192);
+        board = Board::with_size(15, 15, 5);
+        assert_eq!(table_size(&board, Some(2)), 65536);
+        assert_eq!(table_size(&board, None), TABLE_SIZE);
+    }
+
+    #[test]
+    fn lower_levels_blunder() {
+        // O wins in the bottom left
+        let mut board = Board::new();
+        for (row, col) in [(0, 1), (0, 0), (2, 2), (1, 0), (2, 1)] {
+            board.make_move(row, col);
+        }
+        let stop = AtomicBool::new(false);
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut wins = |level| {
+            (0..100)
+                .filter(|_| {
+                    let move_ = search_with(&board, level, MOVE_TIME, &stop, &mut rng);
+                    move_ == Some((2, 0))
+                })
+                .count()
+        };
+
+        assert_eq!(wins(Level::Perfect), 100);
+        assert!((80..100).contains(&wins(Level::Hard)));
+        assert!((40..90).contains(&wins(Level::Easy)));
     }
-    alpha
 }
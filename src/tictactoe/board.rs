@@ -1,11 +1,22 @@
 use std::{error::Error, fmt::Display};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// An m,n,k game: `width` by `height` cells, `win_length` marks in a row win
+#[derive(Debug, Clone, PartialEq)]
 pub struct Board {
-    pub cells: [[Mark; 3]; 3],
+    /// Rows of cells, `cells[row][col]`
+    pub cells: Vec<Vec<Mark>>,
+    pub width: usize,
+    pub height: usize,
+    pub win_length: usize,
     pub turn: Mark,
 }
 
+/// The boards that can be picked: width, height and win length
+pub const BOARD_SIZES: [(usize, usize, usize); 4] = [(3, 3, 3), (4, 4, 4), (5, 5, 4), (15, 15, 5)];
+
+// The directions a line can go in from its first cell: right, down and both diagonals
+const LINES: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(u8)]
 pub enum Mark {
@@ -26,124 +37,118 @@ impl Mark {
 
 impl Board {
     pub fn new() -> Self {
+        Board::with_size(3, 3, 3)
+    }
+
+    pub fn with_size(width: usize, height: usize, win_length: usize) -> Self {
         Board {
-            cells: [[Mark::None; 3]; 3],
+            cells: vec![vec![Mark::None; width]; height],
+            width,
+            height,
+            win_length,
             turn: Mark::X,
         }
     }
 
     /// Returns Error if the move is invalid
-    pub fn make_move(&mut self, x: usize, y: usize) -> Option<TicTacToeError> {
+    pub fn make_move(&mut self, row: usize, col: usize) -> Option<TicTacToeError> {
         if self.is_over() != Mark::None {
             return Some(TicTacToeError::GameOver);
         }
-        if self.cells[x][y] != Mark::None {
+        if self.cells[row][col] != Mark::None {
             return Some(TicTacToeError::Occupied);
         }
-        self.cells[x][y] = self.turn;
+        self.cells[row][col] = self.turn;
         self.turn = self.turn.invert();
         None
     }
 
-    pub fn undo_move(&mut self, x: usize, y: usize) {
-        // Assumes move is legal
-        self.cells[x][y] = Mark::None;
+    /// Takes back the move on (row, col), it has to be the last one made
+    pub fn undo_move(&mut self, row: usize, col: usize) {
+        self.cells[row][col] = Mark::None;
         self.turn = self.turn.invert();
     }
 
     pub fn reset(&mut self) {
-        self.cells = [[Mark::None; 3]; 3];
-        self.turn = Mark::X;
+        *self = Board::with_size(self.width, self.height, self.win_length);
     }
 
     pub fn is_draw(&self) -> bool {
-        for x in 0..3 {
-            for y in 0..3 {
-                if self.cells[x][y] == Mark::None {
-                    return false;
-                }
-            }
-        }
-        true
+        self.cells.iter().flatten().all(|cell| *cell != Mark::None)
     }
 
-    pub fn is_over(&self) -> Mark {
-        for row in 0..3 {
-            let mut x_count = 0;
-            let mut o_count = 0;
-            for col in 0..3 {
-                let cell = self.cells[row][col];
-                match cell {
-                    Mark::X => x_count += 1,
-                    Mark::O => o_count += 1,
-                    Mark::None => (),
-                }
-            }
-            if x_count == 3 {
-                return Mark::X;
-            } else if o_count == 3 {
-                return Mark::O;
-            }
-        }
-
-        for col in 0..3 {
-            let mut x_count = 0;
-            let mut o_count = 0;
-            for row in 0..3 {
-                let cell = self.cells[row][col];
-                match cell {
-                    Mark::X => x_count += 1,
-                    Mark::O => o_count += 1,
-                    Mark::None => (),
-                }
-            }
-            if x_count == 3 {
-                return Mark::X;
-            } else if o_count == 3 {
-                return Mark::O;
-            }
-        }
-
-        {
-            let mut x_count = 0;
-            let mut o_count = 0;
+    /// Every run of `win_length` cells in a row, column or diagonal,
+    /// as the cell it starts at and the direction it goes in
+    pub fn lines(&self) -> impl Iterator<Item = ((usize, usize), (isize, isize))> + '_ {
+        let cells =
+            (0..self.height).flat_map(move |row| (0..self.width).map(move |col| (row, col)));
+        cells
+            .flat_map(|start| LINES.iter().map(move |direction| (start, *direction)))
+            .filter(move |(start, direction)| self.fits(*start, *direction))
+    }
 
-            for col in 0..3 {
-                let cell = self.cells[col][col];
-                match cell {
-                    Mark::X => x_count += 1,
-                    Mark::O => o_count += 1,
-                    Mark::None => (),
-                }
-            }
+    /// The cells of a line from `lines`
+    pub fn line(
+        &self,
+        (row, col): (usize, usize),
+        (dr, dc): (isize, isize),
+    ) -> impl Iterator<Item = Mark> + '_ {
+        (0..self.win_length as isize).map(move |i| {
+            let row = (row as isize + dr * i) as usize;
+            let col = (col as isize + dc * i) as usize;
+            self.cells[row][col]
+        })
+    }
 
-            if x_count == 3 {
-                return Mark::X;
-            } else if o_count == 3 {
-                return Mark::O;
-            }
+    /// Whether the mark on (row, col) is part of `win_length` in a row,
+    /// quicker than `is_over` after a move
+    pub fn wins_at(&self, row: usize, col: usize) -> bool {
+        let mark = self.cells[row][col];
+        if mark == Mark::None {
+            return false;
         }
+        // counts the same marks going away from (row, col), both ways along each line
+        let count = |dr: isize, dc: isize| {
+            (1..self.win_length as isize)
+                .map(|i| (row as isize + dr * i, col as isize + dc * i))
+                .take_while(|(r, c)| {
+                    (0..self.height as isize).contains(r)
+                        && (0..self.width as isize).contains(c)
+                        && self.cells[*r as usize][*c as usize] == mark
+                })
+                .count()
+        };
+        LINES
+            .iter()
+            .any(|(dr, dc)| 1 + count(*dr, *dc) + count(-dr, -dc) >= self.win_length)
+    }
 
-        {
-            let mut x_count = 0;
-            let mut o_count = 0;
+    // Whether a line from `start` in `direction` stays on the board
+    fn fits(&self, (row, col): (usize, usize), (dr, dc): (isize, isize)) -> bool {
+        let last = self.win_length as isize - 1;
+        let end_row = row as isize + dr * last;
+        let end_col = col as isize + dc * last;
+        (0..self.height as isize).contains(&end_row) && (0..self.width as isize).contains(&end_col)
+    }
 
-            for col in 0..3 {
-                let cell = self.cells[col][2 - col];
-                match cell {
-                    Mark::X => x_count += 1,
-                    Mark::O => o_count += 1,
-                    Mark::None => (),
+    /// The mark that has `win_length` in a row, or `Mark::None`
+    pub fn is_over(&self) -> Mark {
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let first = self.cells[row][col];
+                // empty cells can't start a line, which skips most of a big board
+                if first == Mark::None {
+                    continue;
+                }
+                for direction in LINES {
+                    if self.fits((row, col), direction)
+                        && self.line((row, col), direction).all(|cell| cell == first)
+                    {
+                        return first;
+                    }
                 }
-            }
-
-            if x_count == 3 {
-                return Mark::X;
-            } else if o_count == 3 {
-                return Mark::O;
             }
         }
-
         Mark::None
     }
 }
@@ -152,6 +157,8 @@ impl Board {
 pub enum TicTacToeError {
     Occupied,
     GameOver,
+    /// Ultimate TicTacToe only, the move isn't in the board it has to go in
+    WrongBoard,
 }
 
 impl Error for TicTacToeError {}
@@ -161,6 +168,7 @@ impl Display for TicTacToeError {
         match self {
             TicTacToeError::Occupied => write!(f, "Cell is occupied"),
             TicTacToeError::GameOver => write!(f, "Game is over"),
+            TicTacToeError::WrongBoard => write!(f, "Move is outside the active board"),
         }
     }
 }
@@ -8,6 +8,42 @@ pub use board::*;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::components::application::MiniApp;
+    use crate::components::application::{DEFAULT_HEIGHT, DEFAULT_WIDTH};
+    use crate::components::render::{recording::assert_snapshot, RecordingRenderer};
+    use crate::components::widgets::tests::{click, cursor};
+    use crate::menu::{config::Config, highscores::HighScores, storage::tests::TempDir};
+    use std::sync::atomic::AtomicBool;
+
+    const SIZE: [f64; 2] = [DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64];
+
+    // The AI's move with the usual time budget, searched on this thread
+    fn best_move(board: &Board) -> (usize, usize) {
+        search(board, Level::Perfect, MOVE_TIME, &AtomicBool::new(false)).unwrap()
+    }
+
+    // A board from rows of X, O and - for empty cells
+    fn board(rows: &[&str], win_length: usize, turn: Mark) -> Board {
+        let cells: Vec<Vec<Mark>> = rows
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|cell| match cell {
+                        'X' => Mark::X,
+                        'O' => Mark::O,
+                        _ => Mark::None,
+                    })
+                    .collect()
+            })
+            .collect();
+        Board {
+            width: cells[0].len(),
+            height: cells.len(),
+            cells,
+            win_length,
+            turn,
+        }
+    }
 
     #[test]
     fn test_game_over_1() {
@@ -16,14 +52,7 @@ mod tests {
         - O -
         X X X
          */
-        let board = Board {
-            cells: [
-                [Mark::O, Mark::O, Mark::None],
-                [Mark::None, Mark::O, Mark::None],
-                [Mark::X, Mark::X, Mark::X],
-            ],
-            turn: Mark::X,
-        };
+        let board = board(&["OO-", "-O-", "XXX"], 3, Mark::X);
         assert_eq!(board.is_over(), Mark::X);
     }
 
@@ -34,14 +63,7 @@ mod tests {
         - O -
         X X O
          */
-        let board = Board {
-            cells: [
-                [Mark::O, Mark::X, Mark::None],
-                [Mark::None, Mark::O, Mark::None],
-                [Mark::X, Mark::X, Mark::O],
-            ],
-            turn: Mark::X,
-        };
+        let board = board(&["OX-", "-O-", "XXO"], 3, Mark::X);
         assert_eq!(board.is_over(), Mark::O);
     }
 
@@ -52,14 +74,7 @@ mod tests {
         - O -
         X X -
          */
-        let board = Board {
-            cells: [
-                [Mark::O, Mark::None, Mark::None],
-                [Mark::None, Mark::O, Mark::None],
-                [Mark::X, Mark::X, Mark::None],
-            ],
-            turn: Mark::X,
-        };
+        let board = board(&["O--", "-O-", "XX-"], 3, Mark::X);
         assert_eq!(board.is_over(), Mark::None);
     }
 
@@ -72,16 +87,282 @@ mod tests {
 
         O at bottom left (!) wins
          */
-        let mut board = Board {
-            cells: [
-                [Mark::O, Mark::X, Mark::None],
-                [Mark::None, Mark::O, Mark::None],
-                [Mark::None, Mark::X, Mark::X],
-            ],
-            turn: Mark::O,
-        };
-        let bm = negamax_root(&mut board);
+        let board = board(&["OX-", "-O-", "-XX"], 3, Mark::O);
+        let bm = best_move(&board);
         assert_eq!(bm.0, 2);
         assert_eq!(bm.1, 0);
     }
+
+    #[test]
+    fn test_game_over_4x4() {
+        let diagonal = board(&["X--O", "-XO-", "-OX-", "O---"], 4, Mark::X);
+        assert_eq!(diagonal.is_over(), Mark::O);
+
+        // three in a row aren't enough on a 4x4 board
+        let three = board(&["XXX-", "OO--", "----", "----"], 4, Mark::O);
+        assert_eq!(three.is_over(), Mark::None);
+    }
+
+    #[test]
+    fn test_game_over_gomoku() {
+        let mut board = Board::with_size(15, 15, 5);
+        for row in 3..7 {
+            board.cells[row][14 - row] = Mark::X;
+        }
+        assert_eq!(board.is_over(), Mark::None);
+        board.cells[7][7] = Mark::X;
+        assert_eq!(board.is_over(), Mark::X);
+    }
+
+    #[test]
+    fn test_negamax_blocks_on_big_board() {
+        // X has four in a row with one end open, O has to block it
+        let mut board = Board::with_size(15, 15, 5);
+        for col in 3..7 {
+            board.cells[7][col] = Mark::X;
+        }
+        board.cells[7][2] = Mark::O;
+        board.cells[8][8] = Mark::O;
+        board.cells[9][9] = Mark::O;
+        board.turn = Mark::O;
+        assert_eq!(best_move(&board), (7, 7));
+
+        // and takes a win when it has one
+        board.cells[7][7] = Mark::O;
+        board.cells[6][6] = Mark::O;
+        board.cells[10][10] = Mark::X;
+        board.turn = Mark::O;
+        board.cells[5][5] = Mark::None;
+        let (row, col) = best_move(&board);
+        board.make_move(row, col);
+        assert_eq!(board.is_over(), Mark::O);
+    }
+
+    // Moves the mouse to `pos` and clicks in a window of `size`
+    fn click_at(app: &mut ui::TicTacToeApp, [x, y]: [f64; 2], size: [f64; 2]) {
+        let mut config = Config::new();
+        let mut highscores = HighScores::new();
+        for event in [cursor(x, y), click()] {
+            app.handle_event(&event, size, &mut config, &mut highscores);
+        }
+    }
+
+    // Updates the app until the AI's reply is on the board, failing when
+    // that takes much longer than the AI's time for a move
+    fn wait_for_ai(app: &mut ui::TicTacToeApp) {
+        let mut config = Config::new();
+        let mut highscores = HighScores::new();
+        let moves = app.shown;
+        let started = std::time::Instant::now();
+        while app.shown == moves {
+            assert!(
+                started.elapsed() < MOVE_TIME * 5,
+                "the AI didn't move within {:?}",
+                MOVE_TIME * 5
+            );
+            app.update(0.01, &mut config, &mut highscores);
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_app_click_makes_move() {
+        let mut app = ui::TicTacToeApp::new();
+        app.mode.selected = 0;
+
+        // click the top left square
+        click_at(&mut app, [426.0, 154.0], SIZE);
+
+        assert_eq!(app.state.cells[0][0], Mark::X);
+        assert_eq!(app.state.turn, Mark::O);
+    }
+
+    #[test]
+    fn test_app_ai_answers_in_the_background() {
+        let mut app = ui::TicTacToeApp::new();
+
+        // the AI plays O, the click returns before it has moved
+        click_at(&mut app, [426.0, 154.0], SIZE);
+        assert_eq!(app.state.turn, Mark::O);
+
+        // and the board is locked until it has
+        click_at(&mut app, [512.0, 154.0], SIZE);
+        assert_eq!(app.state.cells[0][1], Mark::None);
+
+        wait_for_ai(&mut app);
+        assert_eq!(app.state.turn, Mark::X);
+        assert_eq!(app.state.cells[1][1], Mark::O);
+    }
+
+    #[test]
+    fn test_app_level_is_saved() {
+        use crate::components::layout::GameLayout;
+
+        let mut app = ui::TicTacToeApp::new();
+        let dir = TempDir::new("level");
+        let mut config = Config::new();
+        config.location = dir.join("config.json");
+        let mut highscores = HighScores::new();
+        let level = GameLayout::new(SIZE).buttons(4)[2];
+
+        // open the list and pick the first level in it
+        let (x, y) = (level.x + level.w / 2.0, level.y + level.h / 2.0);
+        for event in [cursor(x, y), click(), cursor(x, y + level.h), click()] {
+            app.handle_event(&event, SIZE, &mut config, &mut highscores);
+        }
+        assert_eq!(config.options.tictactoe_level, Level::Easy);
+        assert_eq!(app.mode.options[2], "X vs Easy");
+    }
+
+    // The middle of a cell of the 3x3 board in the default window
+    fn cell_pos(row: usize, col: usize) -> [f64; 2] {
+        use crate::components::layout::{Anchor, GameLayout};
+
+        let (_, area) = GameLayout::new(SIZE).board.split_bottom(60.0);
+        let side = area.w.min(area.h);
+        let cell = area.anchor(Anchor::Center, side, side).cell(3, 3, col, row);
+        [cell.x + cell.w / 2.0, cell.y + cell.h / 2.0]
+    }
+
+    #[test]
+    fn test_app_undo_redo() {
+        let mut app = ui::TicTacToeApp::new();
+        app.mode.selected = 0;
+        click_at(&mut app, cell_pos(0, 0), SIZE);
+        click_at(&mut app, cell_pos(1, 2), SIZE);
+
+        app.undo();
+        assert_eq!(app.state.cells[1][2], Mark::None);
+        assert_eq!(app.state.turn, Mark::O);
+        assert_eq!(app.shown, 1);
+
+        app.redo();
+        assert_eq!(app.state.cells[1][2], Mark::O);
+        assert_eq!(app.shown, 2);
+
+        // a new move replaces the ones taken back
+        app.undo();
+        click_at(&mut app, cell_pos(2, 2), SIZE);
+        assert_eq!(app.moves, vec![(0, 0), (2, 2)]);
+        app.redo();
+        assert_eq!(app.shown, 2);
+    }
+
+    #[test]
+    fn test_app_undo_takes_back_the_ai_reply() {
+        let mut app = ui::TicTacToeApp::new();
+
+        click_at(&mut app, cell_pos(0, 0), SIZE);
+        // taken back while the AI thinks, only the player's move goes
+        app.undo();
+        assert_eq!(app.shown, 0);
+        assert_eq!(app.state.turn, Mark::X);
+
+        click_at(&mut app, cell_pos(0, 0), SIZE);
+        wait_for_ai(&mut app);
+        app.undo();
+        assert_eq!(app.shown, 0);
+        assert_eq!(app.state, Board::new());
+
+        // the player's move comes back with the AI's reply
+        app.redo();
+        assert_eq!(app.shown, 2);
+        assert_eq!(app.state.cells[1][1], Mark::O);
+    }
+
+    #[test]
+    fn test_app_mode_change_starts_and_stops_the_ai() {
+        use crate::components::layout::GameLayout;
+
+        let mut app = ui::TicTacToeApp::new();
+        let mut config = Config::new();
+        let mut highscores = HighScores::new();
+        let mode = GameLayout::new(SIZE).buttons(5)[1];
+        let (x, y) = (mode.x + mode.w / 2.0, mode.y + mode.h / 2.0);
+        let mut pick = |app: &mut ui::TicTacToeApp, option: usize| {
+            let option_y = y + mode.h * (option + 1) as f64;
+            for event in [cursor(x, y), click(), cursor(x, option_y), click()] {
+                app.handle_event(&event, SIZE, &mut config, &mut highscores);
+            }
+        };
+
+        // the AI's search is dropped when nobody plays against it anymore
+        click_at(&mut app, cell_pos(0, 0), SIZE);
+        pick(&mut app, 0);
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        app.update(0.2, &mut Config::new(), &mut HighScores::new());
+        assert_eq!(app.shown, 1);
+        assert_eq!(app.state.turn, Mark::O);
+
+        // and starts when it becomes the AI's turn
+        pick(&mut app, 2);
+        wait_for_ai(&mut app);
+        assert_eq!(app.state.cells[1][1], Mark::O);
+    }
+
+    #[test]
+    fn test_app_replays_a_finished_game() {
+        let mut app = ui::TicTacToeApp::new();
+        app.mode.selected = 0;
+        let mut config = Config::new();
+        let dir = TempDir::new("replay");
+        let mut highscores = HighScores::new();
+        highscores.location = dir.join("highscores.json");
+
+        // X takes the top row
+        for (row, col) in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            let [x, y] = cell_pos(row, col);
+            for event in [cursor(x, y), click()] {
+                app.handle_event(&event, SIZE, &mut config, &mut highscores);
+            }
+        }
+        assert_eq!(app.state.is_over(), Mark::X);
+        assert_eq!(highscores.scores.tictactoe_lime, 1);
+
+        // undo steps back through the game without changing it
+        app.undo();
+        app.undo();
+        assert_eq!(app.shown, 3);
+        assert_eq!(app.state.cells[1][1], Mark::None);
+        let [x, y] = cell_pos(2, 2);
+        for event in [cursor(x, y), click()] {
+            app.handle_event(&event, SIZE, &mut config, &mut highscores);
+        }
+        assert_eq!(app.state.cells[2][2], Mark::None);
+
+        app.redo();
+        app.redo();
+        app.redo();
+        assert_eq!(app.shown, 5);
+        assert_eq!(highscores.scores.tictactoe_lime, 1);
+        assert_eq!(highscores.stats.records.len(), 1);
+    }
+
+    #[test]
+    fn test_app_board_follows_window_size() {
+        let mut app = ui::TicTacToeApp::new();
+        app.mode.selected = 0;
+
+        // the board grows with the window, so this is still the top left square
+        click_at(&mut app, [700.0, 300.0], [1920.0, 1080.0]);
+        assert_eq!(app.state.cells[0][0], Mark::X);
+
+        // but the middle right one in the default window
+        click_at(&mut app, [700.0, 300.0], SIZE);
+        assert_eq!(app.state.cells[1][2], Mark::O);
+    }
+
+    #[test]
+    fn test_app_snapshot() {
+        let mut app = ui::TicTacToeApp::new();
+        app.mode.selected = 0;
+        app.state.make_move(0, 0);
+        app.state.make_move(1, 1);
+        app.moves = vec![(0, 0), (1, 1)];
+        app.shown = 2;
+
+        let mut r = RecordingRenderer::new(DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64);
+        app.draw(&mut r, &Config::new(), &HighScores::new());
+        assert_snapshot("tictactoe", &r);
+    }
 }
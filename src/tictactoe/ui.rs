@@ -1,150 +1,375 @@
-use super::Board;
-use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use super::{Board, Level, BOARD_SIZES};
+use crate::components::application::MiniApp;
 use crate::components::{
-    button::{draw_text, Pos, UIButton},
-    color::Color,
+    button::{draw_text, ButtonStyle, Pos, UIButton},
+    layout::{Anchor, GameLayout, Rect},
+    render::Renderer,
+    widgets::{Dropdown, Focus, Response},
 };
-use crate::menu::{config::Config, highscores::HighScores, ui::TOP_PAD};
-use crate::tictactoe::{negamax_root, Mark};
-use crate::{rgb, Event};
+use crate::menu::{
+    config::Config,
+    highscores::HighScores,
+    stats::{GameRecord, Outcome},
+    theme::TicTacToePalette,
+};
+use crate::tictactoe::{BackgroundSearch, Mark, MOVE_TIME};
+use crate::Event;
 use piston_window::*;
+use std::time::Instant;
 
 pub struct TicTacToeApp {
     pub state: Board,
-    pub hover_pos: [f64; 2],
-    pub hover_sq: (usize, usize),
-    pub playing_ai: u8,
+    /// The hovered cell, as column and row
+    pub hover_sq: Option<(usize, usize)>,
+    /// Who plays against whom, the index is the side the AI plays
+    pub mode: Dropdown,
+    /// Index into `Level::ALL`, shown on the mode too
+    pub level: Dropdown,
+    /// Index into `BOARD_SIZES`
+    pub board_size: Dropdown,
+    /// Every move of the game as row and column, the ones after `shown`
+    /// were taken back and can be made again
+    pub moves: Vec<(usize, usize)>,
+    /// How many of `moves` are on the board
+    pub shown: usize,
+    /// The game is over, undo and redo step through it then
+    finished: bool,
+    reset: UIButton,
+    undo: UIButton,
+    redo: UIButton,
+    focus: Focus,
+    started: Instant,
+    /// The AI's move while it is searched for
+    thinking: Option<BackgroundSearch>,
 }
 
 impl TicTacToeApp {
     pub fn new() -> Self {
         TicTacToeApp {
             state: Board::new(),
-            hover_pos: [0.0, 0.0],
-            hover_sq: (6, 6),
-            playing_ai: 2,
+            hover_sq: None,
+            mode: mode_dropdown(Level::default()),
+            level: level_dropdown(Level::default()),
+            board_size: size_dropdown(),
+            moves: Vec::new(),
+            shown: 0,
+            finished: false,
+            reset: UIButton::new("     Reset", ButtonStyle::Danger, 24),
+            undo: UIButton::new(UNDO, ButtonStyle::Primary, 20),
+            redo: UIButton::new(REDO, ButtonStyle::Primary, 20),
+            focus: Focus::new(),
+            started: Instant::now(),
+            thinking: None,
+        }
+    }
+
+    // 0 when nobody is the AI, otherwise the AI's mark + 1
+    fn playing_ai(&self) -> u8 {
+        self.mode.selected as u8
+    }
+
+    // Whether the AI moves once `count` moves were made, X makes the first one
+    fn ai_moves_after(&self, count: usize) -> bool {
+        let turn = if count % 2 == 0 { Mark::X } else { Mark::O };
+        self.playing_ai() == turn as u8 + 1
+    }
+
+    // Starts a new game, the AI moves first when it plays X
+    fn restart(&mut self) {
+        // dropping the search stops it
+        self.thinking = None;
+        self.state.reset();
+        self.moves.clear();
+        self.shown = 0;
+        self.finished = false;
+        self.undo.text = UNDO.to_string();
+        self.redo.text = REDO.to_string();
+        self.started = Instant::now();
+        self.start_ai();
+    }
+
+    // Makes a move and adds it to the history, replacing the moves taken back
+    fn play(&mut self, row: usize, col: usize, highscores: &mut HighScores) {
+        self.moves.truncate(self.shown);
+        self.moves.push((row, col));
+        self.shown += 1;
+        self.state.make_move(row, col);
+        self.record_result(highscores);
+    }
+
+    // Moves through the history by one move
+    fn step_back(&mut self) {
+        self.shown -= 1;
+        let (row, col) = self.moves[self.shown];
+        self.state.undo_move(row, col);
+    }
+
+    fn step_forward(&mut self) {
+        let (row, col) = self.moves[self.shown];
+        self.state.make_move(row, col);
+        self.shown += 1;
+    }
+
+    /// Takes back the last move, and the AI's reply before it against the AI.
+    /// Steps back through the replay once the game is over
+    pub fn undo(&mut self) {
+        if self.finished {
+            if self.shown > 0 {
+                self.step_back();
+            }
+            return;
+        }
+        // back to the last time the player was to move
+        let mut target = self.shown.saturating_sub(1);
+        while target > 0 && self.ai_moves_after(target) {
+            target -= 1;
+        }
+        if target == self.shown || self.ai_moves_after(target) {
+            return;
+        }
+        self.thinking = None;
+        while self.shown > target {
+            self.step_back();
+        }
+    }
+
+    /// Makes the last move taken back again, and the AI's reply after it.
+    /// Steps forward through the replay once the game is over
+    pub fn redo(&mut self) {
+        if self.shown == self.moves.len() || (self.thinking.is_some() && !self.finished) {
+            return;
+        }
+        self.step_forward();
+        if self.finished {
+            return;
+        }
+        while self.shown < self.moves.len() && self.ai_moves_after(self.shown) {
+            self.step_forward();
+        }
+        // the AI's reply was taken back before it was found
+        self.start_ai();
+    }
+
+    fn ai_level(&self) -> Level {
+        Level::ALL[self.level.selected]
+    }
+
+    // Shows `level` on both dropdowns
+    fn set_level(&mut self, level: Level) {
+        let selected = self.mode.selected;
+        self.mode = mode_dropdown(level);
+        self.mode.selected = selected;
+        self.level = level_dropdown(level);
+    }
+
+    // Starts the AI's search when it is the AI's turn
+    fn start_ai(&mut self) {
+        // the replay of a finished game shows boards that aren't over yet
+        let over = self.finished || self.state.is_over() != Mark::None || self.state.is_draw();
+        if !over && self.playing_ai() == (self.state.turn as u8 + 1) {
+            let search = BackgroundSearch::start(&self.state, self.ai_level(), MOVE_TIME);
+            self.thinking = Some(search);
         }
     }
+
+    // Counts the win and records the game once it is over
+    fn record_result(&mut self, highscores: &mut HighScores) {
+        let result = self.state.is_over();
+        if result == Mark::X {
+            highscores.scores.tictactoe_lime += 1;
+        } else if result == Mark::O {
+            highscores.scores.tictactoe_purple += 1;
+        }
+
+        if result != Mark::None || self.state.is_draw() {
+            self.finished = true;
+            self.undo.text = BACK.to_string();
+            self.redo.text = NEXT.to_string();
+            let outcome = if result == Mark::None {
+                Outcome::Draw
+            } else if self.playing_ai() == result as u8 + 1 {
+                // the AI won
+                Outcome::Lost
+            } else {
+                Outcome::Won
+            };
+            highscores
+                .stats
+                .record(GameRecord::new("tictactoe", outcome, self.started));
+            highscores.save_scores();
+        }
+    }
+}
+
+fn mode_dropdown(level: Level) -> Dropdown {
+    let modes = vec![
+        "Man vs Man".to_string(),
+        format!("O vs {}", level.name()),
+        format!("X vs {}", level.name()),
+    ];
+    let mut dropdown = Dropdown::new("Mode", modes, 2);
+    // smaller, so the longest level still fits next to the arrow
+    dropdown.font_size = 14;
+    dropdown
+}
+
+fn level_dropdown(level: Level) -> Dropdown {
+    let levels = Level::ALL.map(|level| level.name().to_string()).to_vec();
+    let selected = Level::ALL.iter().position(|l| *l == level).unwrap_or(0);
+    let mut dropdown = Dropdown::new("AI", levels, selected);
+    dropdown.font_size = 16;
+    dropdown
 }
 
-const BOARD_SIZE: f64 = DEFAULT_HEIGHT as f64 - TOP_PAD;
-const CENTER_X: f64 = (DEFAULT_WIDTH as f64 - BOARD_SIZE) / 2.0;
-const SQUARE_SIZE: f64 = BOARD_SIZE / 4.0;
+fn size_dropdown() -> Dropdown {
+    let sizes = BOARD_SIZES
+        .iter()
+        .map(|(width, height, _)| format!("{}x{}", width, height))
+        .collect();
+    let mut dropdown = Dropdown::new("Board", sizes, 0);
+    dropdown.font_size = 16;
+    dropdown
+}
+
+// Height of the line below the board that shows the result
+const RESULT_HEIGHT: f64 = 60.0;
+
+// Labels of the history buttons, while playing and in the replay
+const UNDO: &str = "  Undo";
+const REDO: &str = "  Redo";
+const BACK: &str = "  Back";
+const NEXT: &str = "  Next";
+
+// How the history is listed: the top of the list and the height of a line
+const MOVES_TOP: f64 = 255.0;
+const MOVE_LINE: f64 = 22.0;
+
+// The buttons and dropdowns on the right, undo and redo share the last row
+fn control_rects(size: [f64; 2]) -> [Rect; 6] {
+    let rows = GameLayout::new(size).buttons(5);
+    let history = rows[4].columns(2, 8.0);
+    [rows[0], rows[1], rows[2], rows[3], history[0], history[1]]
+}
+
+// A move as its column letter and row number, like b3
+fn move_name((row, col): (usize, usize)) -> String {
+    format!("{}{}", (b'a' + col as u8) as char, row + 1)
+}
+
+// Where the grid is drawn, leaving room for the result below it
+fn grid_rect(size: [f64; 2], board: &Board) -> Rect {
+    let (_, area) = GameLayout::new(size).board.split_bottom(RESULT_HEIGHT);
+    let cell = (area.w / board.width as f64).min(area.h / board.height as f64);
+    area.anchor(
+        Anchor::Center,
+        cell * board.width as f64,
+        cell * board.height as f64,
+    )
+}
 
 // Converts Mark to Color
-fn mark_to_clr(mark: super::Mark) -> [f32; 4] {
+pub fn mark_to_clr(mark: super::Mark, palette: &TicTacToePalette) -> [f32; 4] {
     match mark {
-        super::Mark::X => rgb!(160, 237, 128),
-        super::Mark::O => rgb!(233, 138, 237),
-        _ => rgb!(250, 246, 188),
+        super::Mark::X => palette.x,
+        super::Mark::O => palette.o,
+        _ => palette.empty,
+    }
+}
+
+// Draws an X or an O inside rect, so the players can be told apart without colours
+pub fn draw_mark(r: &mut dyn Renderer, mark: Mark, rect: [f64; 4], palette: &TicTacToePalette) {
+    let inner = math::margin_rectangle(rect, rect[2] / 4.0);
+    let [x, y, w, h] = inner;
+    let radius = rect[2] / 24.0;
+    match mark {
+        Mark::X => {
+            r.line(palette.glyph, radius, [x, y, x + w, y + h]);
+            r.line(palette.glyph, radius, [x + w, y, x, y + h]);
+        }
+        Mark::O => r.border_ellipse(palette.glyph, radius, inner),
+        Mark::None => (),
     }
 }
 
 impl MiniApp for TicTacToeApp {
-    fn render(
+    fn on_enter(
+        &mut self,
+        _window: &mut PistonWindow,
+        config: &mut Config,
+        _highscores: &mut HighScores,
+    ) {
+        self.set_level(config.options.tictactoe_level);
+    }
+
+    fn handle_event(
         &mut self,
-        window: &mut PistonWindow,
         event: &Event,
-        glyphs: &mut Glyphs,
+        size: [f64; 2],
         config: &mut Config,
         highscores: &mut HighScores,
     ) {
-        if let Some([cx, cy]) = event.mouse_cursor_args() {
-            self.hover_pos = [cx, cy];
-        }
-
-        let left_click = event.press_args() == Some(Button::Mouse(MouseButton::Left));
-
-        // init buttons
-        let mut reset_button = UIButton::new(
-            "     Reset",
-            Color::RESET,
-            Color::WHITE,
-            24,
-            Pos { x: 791.2, y: 135.2 },
-            160.0,
-            48.0,
-        );
-
-        let ai_text = if self.playing_ai == 1 {
-            " Mode: Purple vs AI"
-        } else if self.playing_ai == 2 {
-            "  Mode: Lime vs AI"
-        } else {
-            "  Mode: Man vs Man"
-        };
-        let mut ai_button = UIButton::new(
-            ai_text,
-            rgb!(18, 156, 255),
-            Color::WHITE,
-            14,
-            Pos { x: 791.2, y: 228.0 },
-            160.0,
-            48.0,
-        );
-
         // handle button events
-        if reset_button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-            if left_click {
-                self.state.reset();
-                if self.playing_ai == (self.state.turn as u8 + 1) {
-                    let move_ = negamax_root(&mut self.state);
-                    self.state.make_move(move_.0, move_.1);
-                }
-            } else {
-                reset_button.width += 6.0;
-                reset_button.pos.x -= 3.0;
-                reset_button.height += 6.0;
-                reset_button.pos.y -= 3.0;
-                reset_button.size += 1;
+        let controls = control_rects(size);
+        let response = self.focus.dispatch(
+            event,
+            &mut [
+                (&mut self.reset, controls[0]),
+                (&mut self.mode, controls[1]),
+                (&mut self.level, controls[2]),
+                (&mut self.board_size, controls[3]),
+                (&mut self.undo, controls[4]),
+                (&mut self.redo, controls[5]),
+            ],
+        );
+        match response {
+            Some((0, Response::Clicked)) => self.restart(),
+            Some((4, Response::Clicked)) => self.undo(),
+            Some((5, Response::Clicked)) => self.redo(),
+            Some((1, Response::Picked(_))) => {
+                // a search for the side that isn't the AI anymore is dropped
+                self.thinking = None;
+                self.start_ai();
             }
-        } else if ai_button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-            if left_click {
-                if self.playing_ai != 2 {
-                    self.playing_ai += 1;
-                } else {
-                    self.playing_ai = 0;
-                }
-            } else {
-                ai_button.width += 6.0;
-                ai_button.pos.x -= 3.0;
-                ai_button.height += 6.0;
-                ai_button.pos.y -= 3.0;
-                ai_button.size += 1;
+            Some((2, Response::Picked(index))) => {
+                // the AI plays its next move at the new level
+                config.options.tictactoe_level = Level::ALL[index];
+                config.save_config();
+                self.set_level(Level::ALL[index]);
+                // a search at the old level is started over
+                self.thinking = None;
+                self.start_ai();
+            }
+            Some((3, Response::Picked(index))) => {
+                let (width, height, win_length) = BOARD_SIZES[index];
+                self.state = Board::with_size(width, height, win_length);
+                self.restart();
             }
+            _ => (),
         }
+        // the board only gets clicks the widgets didn't use
+        let left_click =
+            response.is_none() && event.press_args() == Some(Button::Mouse(MouseButton::Left));
+        let [cx, cy] = self.focus.cursor();
 
-        self.hover_sq = (6, 6);
+        self.hover_sq = None;
 
-        'o: for x in 0..3 {
-            for y in 0..3 {
-                let hovered = SQUARE_SIZE * (x as f64) + CENTER_X + 100.0 < self.hover_pos[0]
-                    && self.hover_pos[0] < SQUARE_SIZE * (x as f64 + 1.0) + CENTER_X + 100.0
-                    && SQUARE_SIZE * (y as f64) + TOP_PAD < self.hover_pos[1]
-                    && self.hover_pos[1] < SQUARE_SIZE * (y as f64 + 1.0) + TOP_PAD;
+        let grid = grid_rect(size, &self.state);
+        let (width, height) = (self.state.width, self.state.height);
+        'o: for x in 0..width {
+            for y in 0..height {
+                let hovered = grid.cell(width, height, x, y).contains(cx, cy);
 
                 if hovered {
-                    self.hover_sq = (x, y);
+                    self.hover_sq = Some((x, y));
 
-                    if left_click {
-                        let is_free = self.state.cells[y][x] == Mark::None;
+                    // the board is locked while the AI thinks and in the replay
+                    if left_click && self.thinking.is_none() && !self.finished {
+                        let is_free = self.state.cells[y][x] == Mark::None
+                            && self.state.is_over() == Mark::None;
                         if is_free {
-                            self.state.make_move(y, x);
-                            // if playing against AI, make a move
-                            if self.playing_ai == (self.state.turn as u8 + 1) {
-                                let move_ = negamax_root(&mut self.state);
-                                self.state.make_move(move_.0, move_.1);
-                            }
-
-                            // update highscores
-                            let result = self.state.is_over();
-                            if result == Mark::X {
-                                highscores.scores.tictactoe_lime += 1;
-                                highscores.save_scores();
-                            } else if result == Mark::O {
-                                highscores.scores.tictactoe_purple += 1;
-                                highscores.save_scores();
-                            }
+                            self.play(y, x, highscores);
+                            // if playing against AI, let it search for its move
+                            self.start_ai();
                         }
                     }
 
@@ -152,119 +377,182 @@ impl MiniApp for TicTacToeApp {
                 }
             }
         }
+    }
 
-        window.draw_2d(event, |c, g, device| {
-            clear(
-                if config.options.white_theme {
-                    Color::WHITE
-                } else {
-                    Color::DARK_THEME_BG
-                },
-                g,
-            );
+    fn update(&mut self, _dt: f64, _config: &mut Config, highscores: &mut HighScores) {
+        let move_ = self.thinking.as_ref().and_then(BackgroundSearch::poll);
+        if let Some((row, col)) = move_ {
+            self.thinking = None;
+            self.play(row, col, highscores);
+        }
+    }
 
-            // Draw texts
-            let result = self.state.is_over();
-            if result == Mark::X {
-                draw_text(
-                    &c,
-                    g,
-                    glyphs,
-                    Color::BLACK,
-                    Pos { x: 450.0, y: 528.0 },
-                    "Lime wins!",
-                    32,
-                );
-            } else if result == Mark::O {
-                draw_text(
-                    &c,
-                    g,
-                    glyphs,
-                    Color::BLACK,
-                    Pos { x: 440.0, y: 528.0 },
-                    "Purple wins!",
-                    32,
-                );
-            } else if self.state.is_draw() {
-                draw_text(
-                    &c,
-                    g,
-                    glyphs,
-                    Color::BLACK,
-                    Pos { x: 442.0, y: 528.0 },
-                    "It's a draw!",
-                    32,
-                );
+    fn draw(&self, r: &mut dyn Renderer, config: &Config, highscores: &HighScores) {
+        let size = r.size();
+        let layout = GameLayout::new(size);
+        let sidebar = layout.sidebar;
+        let (result_area, _) = layout.board.split_bottom(RESULT_HEIGHT);
+        let result_pos = |width: f64| {
+            let rect = result_area.anchor(Anchor::Center, width, 32.0);
+            Pos {
+                x: rect.x,
+                y: rect.y + rect.h,
             }
+        };
+        let grid = grid_rect(size, &self.state);
+        let (width, height) = (self.state.width, self.state.height);
 
-            // Draw buttons
-            reset_button.draw(&c, g, glyphs);
-            ai_button.draw(&c, g, glyphs);
+        r.clear(config.theme.game_background);
 
-            {
-                // Draw the STM
-                let ctx = c.trans(120.0, TOP_PAD * 2.0);
-                let rect = [0.0, 0.0, SQUARE_SIZE / 1.4, SQUARE_SIZE / 1.4];
-                rectangle(mark_to_clr(self.state.turn), rect, ctx.transform, g);
+        // Draw texts
+        let result = self.state.is_over();
+        if result == Mark::X {
+            draw_text(r, config.theme.text, result_pos(100.0), "X wins!", 32);
+        } else if result == Mark::O {
+            draw_text(r, config.theme.text, result_pos(104.0), "O wins!", 32);
+        } else if self.state.is_draw() {
+            draw_text(r, config.theme.text, result_pos(140.0), "It's a draw!", 32);
+        } else if self.finished {
+            let text = format!("Move {} of {}", self.shown, self.moves.len());
+            draw_text(r, config.theme.text, result_pos(170.0), &text, 32);
+        } else if self.thinking.is_some() {
+            draw_text(
+                r,
+                config.theme.text,
+                result_pos(190.0),
+                "AI is thinking...",
+                32,
+            );
+        }
+
+        {
+            // Draw the STM, as big as a cell of the 3x3 board but not over the scores
+            let side = (grid.w.min(grid.h) / 3.0 / 1.4).min(100.0);
+            let (_, below) = sidebar.split_top(10.0);
+            let rect = below.anchor(Anchor::Top, side, side).to_array();
+            r.rectangle(mark_to_clr(self.state.turn, &config.theme.tictactoe), rect);
+            if config.options.shape_cues {
+                draw_mark(r, self.state.turn, rect, &config.theme.tictactoe);
             }
+        }
 
-            {
-                // Draw the board
-                let ctx = c.trans(CENTER_X + 80.0, TOP_PAD);
-
-                for x in 0..3 {
-                    for y in 0..3 {
-                        let hovered = self.hover_sq == (x, y);
-
-                        let clr = mark_to_clr(self.state.cells[y][x]);
-                        let rect = math::margin_rectangle(
-                            [
-                                SQUARE_SIZE * (x as f64),
-                                SQUARE_SIZE * (y as f64),
-                                SQUARE_SIZE,
-                                SQUARE_SIZE,
-                            ],
-                            if hovered { 4.0 } else { 7.0 },
-                        );
-                        rectangle(clr, rect, ctx.transform, g);
+        {
+            // Draw the board
+
+            for x in 0..width {
+                for y in 0..height {
+                    let hovered = self.hover_sq == Some((x, y));
+
+                    let clr = mark_to_clr(self.state.cells[y][x], &config.theme.tictactoe);
+                    let cell = grid.cell(width, height, x, y);
+                    // the gaps shrink with the cells on big boards
+                    let margin = if hovered {
+                        (cell.w / 28.0).min(4.0)
+                    } else {
+                        (cell.w / 16.0).min(7.0)
+                    };
+                    let rect = math::margin_rectangle(cell.to_array(), margin);
+                    r.rectangle(clr, rect);
+                    if config.options.shape_cues {
+                        draw_mark(r, self.state.cells[y][x], rect, &config.theme.tictactoe);
                     }
                 }
             }
+        }
 
-            {
-                // draw highscores
-                draw_text(
-                    &c,
-                    g,
-                    glyphs,
-                    if config.options.white_theme {
-                        Color::BLACK
-                    } else {
-                        // black
-                        Color::WHITE
-                    },
-                    Pos { x: 10.0, y: 400.0 },
-                    &format!("Lime wins: {}", highscores.scores.tictactoe_lime),
-                    28,
-                );
-                draw_text(
-                    &c,
-                    g,
-                    glyphs,
-                    if config.options.white_theme {
-                        Color::BLACK
+        {
+            // draw highscores
+            draw_text(
+                r,
+                config.theme.text,
+                Pos {
+                    x: sidebar.x,
+                    y: sidebar.y + 150.0,
+                },
+                &format!("X wins: {}", highscores.scores.tictactoe_lime),
+                28,
+            );
+            draw_text(
+                r,
+                config.theme.text,
+                Pos {
+                    x: sidebar.x,
+                    y: sidebar.y + 185.0,
+                },
+                &format!("O wins: {}", highscores.scores.tictactoe_purple),
+                28,
+            );
+            draw_text(
+                r,
+                config.theme.text,
+                Pos {
+                    x: sidebar.x,
+                    y: sidebar.y + 215.0,
+                },
+                &format!("{} in a row win", self.state.win_length),
+                20,
+            );
+        }
+
+        {
+            // draw the moves, two to a line, scrolled to the last one on the board
+            let top = sidebar.y + MOVES_TOP;
+            let pos = |x: f64, line: usize| Pos {
+                x: sidebar.x + x,
+                y: top + MOVE_LINE * (line + 1) as f64 + 4.0,
+            };
+            let header = Pos {
+                x: sidebar.x,
+                y: top + 4.0,
+            };
+            draw_text(r, config.theme.text, header, "Moves", 20);
+            let lines = ((sidebar.h - MOVES_TOP - 4.0) / MOVE_LINE).max(1.0) as usize;
+            let current = self.shown.saturating_sub(1) / 2;
+            let first = (current + 1).saturating_sub(lines);
+            let [red, green, blue, _] = config.theme.text;
+            // moves that were taken back are faded
+            let undone = [red, green, blue, 0.5];
+
+            for line in 0..lines {
+                let number = first + line;
+                if number * 2 >= self.moves.len() {
+                    break;
+                }
+                let text = format!("{}.", number + 1);
+                draw_text(r, config.theme.text, pos(0.0, line), &text, 18);
+                for index in [number * 2, number * 2 + 1] {
+                    let Some(move_) = self.moves.get(index) else {
+                        break;
+                    };
+                    let x = 50.0 + 60.0 * (index % 2) as f64;
+                    if index + 1 == self.shown {
+                        let Pos { x, y } = pos(x, line);
+                        let rect = [x - 5.0, y - 17.0, 50.0, MOVE_LINE];
+                        r.rectangle(config.theme.accent, rect);
+                    }
+                    let color = if index < self.shown {
+                        config.theme.text
                     } else {
-                        // black
-                        Color::WHITE
-                    },
-                    Pos { x: 10.0, y: 440.0 },
-                    &format!("Purple wins: {}", highscores.scores.tictactoe_purple),
-                    28,
-                );
+                        undone
+                    };
+                    draw_text(r, color, pos(x, line), &move_name(*move_), 18);
+                }
             }
+        }
 
-            // Update glyphs before rendering
-            glyphs.factory.encoder.flush(device);
-        });
+        // Draw buttons last, so the open lists are on top
+        let controls = control_rects(size);
+        self.focus.draw(
+            r,
+            &config.theme,
+            &[
+                (&self.reset, controls[0]),
+                (&self.mode, controls[1]),
+                (&self.level, controls[2]),
+                (&self.board_size, controls[3]),
+                (&self.undo, controls[4]),
+                (&self.redo, controls[5]),
+            ],
+        );
     }
 }
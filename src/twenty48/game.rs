@@ -165,9 +165,9 @@ impl Game {
     }
 
     fn set_state(&mut self) {
-        if self.board.iter().any(|&v| v == 2048) {
+        if self.board.contains(&2048) {
             self.state = GameState::Won;
-        } else if self.board.iter().any(|&v| v == 0)
+        } else if self.board.contains(&0)
             || (0..3).any(|i| {
                 (0..3).any(|j| {
                     self.board[i * Self::WIDTH + j] == self.board[(i + 1) * Self::WIDTH + j]
@@ -1,3 +1,48 @@
 pub mod game;
 pub use game::*;
 pub mod ui;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+    use crate::components::render::{recording::assert_snapshot, RecordingRenderer};
+    use crate::components::widgets::tests::{key, text};
+    use crate::menu::{config::Config, highscores::HighScores, storage::tests::TempDir};
+    use piston_window::*;
+
+    const SIZE: [f64; 2] = [DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64];
+
+    #[test]
+    fn app_snapshot() {
+        let mut app = ui::Twenty48App::new();
+        app.game.board = [2, 0, 0, 4, 0, 8, 0, 0, 0, 0, 16, 0, 2048, 0, 0, 2];
+
+        let mut r = RecordingRenderer::new(DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64);
+        app.draw(&mut r, &Config::new(), &HighScores::new());
+        assert_snapshot("twenty48", &r);
+    }
+
+    #[test]
+    fn finished_run_asks_for_name() {
+        let mut app = ui::Twenty48App::new();
+        let mut config = Config::new();
+        let dir = TempDir::new("twenty48");
+        let mut highscores = HighScores::new();
+        highscores.location = dir.join("highscores.json");
+        app.game.state = GameState::Lost;
+        app.game.score = 512;
+
+        // the first key after the game ended opens the prompt
+        app.handle_event(&key(Key::Up), SIZE, &mut config, &mut highscores);
+        for _ in 0.."Player".len() {
+            app.handle_event(&key(Key::Backspace), SIZE, &mut config, &mut highscores);
+        }
+        app.handle_event(&text("Kim"), SIZE, &mut config, &mut highscores);
+        app.handle_event(&key(Key::Return), SIZE, &mut config, &mut highscores);
+
+        let entries = &highscores.scores.twenty48.entries;
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].name.as_str(), entries[0].score), ("Kim", 512));
+    }
+}
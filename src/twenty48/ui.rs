@@ -1,225 +1,221 @@
 use super::{Direction, Game, GameState};
-use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use crate::components::application::MiniApp;
 use crate::components::{
-    button::{draw_text, Pos, UIButton},
-    color::Color,
+    button::{draw_text, ButtonStyle, Pos, UIButton},
+    gesture::{Gesture, Swipe},
+    input::Action,
+    layout::GameLayout,
+    leaderboard::{draw_leaderboard, NamePrompt, PromptEvent},
+    render::Renderer,
+    widgets::{Focus, Response},
 };
-use crate::menu::{config::Config, highscores::HighScores, ui::TOP_PAD};
-use crate::{rgb, Event};
+use crate::menu::{
+    config::Config,
+    highscores::HighScores,
+    leaderboard::{Entry, Order},
+    stats::{GameRecord, Outcome},
+};
+use crate::Event;
 use piston_window::*;
+use std::time::Instant;
 
 pub struct Twenty48App {
-    game: Game,
-    hover_pos: [f64; 2],
+    pub game: Game,
+    focus: Focus,
+    swipe: Swipe,
+    reset: UIButton,
     first_result: bool,
+    started: Instant,
+    // asks for a name when a run makes the leaderboard
+    naming: Option<NamePrompt>,
+    // place of the last leaderboard entry, highlighted in the table
+    placed: Option<usize>,
 }
 
 impl Twenty48App {
     pub fn new() -> Self {
         Twenty48App {
             game: Game::new(),
-            hover_pos: [0.0, 0.0],
+            focus: Focus::new(),
+            swipe: Swipe::new(),
+            reset: UIButton::new("     Reset", ButtonStyle::Danger, 24),
             first_result: true,
+            started: Instant::now(),
+            naming: None,
+            placed: None,
         }
     }
 }
 
-const BOARD_SIZE: f64 = DEFAULT_HEIGHT as f64 - TOP_PAD;
-const CENTER_X: f64 = (DEFAULT_WIDTH as f64 - BOARD_SIZE) / 2.0;
-const SQUARE_SIZE: f64 = BOARD_SIZE / 4.5;
-
-// Converts Guess to Color
-fn val_to_clr(val: u32) -> [f32; 4] {
-    match val {
-        0 => rgb!(204, 192, 179),
-        2 => rgb!(238, 228, 218),
-        4 => rgb!(237, 224, 200),
-        8 => rgb!(242, 177, 121),
-        16 => rgb!(245, 149, 99),
-        32 => rgb!(246, 124, 95),
-        64 => rgb!(246, 94, 59),
-        128 => rgb!(237, 207, 114),
-        256 => rgb!(237, 204, 97),
-        512 => rgb!(237, 200, 80),
-        1024 => rgb!(237, 197, 63),
-        2048 => rgb!(237, 194, 46),
-        _ => Color::WHITE,
-    }
-}
-
 impl MiniApp for Twenty48App {
-    fn render(
+    fn handle_event(
         &mut self,
-        window: &mut PistonWindow,
         event: &Event,
-        glyphs: &mut Glyphs,
+        size: [f64; 2],
         config: &mut Config,
         highscores: &mut HighScores,
     ) {
-        if let Some([cx, cy]) = event.mouse_cursor_args() {
-            self.hover_pos = [cx, cy];
+        // the name prompt takes all keyboard input while it is open
+        if let Some(prompt) = self.naming.as_mut() {
+            match prompt.handle_event(event) {
+                PromptEvent::Submit(name) => {
+                    let entry = Entry::new(&name, self.game.score, "4x4");
+                    self.placed = highscores.scores.twenty48.insert(entry, Order::Highest);
+                    highscores.save_scores();
+                    self.naming = None;
+                    return;
+                }
+                PromptEvent::Cancel => {
+                    self.naming = None;
+                    return;
+                }
+                PromptEvent::Consumed => return,
+                PromptEvent::Ignored => (),
+            }
         }
 
-        // init buttons
-        let mut reset_button = UIButton::new(
-            "     Reset",
-            Color::RESET,
-            Color::WHITE,
-            24,
-            Pos { x: 791.2, y: 135.2 },
-            160.0,
-            48.0,
-        );
-
-        let left_click = event.press_args() == Some(Button::Mouse(MouseButton::Left));
-
         // handle button events
-        if reset_button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-            if left_click {
-                highscores.scores.twenty48 =
-                    std::cmp::max(highscores.scores.twenty48, self.game.score);
-                highscores.save_scores();
-                self.game.reset();
-            } else {
-                reset_button.width += 6.0;
-                reset_button.pos.x -= 3.0;
-                reset_button.height += 6.0;
-                reset_button.pos.y -= 3.0;
-                reset_button.size += 1;
-            }
+        let layout = GameLayout::new(size);
+        let gesture = self.swipe.handle_event(event, layout.board);
+        let reset = layout.buttons(1)[0];
+        let action = config
+            .options
+            .bindings
+            .action(event)
+            .or(gesture.and_then(Gesture::swipe));
+        let response = self.focus.dispatch(event, &mut [(&mut self.reset, reset)]);
+        if response == Some((0, Response::Clicked)) || action == Some(Action::Reset) {
+            self.game.reset();
+            self.first_result = true;
+            self.naming = None;
+            self.placed = None;
+            self.started = Instant::now();
         }
 
-        if let Some(Button::Keyboard(press)) = event.press_args() {
-            match press {
-                Key::Up => self.game.step(Direction::Up),
-                Key::Down => self.game.step(Direction::Down),
-                Key::Left => self.game.step(Direction::Left),
-                Key::Right => self.game.step(Direction::Right),
-                _ => (),
-            }
+        match action {
+            Some(Action::Up) => self.game.step(Direction::Up),
+            Some(Action::Down) => self.game.step(Direction::Down),
+            Some(Action::Left) => self.game.step(Direction::Left),
+            Some(Action::Right) => self.game.step(Direction::Right),
+            _ => (),
         }
 
-        window.draw_2d(event, |c, g, device| {
-            clear(
-                if config.options.white_theme {
-                    Color::WHITE
-                } else {
-                    Color::DARK_THEME_BG
-                },
-                g,
-            );
-
-            // draw buttons
-            reset_button.draw(&c, g, glyphs);
-
-            // draw highscores
-            draw_text(
-                &c,
-                g,
-                glyphs,
-                if config.options.white_theme {
-                    Color::BLACK
-                } else {
-                    Color::WHITE
-                },
-                Pos { x: 10.0, y: 400.0 },
-                &format!("Score: {}", self.game.score),
-                28,
-            );
-            draw_text(
-                &c,
-                g,
-                glyphs,
-                if config.options.white_theme {
-                    Color::BLACK
-                } else {
-                    Color::WHITE
-                },
-                Pos { x: 10.0, y: 440.0 },
-                &format!("Highscore: {}", highscores.scores.twenty48),
-                28,
-            );
+        // update highscore
+        if self.game.state != GameState::Playing && self.first_result {
+            if self.game.score > 0
+                && highscores
+                    .scores
+                    .twenty48
+                    .place(self.game.score, Order::Highest)
+                    .is_some()
+            {
+                let name = highscores.scores.twenty48.last_name().unwrap_or("Player");
+                self.naming = Some(NamePrompt::new(name));
+            }
+            let outcome = if self.game.state == GameState::Won {
+                Outcome::Won
+            } else {
+                Outcome::Lost
+            };
+            highscores.stats.record(GameRecord {
+                score: Some(self.game.score),
+                ..GameRecord::new("twenty48", outcome, self.started)
+            });
+            highscores.save_scores();
+            self.first_result = false;
+        }
+    }
 
-            // draw text
-            match self.game.state {
-                GameState::Lost => {
-                    // update highscore
-                    if self.first_result {
-                        highscores.scores.twenty48 =
-                            std::cmp::max(highscores.scores.twenty48, self.game.score);
-                        highscores.save_scores();
-                        self.first_result = false;
-                    }
+    fn takes_keyboard(&self) -> bool {
+        self.naming.is_some()
+    }
 
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        Color::LOSE_TEXT,
-                        Pos { x: 10.0, y: 200.0 },
-                        "Game Over",
-                        24,
-                    );
-                }
-                GameState::Won => {
-                    // update highscore
-                    if self.first_result {
-                        highscores.scores.twenty48 =
-                            std::cmp::max(highscores.scores.twenty48, self.game.score);
-                        highscores.save_scores();
-                        self.first_result = false;
-                    }
+    fn draw(&self, r: &mut dyn Renderer, config: &Config, highscores: &HighScores) {
+        let size = r.size();
+        let layout = GameLayout::new(size);
+        let sidebar = layout.sidebar;
+
+        r.clear(config.theme.game_background);
+
+        // draw highscores
+        draw_text(
+            r,
+            config.theme.text,
+            Pos {
+                x: sidebar.x,
+                y: sidebar.y + 300.0,
+            },
+            &format!("Score: {}", self.game.score),
+            28,
+        );
+        draw_leaderboard(
+            r,
+            &highscores.scores.twenty48,
+            sidebar.pos(),
+            &config.theme,
+            self.placed,
+        );
 
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        Color::WIN_TEXT,
-                        Pos { x: 10.0, y: 200.0 },
-                        "You win!",
-                        20,
-                    );
-                }
-                GameState::Playing => (),
+        // draw text
+        match self.game.state {
+            GameState::Lost => {
+                draw_text(
+                    r,
+                    config.theme.lose_text,
+                    Pos {
+                        x: sidebar.x,
+                        y: sidebar.y + 340.0,
+                    },
+                    "Game Over",
+                    24,
+                );
             }
+            GameState::Won => {
+                draw_text(
+                    r,
+                    config.theme.win_text,
+                    Pos {
+                        x: sidebar.x,
+                        y: sidebar.y + 340.0,
+                    },
+                    "You win!",
+                    20,
+                );
+            }
+            GameState::Playing => (),
+        }
 
-            // Draw the board
-            let ctx = c.trans(CENTER_X + 40.0, TOP_PAD);
-
-            for (i, &val) in self.game.board.iter().enumerate() {
-                let x = i % Game::WIDTH;
-                let y = i / Game::LENGTH;
-                let rect = math::margin_rectangle(
-                    [
-                        SQUARE_SIZE * (x as f64),
-                        SQUARE_SIZE * (y as f64),
-                        SQUARE_SIZE,
-                        SQUARE_SIZE,
-                    ],
-                    4.0,
+        // Draw the board
+        let board = layout.board.square();
+        // numbers grow with the tiles
+        let font_size = (board.w / 4.0 / 3.5) as u32;
+
+        for (i, &val) in self.game.board.iter().enumerate() {
+            let x = i % Game::WIDTH;
+            let y = i / Game::LENGTH;
+            let cell = board.cell(Game::WIDTH, Game::LENGTH, x, y);
+            let rect = math::margin_rectangle(cell.to_array(), 4.0);
+            r.rectangle(config.theme.twenty48.tile(val), rect);
+            if val != 0 {
+                draw_text(
+                    r,
+                    config.theme.twenty48.text,
+                    Pos {
+                        x: rect[0] + cell.w / 4.0 + 2.0,
+                        y: rect[1] + cell.h / 2.0 + 5.0,
+                    },
+                    &val.to_string(),
+                    font_size,
                 );
-                Rectangle::new(val_to_clr(val)).draw(rect, &Default::default(), ctx.transform, g);
-                if val != 0 {
-                    draw_text(
-                        &ctx,
-                        g,
-                        glyphs,
-                        Color::BLACK,
-                        Pos {
-                            x: rect[0] + SQUARE_SIZE / 4.0 + 2.0,
-                            y: rect[1] + SQUARE_SIZE / 2.0 + 5.0,
-                        },
-                        &val.to_string(),
-                        30,
-                    );
-                }
             }
+        }
 
-            // draw buttons
-            reset_button.draw(&c, g, glyphs);
+        // draw buttons
+        self.focus
+            .draw(r, &config.theme, &[(&self.reset, layout.buttons(1)[0])]);
 
-            // Update glyphs before rendering
-            glyphs.factory.encoder.flush(device);
-        });
+        if let Some(prompt) = &self.naming {
+            prompt.draw(r, config);
+        }
     }
 }
@@ -0,0 +1,91 @@
+/*!
+    The Ultimate TicTacToe AI. It is the alpha-beta negamax of the TicTacToe
+    AI, but the game is too big to search to the end: it stops a few moves
+    ahead and scores the positions there by the open lines on the small
+    boards and on the big one.
+*/
+
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use super::Game;
+use crate::tictactoe::{self, deepen, Mark, Position};
+
+/// How many moves ahead the AI looks at most
+pub const MAX_DEPTH: i32 = 8;
+
+// An open line on the big board counts this many times an open line on a small one
+const BIG_BOARD_WEIGHT: i32 = 16;
+
+/// Scores a game nobody has won yet for the side to move, by the open
+/// lines of the small boards still played in and of the big board
+pub fn evaluate(game: &Game) -> i32 {
+    // the boards keep X to move, so these are scores for X
+    let mut score = BIG_BOARD_WEIGHT * tictactoe::evaluate(&game.big);
+    for (index, board) in game.boards.iter().enumerate() {
+        if !game.closed(index) {
+            score += tictactoe::evaluate(board);
+        }
+    }
+    if game.turn == Mark::X {
+        score
+    } else {
+        -score
+    }
+}
+
+/// Searches one move deeper each time, up to `max_depth` moves, until
+/// `budget` is used up or `stop` is set. None when there is no move to make.
+pub fn search(
+    game: &Game,
+    max_depth: i32,
+    budget: Duration,
+    stop: &AtomicBool,
+) -> Option<(usize, usize)> {
+    let line = Line {
+        game: game.clone(),
+        active: Vec::new(),
+    };
+    // the game has no hash, so the search keeps no table
+    deepen(line, max_depth, 0, Instant::now() + budget, stop)
+}
+
+// The game with the active board from before each move, so moves can be taken back
+struct Line {
+    game: Game,
+    active: Vec<Option<usize>>,
+}
+
+impl Position for Line {
+    type Move = (usize, usize);
+
+    // the best move of the last search first, it is likely still good
+    fn ordered_moves(&self, best: Option<(usize, usize)>) -> Vec<(usize, usize)> {
+        let mut moves = self.game.moves();
+        if let Some(index) = best.and_then(|best| moves.iter().position(|move_| *move_ == best)) {
+            let best = moves.remove(index);
+            moves.insert(0, best);
+        }
+        moves
+    }
+
+    fn play(&mut self, (board, cell): (usize, usize)) -> bool {
+        self.active.push(self.game.active);
+        self.game.play(board, cell);
+        // only the big board cell of the played board can have changed
+        self.game.big.wins_at(board / 3, board % 3)
+    }
+
+    fn undo(&mut self, (board, cell): (usize, usize)) {
+        let active = self.active.pop().flatten();
+        self.game.undo(board, cell, active);
+    }
+
+    fn is_full(&self) -> bool {
+        (0..9).all(|board| self.game.closed(board))
+    }
+
+    fn evaluate(&self) -> i32 {
+        evaluate(&self.game)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::tictactoe::{Board, Mark, TicTacToeError};
+
+/// Ultimate TicTacToe: a 3x3 grid of 3x3 boards. Winning a small board
+/// claims its cell on the big board, three claimed cells in a row win.
+/// The cell a move goes in picks the small board the other side plays next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game {
+    /// The small boards, row by row. They keep X to move, the game's
+    /// `turn` says who actually moves, so `evaluate` scores them for X
+    pub boards: Vec<Board>,
+    /// Who claimed which small board, X to move like the small boards
+    pub big: Board,
+    pub turn: Mark,
+    /// The small board the next move has to go in, None when any open one will do
+    pub active: Option<usize>,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Game {
+            boards: vec![Board::new(); 9],
+            big: Board::new(),
+            turn: Mark::X,
+            active: None,
+        }
+    }
+
+    /// Whether the small board can't be played in anymore, because it
+    /// was won or is full
+    pub fn closed(&self, board: usize) -> bool {
+        self.big.cells[board / 3][board % 3] != Mark::None || self.boards[board].is_draw()
+    }
+
+    /// Whether the next move may go in the small board
+    pub fn playable(&self, board: usize) -> bool {
+        self.winner() == Mark::None
+            && !self.closed(board)
+            && self.active.is_none_or(|active| active == board)
+    }
+
+    /// The side that has three small boards in a row, or `Mark::None`
+    pub fn winner(&self) -> Mark {
+        self.big.is_over()
+    }
+
+    /// Nobody won and there is nowhere left to play
+    pub fn is_draw(&self) -> bool {
+        self.winner() == Mark::None && (0..9).all(|board| self.closed(board))
+    }
+
+    /// Every move the side to move can make, as small board and cell
+    pub fn moves(&self) -> Vec<(usize, usize)> {
+        (0..9)
+            .filter(|board| self.playable(*board))
+            .flat_map(|board| {
+                (0..9)
+                    .filter(move |cell| self.boards[board].cells[cell / 3][cell % 3] == Mark::None)
+                    .map(move |cell| (board, cell))
+            })
+            .collect()
+    }
+
+    /// Plays in `cell` of the small board `board`, both counted row by row.
+    /// Returns Error if the move is invalid
+    pub fn make_move(&mut self, board: usize, cell: usize) -> Option<TicTacToeError> {
+        if self.winner() != Mark::None || self.is_draw() {
+            return Some(TicTacToeError::GameOver);
+        }
+        if !self.playable(board) {
+            return Some(TicTacToeError::WrongBoard);
+        }
+        let (row, col) = (cell / 3, cell % 3);
+        if self.boards[board].cells[row][col] != Mark::None {
+            return Some(TicTacToeError::Occupied);
+        }
+        self.play(board, cell);
+        None
+    }
+
+    /// Plays a move from `moves` without checking it
+    pub fn play(&mut self, board: usize, cell: usize) {
+        let (row, col) = (cell / 3, cell % 3);
+        self.boards[board].cells[row][col] = self.turn;
+        if self.boards[board].wins_at(row, col) {
+            self.big.cells[board / 3][board % 3] = self.turn;
+        }
+        self.turn = self.turn.invert();
+        // a closed board sends the next move anywhere
+        self.active = Some(cell).filter(|next| !self.closed(*next));
+    }
+
+    /// Takes back the move `play` made, `active` is the board that was active before it
+    pub fn undo(&mut self, board: usize, cell: usize, active: Option<usize>) {
+        // a claimed board can't be played in, so this move claimed it
+        self.big.cells[board / 3][board % 3] = Mark::None;
+        self.boards[board].cells[cell / 3][cell % 3] = Mark::None;
+        self.turn = self.turn.invert();
+        self.active = active;
+    }
+}
@@ -0,0 +1,153 @@
+pub mod ai;
+pub mod game;
+pub mod ui;
+
+pub use ai::*;
+pub use game::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+    use crate::components::layout::{Anchor, GameLayout};
+    use crate::components::render::{recording::assert_snapshot, RecordingRenderer};
+    use crate::components::widgets::tests::{click, cursor};
+    use crate::menu::{config::Config, highscores::HighScores};
+    use crate::tictactoe::{Mark, TicTacToeError, MOVE_TIME};
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+
+    const SIZE: [f64; 2] = [DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64];
+
+    // Plays moves given as small board and cell
+    fn play(game: &mut Game, moves: &[(usize, usize)]) {
+        for (board, cell) in moves {
+            assert_eq!(game.make_move(*board, *cell), None);
+        }
+    }
+
+    #[test]
+    fn move_picks_the_next_board() {
+        let mut game = Game::new();
+        play(&mut game, &[(4, 2)]);
+        assert_eq!(game.active, Some(2));
+        assert!(game.playable(2));
+        assert!(!game.playable(4));
+        assert_eq!(game.make_move(4, 0), Some(TicTacToeError::WrongBoard));
+        assert_eq!(game.moves().len(), 9);
+
+        play(&mut game, &[(2, 4)]);
+        assert_eq!(game.make_move(4, 2), Some(TicTacToeError::Occupied));
+    }
+
+    #[test]
+    fn won_boards_are_claimed_and_closed() {
+        let mut game = Game::new();
+        // X takes the top row of the middle board, O plays in the boards it is sent to
+        play(&mut game, &[(4, 0), (0, 4), (4, 1), (1, 4), (4, 2)]);
+        assert_eq!(game.big.cells[1][1], Mark::X);
+        assert!(game.closed(4));
+
+        // being sent to a claimed board frees the move
+        play(&mut game, &[(2, 4)]);
+        assert_eq!(game.active, None);
+        assert!(!game.playable(4));
+        assert_eq!(game.make_move(4, 5), Some(TicTacToeError::WrongBoard));
+    }
+
+    #[test]
+    fn three_boards_in_a_row_win() {
+        let mut game = Game::new();
+        for board in [0, 4] {
+            game.big.cells[board / 3][board % 3] = Mark::X;
+        }
+        game.boards[8].cells[0] = vec![Mark::X, Mark::X, Mark::None];
+        game.boards[5].cells[2] = vec![Mark::O, Mark::O, Mark::None];
+        game.active = Some(8);
+
+        // X would win the game, taking back the move leaves everything as it was
+        let before = game.clone();
+        game.play(8, 2);
+        assert_eq!(game.winner(), Mark::X);
+        assert_eq!(game.make_move(5, 0), Some(TicTacToeError::GameOver));
+        game.undo(8, 2, before.active);
+        assert_eq!(game, before);
+
+        let move_ = search(&game, MAX_DEPTH, MOVE_TIME, &AtomicBool::new(false));
+        assert_eq!(move_, Some((8, 2)));
+    }
+
+    #[test]
+    fn ai_stops_a_win() {
+        let mut game = Game::new();
+        for board in [0, 4] {
+            game.big.cells[board / 3][board % 3] = Mark::O;
+        }
+        game.boards[8].cells[0] = vec![Mark::O, Mark::O, Mark::None];
+        // X has to play in board 8 and can block O there
+        game.active = Some(8);
+
+        let budget = Duration::from_millis(500);
+        let move_ = search(&game, MAX_DEPTH, budget, &AtomicBool::new(false));
+        assert_eq!(move_, Some((8, 2)));
+    }
+
+    #[test]
+    fn app_click_makes_move() {
+        let mut app = ui::UltimateApp::new();
+        app.mode.selected = 0;
+        let mut config = Config::new();
+        let mut highscores = HighScores::new();
+
+        // the middle of the board, above the result line
+        let (_, area) = GameLayout::new(SIZE).board.split_bottom(60.0);
+        let middle = area.anchor(Anchor::Center, 2.0, 2.0);
+        for event in [cursor(middle.x + 1.0, middle.y + 1.0), click()] {
+            app.handle_event(&event, SIZE, &mut config, &mut highscores);
+        }
+        assert_eq!(app.game.boards[4].cells[1][1], Mark::X);
+        assert_eq!(app.game.active, Some(4));
+    }
+
+    #[test]
+    fn app_mode_change_starts_the_ai() {
+        let mut app = ui::UltimateApp::new();
+        app.mode.selected = 0;
+        let mut config = Config::new();
+        let mut highscores = HighScores::new();
+        app.game.make_move(4, 4);
+
+        // the AI takes over O, whose turn it is
+        let mode = GameLayout::new(SIZE).buttons(2)[1];
+        let (x, y) = (mode.x + mode.w / 2.0, mode.y + mode.h / 2.0);
+        let option = y + mode.h * 3.0;
+        for event in [cursor(x, y), click(), cursor(x, option), click()] {
+            app.handle_event(&event, SIZE, &mut config, &mut highscores);
+        }
+        assert_eq!(app.mode.selected, 2);
+        let started = std::time::Instant::now();
+        while app.game.turn == Mark::O {
+            assert!(started.elapsed() < MOVE_TIME * 3, "the AI didn't move");
+            app.update(0.01, &mut config, &mut highscores);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        // in the board X sent it to
+        let cells = app.game.boards[4].cells.iter().flatten();
+        assert_eq!(cells.filter(|cell| **cell == Mark::O).count(), 1);
+    }
+
+    #[test]
+    fn app_snapshot() {
+        let mut app = ui::UltimateApp::new();
+        app.mode.selected = 0;
+        play(
+            &mut app.game,
+            &[(4, 0), (0, 4), (4, 1), (1, 4), (4, 2), (2, 4)],
+        );
+
+        let mut r = RecordingRenderer::new(DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64);
+        app.draw(&mut r, &Config::new(), &HighScores::new());
+        assert_snapshot("ultimate", &r);
+        assert!(r.texts().contains(&"X boards: 1"));
+    }
+}
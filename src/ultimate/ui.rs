@@ -0,0 +1,299 @@
+use super::{search, Game, MAX_DEPTH};
+use crate::components::application::MiniApp;
+use crate::components::{
+    button::{draw_text, ButtonStyle, Pos, UIButton},
+    layout::{Anchor, GameLayout, Rect},
+    render::Renderer,
+    widgets::{Dropdown, Focus, Response},
+};
+use crate::menu::{
+    config::Config,
+    highscores::HighScores,
+    stats::{GameRecord, Outcome},
+};
+use crate::tictactoe::{
+    ui::{draw_mark, mark_to_clr},
+    BackgroundSearch, Mark, MOVE_TIME,
+};
+use crate::Event;
+use piston_window::*;
+use std::time::Instant;
+
+pub struct UltimateApp {
+    pub game: Game,
+    /// The hovered cell, as small board and cell
+    pub hover: Option<(usize, usize)>,
+    /// Who plays against whom, the index is the side the AI plays
+    pub mode: Dropdown,
+    reset: UIButton,
+    focus: Focus,
+    started: Instant,
+    /// The AI's move while it is searched for
+    thinking: Option<BackgroundSearch>,
+}
+
+impl UltimateApp {
+    pub fn new() -> Self {
+        UltimateApp {
+            game: Game::new(),
+            hover: None,
+            mode: mode_dropdown(),
+            reset: UIButton::new("     Reset", ButtonStyle::Danger, 24),
+            focus: Focus::new(),
+            started: Instant::now(),
+            thinking: None,
+        }
+    }
+
+    // 0 when nobody is the AI, otherwise the AI's mark + 1
+    fn playing_ai(&self) -> u8 {
+        self.mode.selected as u8
+    }
+
+    // Starts a new game, the AI moves first when it plays X
+    fn restart(&mut self) {
+        // dropping the search stops it
+        self.thinking = None;
+        self.game = Game::new();
+        self.started = Instant::now();
+        self.start_ai();
+    }
+
+    // Starts the AI's search when it is the AI's turn
+    fn start_ai(&mut self) {
+        let over = self.game.winner() != Mark::None || self.game.is_draw();
+        if !over && self.playing_ai() == (self.game.turn as u8 + 1) {
+            let game = self.game.clone();
+            self.thinking = Some(BackgroundSearch::spawn(move |stop| {
+                search(&game, MAX_DEPTH, MOVE_TIME, stop)
+            }));
+        }
+    }
+
+    // Records the game once it is over
+    fn record_result(&self, highscores: &mut HighScores) {
+        let winner = self.game.winner();
+        if winner != Mark::None || self.game.is_draw() {
+            let outcome = if winner == Mark::None {
+                Outcome::Draw
+            } else if self.playing_ai() == winner as u8 + 1 {
+                // the AI won
+                Outcome::Lost
+            } else {
+                Outcome::Won
+            };
+            highscores
+                .stats
+                .record(GameRecord::new("ultimate", outcome, self.started));
+            highscores.save_scores();
+        }
+    }
+}
+
+fn mode_dropdown() -> Dropdown {
+    let modes = ["Man vs Man", "O vs AI", "X vs AI"];
+    let mut dropdown = Dropdown::new("Mode", modes.map(String::from).to_vec(), 2);
+    dropdown.font_size = 16;
+    dropdown
+}
+
+// Height of the line below the board that shows the result
+const RESULT_HEIGHT: f64 = 60.0;
+
+// Where the big board is drawn, leaving room for the result below it
+fn grid_rect(size: [f64; 2]) -> Rect {
+    let (_, area) = GameLayout::new(size).board.split_bottom(RESULT_HEIGHT);
+    area.square()
+}
+
+// The rect of a small board, with a gap to the next ones
+fn board_rect(grid: Rect, board: usize) -> Rect {
+    grid.cell(3, 3, board % 3, board / 3).pad(grid.w / 60.0)
+}
+
+fn cell_rect(grid: Rect, board: usize, cell: usize) -> Rect {
+    board_rect(grid, board).cell(3, 3, cell % 3, cell / 3)
+}
+
+impl MiniApp for UltimateApp {
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        size: [f64; 2],
+        _config: &mut Config,
+        highscores: &mut HighScores,
+    ) {
+        // handle button events
+        let controls = GameLayout::new(size).buttons(2);
+        let response = self.focus.dispatch(
+            event,
+            &mut [
+                (&mut self.reset, controls[0]),
+                (&mut self.mode, controls[1]),
+            ],
+        );
+        match response {
+            Some((0, Response::Clicked)) => self.restart(),
+            Some((1, Response::Picked(_))) => {
+                // a search for the side that isn't the AI anymore is dropped
+                self.thinking = None;
+                self.start_ai();
+            }
+            _ => (),
+        }
+        // the board only gets clicks the widgets didn't use
+        let left_click =
+            response.is_none() && event.press_args() == Some(Button::Mouse(MouseButton::Left));
+        let [cx, cy] = self.focus.cursor();
+
+        let grid = grid_rect(size);
+        self.hover = (0..9)
+            .flat_map(|board| (0..9).map(move |cell| (board, cell)))
+            .find(|(board, cell)| cell_rect(grid, *board, *cell).contains(cx, cy));
+
+        // the board is locked while the AI thinks
+        if let Some((board, cell)) = self.hover.filter(|_| left_click && self.thinking.is_none()) {
+            if self.game.make_move(board, cell).is_none() {
+                self.record_result(highscores);
+                // if playing against AI, let it search for its move
+                self.start_ai();
+            }
+        }
+    }
+
+    fn update(&mut self, _dt: f64, _config: &mut Config, highscores: &mut HighScores) {
+        let move_ = self.thinking.as_ref().and_then(BackgroundSearch::poll);
+        if let Some((board, cell)) = move_ {
+            self.thinking = None;
+            self.game.make_move(board, cell);
+            self.record_result(highscores);
+        }
+    }
+
+    fn draw(&self, r: &mut dyn Renderer, config: &Config, _highscores: &HighScores) {
+        let size = r.size();
+        let layout = GameLayout::new(size);
+        let sidebar = layout.sidebar;
+        let palette = &config.theme.tictactoe;
+        let (result_area, _) = layout.board.split_bottom(RESULT_HEIGHT);
+        let result_pos = |width: f64| {
+            let rect = result_area.anchor(Anchor::Center, width, 32.0);
+            Pos {
+                x: rect.x,
+                y: rect.y + rect.h,
+            }
+        };
+        let grid = grid_rect(size);
+
+        r.clear(config.theme.game_background);
+
+        // Draw texts
+        let winner = self.game.winner();
+        if winner == Mark::X {
+            draw_text(r, config.theme.text, result_pos(100.0), "X wins!", 32);
+        } else if winner == Mark::O {
+            draw_text(r, config.theme.text, result_pos(104.0), "O wins!", 32);
+        } else if self.game.is_draw() {
+            draw_text(r, config.theme.text, result_pos(140.0), "It's a draw!", 32);
+        } else if self.thinking.is_some() {
+            draw_text(
+                r,
+                config.theme.text,
+                result_pos(190.0),
+                "AI is thinking...",
+                32,
+            );
+        }
+
+        {
+            // Draw the STM, as big as a cell of the TicTacToe board
+            let side = grid.w / 3.0 / 1.4;
+            let (_, below) = sidebar.split_top(100.0);
+            let rect = below.anchor(Anchor::Top, side, side).to_array();
+            r.rectangle(mark_to_clr(self.game.turn, palette), rect);
+            if config.options.shape_cues {
+                draw_mark(r, self.game.turn, rect, palette);
+            }
+        }
+
+        for board in 0..9 {
+            // the boards the next move can go in stand out
+            let area = board_rect(grid, board);
+            if self.game.playable(board) {
+                let highlight = area.pad(-grid.w / 120.0);
+                r.rectangle(config.theme.accent, highlight.to_array());
+            }
+
+            for cell in 0..9 {
+                let mark = self.game.boards[board].cells[cell / 3][cell % 3];
+                let rect = cell_rect(grid, board, cell);
+                let margin = if self.hover == Some((board, cell)) {
+                    rect.w / 28.0
+                } else {
+                    rect.w / 16.0
+                };
+                let rect = math::margin_rectangle(rect.to_array(), margin);
+                r.rectangle(mark_to_clr(mark, palette), rect);
+                if config.options.shape_cues {
+                    draw_mark(r, mark, rect, palette);
+                }
+            }
+
+            // a claimed board is covered by the mark of its winner
+            let claimed = self.game.big.cells[board / 3][board % 3];
+            if claimed != Mark::None {
+                let [red, green, blue, _] = mark_to_clr(claimed, palette);
+                r.rectangle([red, green, blue, 0.85], area.to_array());
+                if config.options.shape_cues {
+                    draw_mark(r, claimed, area.to_array(), palette);
+                }
+            }
+        }
+
+        {
+            // count the claimed boards
+            let claimed = |mark| {
+                let cells = self.game.big.cells.iter().flatten();
+                cells.filter(|cell| **cell == mark).count()
+            };
+            draw_text(
+                r,
+                config.theme.text,
+                Pos {
+                    x: sidebar.x,
+                    y: sidebar.y + 300.0,
+                },
+                &format!("X boards: {}", claimed(Mark::X)),
+                28,
+            );
+            draw_text(
+                r,
+                config.theme.text,
+                Pos {
+                    x: sidebar.x,
+                    y: sidebar.y + 340.0,
+                },
+                &format!("O boards: {}", claimed(Mark::O)),
+                28,
+            );
+            draw_text(
+                r,
+                config.theme.text,
+                Pos {
+                    x: sidebar.x,
+                    y: sidebar.y + 380.0,
+                },
+                "Play in a highlighted board",
+                16,
+            );
+        }
+
+        // Draw buttons last, so the open list is on top
+        let controls = layout.buttons(2);
+        self.focus.draw(
+            r,
+            &config.theme,
+            &[(&self.reset, controls[0]), (&self.mode, controls[1])],
+        );
+    }
+}
@@ -33,7 +33,7 @@ impl Game {
         }
     }
 
-    pub fn guess(&mut self, guess: &String) -> Result<GuessResult, GuessError> {
+    pub fn guess(&mut self, guess: &String) -> Result<GuessResult, GuessError<'_>> {
         if guess.len() != 5 {
             return Err(GuessError::NotLongEnough);
         }
@@ -5,6 +5,12 @@ pub mod ui;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+    use crate::components::render::{
+        recording::{assert_snapshot, DrawCommand},
+        RecordingRenderer,
+    };
+    use crate::menu::{config::Config, highscores::HighScores};
 
     #[test]
     fn word_was_not_in_list() {
@@ -23,4 +29,34 @@ mod tests {
             Ok(GuessResult::Right)
         );
     }
+
+    #[test]
+    fn results_have_corner_markers() {
+        let mut app = ui::WordleApp::new();
+        let word = app.state.word.to_string();
+        app.state.guess(&word).unwrap();
+
+        let config = Config::new();
+        let mut r = RecordingRenderer::new(DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64);
+        app.draw(&mut r, &config, &HighScores::new());
+
+        // one filled corner for every letter in the right place
+        let markers = r
+            .commands
+            .iter()
+            .filter(|command| {
+                matches!(command, DrawCommand::Rectangle { color, .. } if *color == config.theme.wordle.letter)
+            })
+            .count();
+        assert_eq!(markers, 5);
+    }
+
+    #[test]
+    fn app_snapshot() {
+        let app = ui::WordleApp::new();
+
+        let mut r = RecordingRenderer::new(DEFAULT_WIDTH as f64, DEFAULT_HEIGHT as f64);
+        app.draw(&mut r, &Config::new(), &HighScores::new());
+        assert_snapshot("wordle", &r);
+    }
 }
@@ -1,296 +1,283 @@
 use super::{CharGuess, Game, GuessError, GuessResult, GuessType};
-use crate::components::application::{MiniApp, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use crate::components::application::MiniApp;
 use crate::components::{
-    button::{draw_text, Pos, UIButton},
-    color::Color,
+    button::{draw_text, ButtonStyle, Pos, UIButton},
+    layout::{Anchor, GameLayout, Rect},
+    render::Renderer,
+    widgets::{Focus, Response, TextInput, Widget, WidgetState},
 };
-use crate::menu::{config::Config, highscores::HighScores, ui::TOP_PAD};
-use crate::{rgb, Event};
+use crate::menu::{
+    config::Config,
+    highscores::HighScores,
+    stats::{GameRecord, Outcome},
+    theme::WordlePalette,
+};
+use crate::Event;
 use piston_window::*;
+use std::time::Instant;
 
 pub struct WordleApp {
-    state: Game,
-    guess: String,
-    hover_pos: [f64; 2],
+    pub state: Game,
+    guess: TextInput,
+    focus: Focus,
+    reset: UIButton,
     prev_text: Option<String>,
     first_result: bool,
+    started: Instant,
 }
 
 impl WordleApp {
     pub fn new() -> Self {
         WordleApp {
             state: Game::new(),
-            guess: String::new(),
-            hover_pos: [0.0, 0.0],
+            guess: TextInput::new(5)
+                .with_filter(|c| Some(c.to_ascii_uppercase()).filter(char::is_ascii_alphabetic)),
+            focus: Focus::new(),
+            reset: UIButton::new("     Reset", ButtonStyle::Danger, 24),
             prev_text: None,
             first_result: true,
+            started: Instant::now(),
         }
     }
 }
 
-const BOARD_SIZE: f64 = DEFAULT_HEIGHT as f64 - 100.0;
-const CENTER_X: f64 = (DEFAULT_WIDTH as f64 - BOARD_SIZE) / 2.0;
-const SQUARE_SIZE: f64 = BOARD_SIZE / 6.5;
+// Where the tiles are drawn, 5 letters wide and 6 guesses high
+fn tiles_rect(size: [f64; 2]) -> Rect {
+    let board = GameLayout::new(size).board;
+    let side = (board.w / 5.0).min(board.h / 6.0);
+    board.anchor(Anchor::Top, side * 5.0, side * 6.0)
+}
 
 // Converts Guess to Color
-fn guess_to_clr(guess: CharGuess) -> [f32; 4] {
+fn guess_to_clr(guess: CharGuess, palette: &WordlePalette) -> [f32; 4] {
+    match guess.type_ {
+        GuessType::Correct => palette.correct,
+        GuessType::OutOfOrder => palette.present,
+        GuessType::Incorrect => palette.absent,
+    }
+}
+
+// Marks the top right corner of a tile so the result doesn't depend on colours alone,
+// filled for the right place and outlined for the wrong place
+fn draw_marker(r: &mut dyn Renderer, guess: CharGuess, rect: [f64; 4], palette: &WordlePalette) {
+    let size = rect[2] / 6.0;
+    let corner = [rect[0] + rect[2] - size - 4.0, rect[1] + 4.0, size, size];
     match guess.type_ {
-        GuessType::Correct => rgb!(77, 143, 69),
-        GuessType::OutOfOrder => rgb!(212, 189, 59),
-        GuessType::Incorrect => rgb!(128, 128, 128),
+        GuessType::Correct => r.rectangle(palette.letter, corner),
+        GuessType::OutOfOrder => r.border_rectangle(palette.letter, 1.0, corner),
+        GuessType::Incorrect => (),
     }
 }
 
+// The guess is typed in all the time, the tiles show it instead of the input
+const GUESS_STATE: WidgetState = WidgetState {
+    cursor: [0.0; 2],
+    hovered: false,
+    focused: true,
+};
+
 impl MiniApp for WordleApp {
-    fn render(
+    fn handle_event(
         &mut self,
-        window: &mut PistonWindow,
         event: &Event,
-        glyphs: &mut Glyphs,
-        config: &mut Config,
+        size: [f64; 2],
+        _config: &mut Config,
         highscores: &mut HighScores,
     ) {
-        if let Some([cx, cy]) = event.mouse_cursor_args() {
-            self.hover_pos = [cx, cy];
-        }
-
-        // init buttons
-        let mut reset_button = UIButton::new(
-            "     Reset",
-            Color::RESET,
-            Color::WHITE,
-            24,
-            Pos { x: 791.2, y: 135.2 },
-            160.0,
-            48.0,
-        );
-
-        let left_click = event.press_args() == Some(Button::Mouse(MouseButton::Left));
-
-        // handle button events
-        if reset_button.is_over(self.hover_pos[0], self.hover_pos[1]) {
-            if left_click {
+        let reset = GameLayout::new(size).buttons(1)[0];
+        match self.focus.dispatch(event, &mut [(&mut self.reset, reset)]) {
+            Some((_, Response::Clicked)) => {
                 self.prev_text = None;
                 self.state.reset();
-                self.guess.clear();
+                self.guess.text.clear();
                 self.first_result = true;
-            } else {
-                reset_button.width += 6.0;
-                reset_button.pos.x -= 3.0;
-                reset_button.height += 6.0;
-                reset_button.pos.y -= 3.0;
-                reset_button.size += 1;
+                self.started = Instant::now();
+                // typing goes back to the guess
+                self.focus.set_focused(None);
+                return;
             }
+            Some(_) => return,
+            None => (),
         }
 
-        if let Some(Button::Keyboard(press)) = event.press_args() {
-            match press {
-                Key::Backspace | Key::Delete => {
-                    self.guess.pop();
-                    self.prev_text = None;
-                }
-                Key::Return => {
-                    let result = self.state.guess(&self.guess);
-                    match result {
-                        Err(GuessError::GameOver(_)) => {
-                            self.prev_text = Some("You ran out of tries!".to_string());
-
-                            // update highscores
-                            if self.first_result {
-                                self.first_result = false;
-                                highscores.scores.wordle = 0;
-                                highscores.save_scores();
-                            }
-                        }
-                        Err(error) => {
-                            self.prev_text = Some(error.to_string());
-                        }
-                        Ok(res) => {
-                            self.prev_text = match res {
-                                GuessResult::Right => {
-                                    // update highscores
-                                    if self.first_result {
-                                        self.first_result = false;
-                                        highscores.scores.wordle += 1;
-                                        highscores.save_scores();
-                                    }
+        let tiles = tiles_rect(size);
+        match self.guess.handle_event(event, tiles, GUESS_STATE) {
+            Response::Changed => self.prev_text = None,
+            Response::Submitted => {
+                let result = self.state.guess(&self.guess.text);
+                match result {
+                    Err(GuessError::GameOver(_)) => {
+                        self.prev_text = Some("You ran out of tries!".to_string());
 
-                                    Some("You won!".to_string())
-                                }
-                                GuessResult::Wrong => None,
-                            };
-                            self.guess.clear();
+                        // update highscores
+                        if self.first_result {
+                            self.first_result = false;
+                            highscores.scores.wordle = 0;
+                            highscores.stats.record(GameRecord::new(
+                                "wordle",
+                                Outcome::Lost,
+                                self.started,
+                            ));
+                            highscores.save_scores();
                         }
                     }
-                }
-                _ => {
-                    if self.guess.len() < 5 {
-                        let character: char = unsafe { std::mem::transmute(press) };
-                        if character.is_ascii_alphabetic() {
-                            self.guess.push(character.to_ascii_uppercase());
-                        }
-                        self.prev_text = None;
+                    Err(error) => {
+                        self.prev_text = Some(error.to_string());
+                    }
+                    Ok(res) => {
+                        self.prev_text = match res {
+                            GuessResult::Right => {
+                                // update highscores
+                                if self.first_result {
+                                    self.first_result = false;
+                                    highscores.scores.wordle += 1;
+                                    let guesses = self
+                                        .state
+                                        .guesses()
+                                        .iter()
+                                        .filter(|guess| guess.is_some())
+                                        .count();
+                                    highscores.stats.record(GameRecord {
+                                        guesses: Some(guesses as u32),
+                                        ..GameRecord::new("wordle", Outcome::Won, self.started)
+                                    });
+                                    highscores.save_scores();
+                                }
+
+                                Some("You won!".to_string())
+                            }
+                            GuessResult::Wrong => None,
+                        };
+                        self.guess.text.clear();
                     }
                 }
             }
+            _ => (),
         }
+    }
 
-        window.draw_2d(event, |c, g, device| {
-            clear(
-                if config.options.white_theme {
-                    Color::WHITE
-                } else {
-                    Color::DARK_THEME_BG
-                },
-                g,
-            );
+    fn draw(&self, r: &mut dyn Renderer, config: &Config, highscores: &HighScores) {
+        let size = r.size();
+        let layout = GameLayout::new(size);
+        let sidebar = layout.sidebar;
 
-            // draw buttons
-            reset_button.draw(&c, g, glyphs);
+        r.clear(config.theme.game_background);
 
-            // draw highscores
-            draw_text(
-                &c,
-                g,
-                glyphs,
-                if config.options.white_theme {
-                    Color::BLACK
-                } else {
-                    // black
-                    Color::WHITE
-                },
-                Pos { x: 10.0, y: 400.0 },
-                &format!("Win streak: {}", highscores.scores.wordle),
-                28,
-            );
+        // draw buttons
+        self.focus
+            .draw(r, &config.theme, &[(&self.reset, layout.buttons(1)[0])]);
 
-            // draw win/lose/error text
-            if let Some(ref text) = self.prev_text {
-                if text == "You ran out of tries!" {
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        Color::LOSE_TEXT,
-                        Pos { x: 10.0, y: 200.0 },
-                        text,
-                        24,
-                    );
-                    // reveal the word
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        rgb!(77, 143, 69),
-                        Pos { x: 10.0, y: 225.0 },
-                        format!("The word was {}", self.state.word).as_str(),
-                        24,
-                    );
-                } else if text == "You won!" {
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        Color::WIN_TEXT,
-                        Pos { x: 10.0, y: 200.0 },
-                        text,
-                        20,
-                    );
-                } else {
-                    draw_text(
-                        &c,
-                        g,
-                        glyphs,
-                        rgb!(212, 189, 59),
-                        Pos { x: 10.0, y: 200.0 },
-                        text,
-                        20,
-                    );
-                }
+        // draw highscores
+        draw_text(
+            r,
+            config.theme.text,
+            Pos {
+                x: sidebar.x,
+                y: sidebar.y + 300.0,
+            },
+            &format!("Win streak: {}", highscores.scores.wordle),
+            28,
+        );
+
+        // draw win/lose/error text
+        if let Some(ref text) = self.prev_text {
+            if text == "You ran out of tries!" {
+                draw_text(
+                    r,
+                    config.theme.lose_text,
+                    Pos {
+                        x: sidebar.x,
+                        y: sidebar.y + 100.0,
+                    },
+                    text,
+                    24,
+                );
+                // reveal the word
+                draw_text(
+                    r,
+                    config.theme.wordle.correct,
+                    Pos {
+                        x: sidebar.x,
+                        y: sidebar.y + 125.0,
+                    },
+                    format!("The word was {}", self.state.word).as_str(),
+                    24,
+                );
+            } else if text == "You won!" {
+                draw_text(
+                    r,
+                    config.theme.win_text,
+                    Pos {
+                        x: sidebar.x,
+                        y: sidebar.y + 100.0,
+                    },
+                    text,
+                    20,
+                );
+            } else {
+                draw_text(
+                    r,
+                    config.theme.wordle.present,
+                    Pos {
+                        x: sidebar.x,
+                        y: sidebar.y + 100.0,
+                    },
+                    text,
+                    20,
+                );
             }
+        }
 
-            // Draw the board
-            let ctx = c.trans(CENTER_X + 80.0, TOP_PAD);
+        // Draw the board
+        let tiles = tiles_rect(size);
+        // letters grow with the tiles
+        let font_size = (tiles.w / 5.0 / 2.5) as u32;
 
-            let mut first = true;
-            for (y, guesses) in self.state.guesses().iter().enumerate() {
-                if let Some(guesses) = guesses {
-                    for (x, char_guess) in guesses.result().iter().enumerate() {
-                        let clr = guess_to_clr(*char_guess);
-                        let rect = math::margin_rectangle(
-                            [
-                                SQUARE_SIZE * (x as f64),
-                                SQUARE_SIZE * (y as f64),
-                                SQUARE_SIZE,
-                                SQUARE_SIZE,
-                            ],
-                            4.0,
-                        );
-                        Rectangle::new_round(clr, 2.0).draw(
-                            rect,
-                            &Default::default(),
-                            ctx.transform,
-                            g,
-                        );
-                        draw_text(
-                            &ctx,
-                            g,
-                            glyphs,
-                            Color::WHITE,
-                            Pos {
-                                x: rect[0] + SQUARE_SIZE / 4.0 + 2.0,
-                                y: rect[1] + SQUARE_SIZE / 2.0 + 5.0,
-                            },
-                            &char_guess.char.to_string(),
-                            30,
-                        );
+        let mut first = true;
+        for (y, guesses) in self.state.guesses().iter().enumerate() {
+            if let Some(guesses) = guesses {
+                for (x, char_guess) in guesses.result().iter().enumerate() {
+                    let clr = guess_to_clr(*char_guess, &config.theme.wordle);
+                    let cell = tiles.cell(5, 6, x, y);
+                    let rect = math::margin_rectangle(cell.to_array(), 4.0);
+                    r.round_rectangle(clr, 2.0, rect);
+                    if config.options.shape_cues {
+                        draw_marker(r, *char_guess, rect, &config.theme.wordle);
                     }
-                } else {
-                    for x in 0..5 {
-                        let rect = math::margin_rectangle(
-                            [
-                                SQUARE_SIZE * (x as f64),
-                                SQUARE_SIZE * (y as f64),
-                                SQUARE_SIZE,
-                                SQUARE_SIZE,
-                            ],
-                            4.0,
-                        );
-                        Rectangle::new_border(rgb!(211, 211, 211), 2.0).draw(
-                            rect,
-                            &Default::default(),
-                            ctx.transform,
-                            g,
-                        );
-                        Rectangle::new(rgb!(100, 100, 100)).draw(
-                            rect,
-                            &Default::default(),
-                            ctx.transform,
-                            g,
-                        );
-                        if first {
-                            if let Some(&char) = self.guess.as_bytes().get(x) {
-                                draw_text(
-                                    &ctx,
-                                    g,
-                                    glyphs,
-                                    Color::WHITE,
-                                    Pos {
-                                        x: rect[0] + SQUARE_SIZE / 4.0 + 2.0,
-                                        y: rect[1] + SQUARE_SIZE / 2.0 + 5.0,
-                                    },
-                                    &(char as char).to_string(),
-                                    30,
-                                );
-                            }
+                    draw_text(
+                        r,
+                        config.theme.wordle.letter,
+                        Pos {
+                            x: rect[0] + cell.w / 4.0 + 2.0,
+                            y: rect[1] + cell.h / 2.0 + 5.0,
+                        },
+                        &char_guess.char.to_string(),
+                        font_size,
+                    );
+                }
+            } else {
+                for x in 0..5 {
+                    let cell = tiles.cell(5, 6, x, y);
+                    let rect = math::margin_rectangle(cell.to_array(), 4.0);
+                    r.border_rectangle(config.theme.wordle.border, 2.0, rect);
+                    r.rectangle(config.theme.wordle.empty, rect);
+                    if first {
+                        if let Some(&char) = self.guess.text.as_bytes().get(x) {
+                            draw_text(
+                                r,
+                                config.theme.wordle.letter,
+                                Pos {
+                                    x: rect[0] + cell.w / 4.0 + 2.0,
+                                    y: rect[1] + cell.h / 2.0 + 5.0,
+                                },
+                                &(char as char).to_string(),
+                                font_size,
+                            );
                         }
                     }
-                    first = false;
                 }
+                first = false;
             }
-
-            // Update glyphs before rendering
-            glyphs.factory.encoder.flush(device);
-        });
+        }
     }
 }